@@ -0,0 +1,343 @@
+//validates src/shaders.wgsl and generates the metal source and cpu dispatch table from it, on any host
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::{env, fs, process};
+
+use naga::back::msl;
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use naga::{Binding, BuiltIn};
+
+use eval::{Evaluator, Value, struct_value};
+
+#[path = "build/eval.rs"]
+mod eval;
+
+const SHADER_PATH: &str = "src/shaders.wgsl";
+const PORTS_PATH: &str = "src/software.rs";
+//the renderers that look shader functions up by name when building their pipelines
+const FRONTEND_PATHS: [&str; 3] = ["src/appkit.rs", "src/gpu.rs", PORTS_PATH];
+//the line above each cpu port, followed by the fingerprint of the wgsl it was ported from
+const PORTED_MARK: &str = "//ported from shaders.wgsl ";
+
+fn fail(message: String) -> ! {
+    eprintln!("{message}");
+    process::exit(1);
+}
+
+//the text of a wgsl function from fn to its closing brace
+fn function_source<'a>(source: &'a str, name: &str) -> Option<&'a str> {
+    let start = source.find(&format!("fn {name}("))?;
+    let open = start + source[start..].find('{')?;
+    let mut depth = 0;
+    for (offset, c) in source[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&source[start..=open + offset]);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+//fnv-1a over a fragment and the helpers it calls, comments and spacing don't count
+fn fingerprint(source: &str, entry: &str, helpers: &[&str]) -> u32 {
+    let mut names = vec![entry];
+    let mut text = String::new();
+    let mut index = 0;
+    while let Some(name) = names.get(index) {
+        let function = function_source(source, name).unwrap_or_default();
+        for line in function.lines() {
+            let code = line.split("//").next().unwrap_or_default();
+            text.extend(code.split_whitespace().map(|word| format!("{word} ")));
+        }
+        for helper in helpers {
+            if !names.contains(helper) && function.contains(&format!("{helper}(")) {
+                names.push(helper);
+            }
+        }
+        index += 1;
+    }
+    text.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+//every fragment port has to carry the fingerprint of the wgsl it matches, so a shader edit
+//stops the build until the port is brought along, the software.rs tests check it really was
+fn check_ports(source: &str, module: &naga::Module) {
+    let ports = fs::read_to_string(PORTS_PATH)
+        .unwrap_or_else(|error| fail(format!("couldn't read {PORTS_PATH}: {error}")));
+    let mut recorded = BTreeMap::new();
+    let mut lines = ports.lines();
+    while let Some(line) = lines.next() {
+        let Some(hex) = line.trim().strip_prefix(PORTED_MARK) else {
+            continue;
+        };
+        let name = lines
+            .next()
+            .and_then(|next| next.trim().strip_prefix("pub fn "))
+            .and_then(|next| next.split('(').next());
+        if let (Some(name), Ok(hash)) = (name, u32::from_str_radix(hex.trim(), 16)) {
+            recorded.insert(name.to_string(), hash);
+        }
+    }
+    let helpers: Vec<&str> = module
+        .functions
+        .iter()
+        .filter_map(|(_, function)| function.name.as_deref())
+        .collect();
+    let stale: Vec<String> = module
+        .entry_points
+        .iter()
+        .filter(|entry| entry.stage == naga::ShaderStage::Fragment)
+        .filter_map(|entry| {
+            let hash = fingerprint(source, &entry.name, &helpers);
+            match recorded.get(&entry.name) {
+                Some(&ported) if ported == hash => None,
+                Some(_) => Some(format!(
+                    "{} changed in {SHADER_PATH}, update its port in {PORTS_PATH} and mark it {PORTED_MARK}{hash:08x}",
+                    entry.name
+                )),
+                None => Some(format!(
+                    "{} has no port marked {PORTED_MARK}{hash:08x} in {PORTS_PATH}",
+                    entry.name
+                )),
+            }
+        })
+        .collect();
+    if !stale.is_empty() {
+        fail(stale.join("\n"));
+    }
+}
+
+//every quoted *_vertex or *_fragment name a renderer asks for has to be an entry point of that
+//stage, so a pipeline naming a function the shader doesn't have fails here instead of at startup
+fn check_entry_points(module: &naga::Module) {
+    let mut missing = Vec::new();
+    for path in FRONTEND_PATHS {
+        let frontend = fs::read_to_string(path)
+            .unwrap_or_else(|error| fail(format!("couldn't read {path}: {error}")));
+        //every other piece between quotes is inside a string literal
+        for name in frontend.split('"').skip(1).step_by(2) {
+            let stage = if name.ends_with("_vertex") {
+                naga::ShaderStage::Vertex
+            } else if name.ends_with("_fragment") {
+                naga::ShaderStage::Fragment
+            } else {
+                continue;
+            };
+            if !name.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+                continue;
+            }
+            let found = module
+                .entry_points
+                .iter()
+                .any(|entry| entry.name == name && entry.stage == stage);
+            if !found {
+                missing.push(format!(
+                    "{path} uses {name}, which is not a {stage:?} entry point in {SHADER_PATH}"
+                ));
+            }
+        }
+    }
+    if !missing.is_empty() {
+        fail(missing.join("\n"));
+    }
+}
+
+//the uniforms and fragment inputs every fragment function is evaluated at, flattened as
+//[player x, player y, radius, signal_lost] and [position x, y, color rgba, uv xyzw]
+fn samples() -> Vec<([f32; 4], [f32; 10])> {
+    let shadings = [[24.0, 24.0, 24.0, 0.0], [10.0, 30.0, 40.0, 0.45]];
+    let colors = [[1.0, 0.25, 0.0, 1.0], [0.2, 0.6, 0.9, 0.7]];
+    //off the half and quarter marks so no sample sits exactly on a shape's edge
+    let grid: Vec<f32> = (0..8).map(|i| (i as f32 + 0.37) / 8.0).collect();
+    let mut samples = Vec::new();
+    for shading in shadings {
+        for color in colors {
+            for &u in grid.iter() {
+                for &v in grid.iter() {
+                    for data in [0.0, 0.35, 0.8, 1.3] {
+                        let [r, g, b, a] = color;
+                        let fragment = [u * 48.0, v * 48.0, r, g, b, a, u, v, data, 1.0];
+                        samples.push((shading, fragment));
+                    }
+                }
+            }
+        }
+    }
+    samples
+}
+
+fn float_literal(value: f32) -> String {
+    match value {
+        value if value.is_nan() => "f32::NAN".to_string(),
+        value if value.is_infinite() => {
+            format!("{}f32::INFINITY", if value < 0.0 { "-" } else { "" })
+        }
+        value => format!("{value:?}"),
+    }
+}
+
+fn floats_literal(values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(|&value| float_literal(value)).collect();
+    format!("[{}]", values.join(", "))
+}
+
+//runs every fragment function at the samples through the naga ir, software.rs tests its ports
+//against the results so a port that drifts from the shader fails whatever its fingerprint says
+fn fragment_samples(module: &naga::Module) -> String {
+    let samples = samples();
+    let vec = |values: &[f32]| Value::Composite(values.iter().map(|&v| Value::Float(v)).collect());
+    let mut outputs = Vec::new();
+    for entry in module
+        .entry_points
+        .iter()
+        .filter(|entry| entry.stage == naga::ShaderStage::Fragment)
+    {
+        let mut results = Vec::new();
+        for (shading, fragment) in samples.iter() {
+            let globals = module
+                .global_variables
+                .iter()
+                .map(|(handle, global)| {
+                    let uniforms = struct_value(module, global.ty, |member| match member {
+                        //fragments see the position after the transform, the matrix isn't read
+                        "view_projection" => Some(Value::Composite(vec![vec(&[0.0; 4]); 4])),
+                        "player" => Some(vec(&shading[..2])),
+                        "radius" => Some(Value::Float(shading[2])),
+                        "signal_lost" => Some(Value::Float(shading[3])),
+                        _ => None,
+                    });
+                    uniforms.map(|value| (handle, value))
+                })
+                .collect::<Result<HashMap<_, _>, _>>()
+                .unwrap_or_else(|error| fail(format!("{SHADER_PATH}: {error}")));
+            let evaluator = Evaluator::new(module, globals);
+            let output = evaluator
+                .fragment(&entry.name, |binding| match binding {
+                    Binding::BuiltIn(BuiltIn::Position { .. }) => {
+                        Some(vec(&[fragment[0], fragment[1], 0.5, 1.0]))
+                    }
+                    Binding::Location { location: 0, .. } => Some(vec(&fragment[2..6])),
+                    Binding::Location { location: 1, .. } => Some(vec(&fragment[6..10])),
+                    _ => None,
+                })
+                .and_then(|output| {
+                    output
+                        .map(|color| match color {
+                            Value::Composite(items) if items.len() == 4 => items
+                                .iter()
+                                .map(Value::float)
+                                .collect::<Result<Vec<f32>, _>>(),
+                            other => Err(format!("returned {other:?}")),
+                        })
+                        .transpose()
+                })
+                .unwrap_or_else(|error| {
+                    fail(format!("{SHADER_PATH}: evaluating {}: {error}", entry.name))
+                });
+            results.push(match output {
+                Some(color) => format!("Some({})", floats_literal(&color)),
+                None => "None".to_string(),
+            });
+        }
+        outputs.push(format!(
+            "    (\"{}\", [{}]),\n",
+            entry.name,
+            results.join(", ")
+        ));
+    }
+    let inputs: Vec<String> = samples
+        .iter()
+        .map(|(shading, fragment)| {
+            format!(
+                "    ({}, {}),\n",
+                floats_literal(shading),
+                floats_literal(fragment)
+            )
+        })
+        .collect();
+    format!(
+        "pub static FRAGMENT_INPUTS: [([f32; 4], [f32; 10]); {0}] = [\n{1}];\n\
+         pub static FRAGMENT_OUTPUTS: [(&str, [Option<[f32; 4]>; {0}]); {2}] = [\n{3}];\n",
+        samples.len(),
+        inputs.concat(),
+        outputs.len(),
+        outputs.concat()
+    )
+}
+
+fn main() {
+    println!("cargo::rerun-if-changed={SHADER_PATH}");
+    for path in FRONTEND_PATHS {
+        println!("cargo::rerun-if-changed={path}");
+    }
+    let source = fs::read_to_string(SHADER_PATH)
+        .unwrap_or_else(|error| fail(format!("couldn't read {SHADER_PATH}: {error}")));
+
+    let module = naga::front::wgsl::parse_str(&source)
+        .unwrap_or_else(|error| fail(error.emit_to_string_with_path(&source, SHADER_PATH)));
+    let info = Validator::new(ValidationFlags::all(), Capabilities::empty())
+        .validate(&module)
+        .unwrap_or_else(|error| fail(error.emit_to_string_with_path(&source, SHADER_PATH)));
+
+    //the uniforms sit in buffer 0 for every stage, vertices arrive through a vertex descriptor
+    let uniforms = naga::ResourceBinding {
+        group: 0,
+        binding: 0,
+    };
+    let resources = msl::EntryPointResources {
+        resources: BTreeMap::from([(
+            uniforms,
+            msl::BindTarget {
+                buffer: Some(0),
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    };
+    let options = msl::Options {
+        lang_version: (2, 0),
+        per_entry_point_map: module
+            .entry_points
+            .iter()
+            .map(|entry| (entry.name.clone(), resources.clone()))
+            .collect(),
+        fake_missing_bindings: false,
+        ..Default::default()
+    };
+    let (metal, _) = msl::write_string(&module, &info, &options, &msl::PipelineOptions::default())
+        .unwrap_or_else(|error| fail(format!("{SHADER_PATH}: metal translation failed: {error}")));
+
+    check_ports(&source, &module);
+    check_entry_points(&module);
+
+    //the cpu renderer's dispatch table, each fragment function needs a port of the same name in software.rs
+    let fragments: Vec<String> = module
+        .entry_points
+        .iter()
+        .filter(|entry| entry.stage == naga::ShaderStage::Fragment)
+        .map(|entry| format!("    (\"{0}\", {0}),\n", entry.name))
+        .collect();
+    let table = format!(
+        "pub const FRAGMENTS: [(&str, FragmentFn); {}] = [\n{}];\n",
+        fragments.len(),
+        fragments.concat()
+    );
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    for (name, contents) in [
+        ("shaders.metal", metal),
+        ("fragments.rs", table),
+        ("fragment_samples.rs", fragment_samples(&module)),
+    ] {
+        fs::write(out_dir.join(name), contents)
+            .unwrap_or_else(|error| fail(format!("couldn't write {name}: {error}")));
+    }
+}
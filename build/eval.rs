@@ -0,0 +1,615 @@
+//a small interpreter over the naga ir of shaders.wgsl, enough to run the fragment functions on the
+//host so the cpu ports in software.rs can be tested against the shader itself instead of a hash
+use std::collections::HashMap;
+
+use naga::{
+    Arena, BinaryOperator, Binding, Block, Expression, Function, GlobalVariable, Handle, Literal,
+    LocalVariable, MathFunction, Module, RelationalFunction, ScalarKind, Statement, TypeInner,
+    UnaryOperator,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Float(f32),
+    Uint(u32),
+    Sint(i32),
+    Bool(bool),
+    //vectors, matrices as their columns and structs
+    Composite(Vec<Value>),
+    //a variable and the member path into it
+    Pointer(Variable, Vec<usize>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variable {
+    Global(Handle<GlobalVariable>),
+    Local(Handle<LocalVariable>),
+}
+
+impl Value {
+    pub fn float(&self) -> Result<f32, String> {
+        match self {
+            Value::Float(value) => Ok(*value),
+            other => Err(format!("expected a float, got {other:?}")),
+        }
+    }
+
+    fn components(&self) -> Option<&[Value]> {
+        match self {
+            Value::Composite(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+enum Flow {
+    Next,
+    Return(Option<Value>),
+    Discard,
+}
+
+//one function call, expressions are cached as their emit statements run
+struct Frame<'a> {
+    expressions: &'a Arena<Expression>,
+    arguments: Vec<Value>,
+    locals: HashMap<Handle<LocalVariable>, Value>,
+    values: HashMap<Handle<Expression>, Value>,
+}
+
+impl<'a> Frame<'a> {
+    fn new(expressions: &'a Arena<Expression>, arguments: Vec<Value>) -> Self {
+        Frame {
+            expressions,
+            arguments,
+            locals: HashMap::new(),
+            values: HashMap::new(),
+        }
+    }
+}
+
+pub struct Evaluator<'a> {
+    module: &'a Module,
+    globals: HashMap<Handle<GlobalVariable>, Value>,
+}
+
+impl<'a> Evaluator<'a> {
+    //globals are read only, the uniforms a frame was drawn with
+    pub fn new(module: &'a Module, globals: HashMap<Handle<GlobalVariable>, Value>) -> Self {
+        Evaluator { module, globals }
+    }
+
+    //runs a fragment entry point with its inputs looked up by binding, None is a discard
+    pub fn fragment(
+        &self,
+        name: &str,
+        input: impl Fn(&Binding) -> Option<Value>,
+    ) -> Result<Option<Value>, String> {
+        let entry = self
+            .module
+            .entry_points
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| format!("no entry point {name}"))?;
+        let bound = |binding: &Binding| {
+            input(binding).ok_or_else(|| format!("{name}: no input for {binding:?}"))
+        };
+        let arguments = entry
+            .function
+            .arguments
+            .iter()
+            .map(|argument| match &argument.binding {
+                Some(binding) => bound(binding),
+                None => match &self.module.types[argument.ty].inner {
+                    TypeInner::Struct { members, .. } => members
+                        .iter()
+                        .map(|m| match &m.binding {
+                            Some(binding) => bound(binding),
+                            None => Err(format!("{name}: unbound input member")),
+                        })
+                        .collect::<Result<_, _>>()
+                        .map(Value::Composite),
+                    _ => Err(format!("{name}: unbound input")),
+                },
+            })
+            .collect::<Result<_, _>>()?;
+        match self.call(&entry.function, arguments)? {
+            Flow::Return(Some(value)) => Ok(Some(value)),
+            Flow::Discard => Ok(None),
+            _ => Err(format!("{name} returned nothing")),
+        }
+    }
+
+    fn call(&self, function: &Function, arguments: Vec<Value>) -> Result<Flow, String> {
+        let mut frame = Frame::new(&function.expressions, arguments);
+        for (handle, local) in function.local_variables.iter() {
+            let value = match local.init {
+                Some(init) => self.expression(&mut frame, init)?,
+                None => self.zero(local.ty)?,
+            };
+            frame.locals.insert(handle, value);
+        }
+        self.block(&mut frame, &function.body)
+    }
+
+    fn block(&self, frame: &mut Frame, block: &Block) -> Result<Flow, String> {
+        for statement in block.iter() {
+            let flow = match statement {
+                Statement::Emit(range) => {
+                    for handle in range.clone() {
+                        let value = self.compute(frame, handle)?;
+                        frame.values.insert(handle, value);
+                    }
+                    Flow::Next
+                }
+                Statement::Block(inner) => self.block(frame, inner)?,
+                Statement::If {
+                    condition,
+                    accept,
+                    reject,
+                } => match self.expression(frame, *condition)? {
+                    Value::Bool(true) => self.block(frame, accept)?,
+                    Value::Bool(false) => self.block(frame, reject)?,
+                    other => return Err(format!("if on {other:?}")),
+                },
+                Statement::Return { value } => Flow::Return(
+                    value
+                        .map(|value| self.expression(frame, value))
+                        .transpose()?,
+                ),
+                Statement::Kill => Flow::Discard,
+                Statement::Store { pointer, value } => {
+                    let value = self.expression(frame, *value)?;
+                    let Value::Pointer(Variable::Local(local), path) =
+                        self.expression(frame, *pointer)?
+                    else {
+                        return Err("stores only go to local variables".to_string());
+                    };
+                    let mut target = frame.locals.get_mut(&local).unwrap();
+                    for &index in path.iter() {
+                        let Value::Composite(items) = target else {
+                            return Err(format!("store through {path:?}"));
+                        };
+                        target = &mut items[index];
+                    }
+                    *target = value;
+                    Flow::Next
+                }
+                Statement::Call {
+                    function,
+                    arguments,
+                    result,
+                } => {
+                    let arguments = arguments
+                        .iter()
+                        .map(|&argument| self.expression(frame, argument))
+                        .collect::<Result<_, _>>()?;
+                    match self.call(&self.module.functions[*function], arguments)? {
+                        Flow::Discard => Flow::Discard,
+                        Flow::Return(value) => {
+                            if let (Some(result), Some(value)) = (result, value) {
+                                frame.values.insert(*result, value);
+                            }
+                            Flow::Next
+                        }
+                        Flow::Next => Flow::Next,
+                    }
+                }
+                other => return Err(format!("can't evaluate {other:?}")),
+            };
+            if !matches!(flow, Flow::Next) {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    //emitted expressions come from the cache, the rest are evaluated where they're used
+    fn expression(&self, frame: &mut Frame, handle: Handle<Expression>) -> Result<Value, String> {
+        match frame.values.get(&handle) {
+            Some(value) => Ok(value.clone()),
+            None => self.compute(frame, handle),
+        }
+    }
+
+    fn compute(&self, frame: &mut Frame, handle: Handle<Expression>) -> Result<Value, String> {
+        let value = match &frame.expressions[handle] {
+            Expression::Literal(literal) => match *literal {
+                Literal::F32(value) => Value::Float(value),
+                Literal::F64(value) | Literal::AbstractFloat(value) => Value::Float(value as f32),
+                Literal::U32(value) => Value::Uint(value),
+                Literal::I32(value) => Value::Sint(value),
+                Literal::AbstractInt(value) => Value::Sint(value as i32),
+                Literal::Bool(value) => Value::Bool(value),
+                other => return Err(format!("can't evaluate {other:?}")),
+            },
+            Expression::Constant(constant) => {
+                let init = self.module.constants[*constant].init;
+                self.expression(
+                    &mut Frame::new(&self.module.global_expressions, vec![]),
+                    init,
+                )?
+            }
+            Expression::ZeroValue(ty) => self.zero(*ty)?,
+            Expression::Compose { ty, components } => {
+                let mut values = Vec::new();
+                for &component in components.iter() {
+                    match (
+                        &self.module.types[*ty].inner,
+                        self.expression(frame, component)?,
+                    ) {
+                        //vectors build from smaller vectors flatten
+                        (TypeInner::Vector { .. }, Value::Composite(items)) => values.extend(items),
+                        (_, value) => values.push(value),
+                    }
+                }
+                Value::Composite(values)
+            }
+            Expression::Splat { size, value } => {
+                Value::Composite(vec![self.expression(frame, *value)?; *size as usize])
+            }
+            Expression::Swizzle {
+                size,
+                vector,
+                pattern,
+            } => {
+                let vector = self.expression(frame, *vector)?;
+                let items = vector
+                    .components()
+                    .ok_or_else(|| format!("swizzle of {vector:?}"))?;
+                Value::Composite(
+                    pattern[..*size as usize]
+                        .iter()
+                        .map(|&component| items[component as usize].clone())
+                        .collect(),
+                )
+            }
+            Expression::AccessIndex { base, index } => {
+                self.access(self.expression(frame, *base)?, *index as usize)?
+            }
+            Expression::Access { base, index } => {
+                let index = match self.expression(frame, *index)? {
+                    Value::Uint(index) => index as usize,
+                    Value::Sint(index) => index as usize,
+                    other => return Err(format!("index {other:?}")),
+                };
+                self.access(self.expression(frame, *base)?, index)?
+            }
+            Expression::FunctionArgument(index) => frame.arguments[*index as usize].clone(),
+            Expression::GlobalVariable(global) => Value::Pointer(Variable::Global(*global), vec![]),
+            Expression::LocalVariable(local) => Value::Pointer(Variable::Local(*local), vec![]),
+            Expression::Load { pointer } => {
+                let Value::Pointer(variable, path) = self.expression(frame, *pointer)? else {
+                    return Err("load of a value".to_string());
+                };
+                let mut value = match variable {
+                    Variable::Global(global) => self
+                        .globals
+                        .get(&global)
+                        .ok_or_else(|| format!("no value for global {global:?}"))?,
+                    Variable::Local(local) => &frame.locals[&local],
+                };
+                for &index in path.iter() {
+                    value = &value.components().ok_or("load through a scalar")?[index];
+                }
+                value.clone()
+            }
+            Expression::Unary { op, expr } => {
+                let value = self.expression(frame, *expr)?;
+                map(&value, &|scalar| match (op, scalar) {
+                    (UnaryOperator::Negate, Value::Float(value)) => Ok(Value::Float(-value)),
+                    (UnaryOperator::Negate, Value::Sint(value)) => Ok(Value::Sint(-value)),
+                    (UnaryOperator::LogicalNot, Value::Bool(value)) => Ok(Value::Bool(!value)),
+                    (op, value) => Err(format!("can't evaluate {op:?} {value:?}")),
+                })?
+            }
+            Expression::Binary { op, left, right } => {
+                let left = self.expression(frame, *left)?;
+                let right = self.expression(frame, *right)?;
+                binary(*op, &left, &right)?
+            }
+            Expression::Select {
+                condition,
+                accept,
+                reject,
+            } => match self.expression(frame, *condition)? {
+                Value::Bool(true) => self.expression(frame, *accept)?,
+                Value::Bool(false) => self.expression(frame, *reject)?,
+                other => return Err(format!("select on {other:?}")),
+            },
+            Expression::Relational { fun, argument } => {
+                let argument = self.expression(frame, *argument)?;
+                let flags = match &argument {
+                    Value::Composite(items) => items.clone(),
+                    scalar => vec![scalar.clone()],
+                };
+                match fun {
+                    RelationalFunction::All => {
+                        Value::Bool(flags.iter().all(|f| *f == Value::Bool(true)))
+                    }
+                    RelationalFunction::Any => Value::Bool(flags.contains(&Value::Bool(true))),
+                    RelationalFunction::IsNan => {
+                        map(&argument, &|x| Ok(Value::Bool(x.float()?.is_nan())))?
+                    }
+                    RelationalFunction::IsInf => {
+                        map(&argument, &|x| Ok(Value::Bool(x.float()?.is_infinite())))?
+                    }
+                }
+            }
+            Expression::Math {
+                fun,
+                arg,
+                arg1,
+                arg2,
+                arg3,
+            } => {
+                let mut args = vec![self.expression(frame, *arg)?];
+                for extra in [arg1, arg2, arg3].into_iter().flatten() {
+                    args.push(self.expression(frame, *extra)?);
+                }
+                math(*fun, &args)?
+            }
+            Expression::As {
+                expr,
+                kind,
+                convert,
+            } => {
+                let value = self.expression(frame, *expr)?;
+                map(&value, &|scalar| {
+                    convert_scalar(scalar, *kind, convert.is_some())
+                })?
+            }
+            Expression::CallResult(_) => {
+                return Err("call result used before its call".to_string());
+            }
+            other => return Err(format!("can't evaluate {other:?}")),
+        };
+        Ok(value)
+    }
+
+    fn access(&self, base: Value, index: usize) -> Result<Value, String> {
+        match base {
+            Value::Pointer(variable, mut path) => {
+                path.push(index);
+                Ok(Value::Pointer(variable, path))
+            }
+            Value::Composite(mut items) if index < items.len() => Ok(items.swap_remove(index)),
+            other => Err(format!("index {index} into {other:?}")),
+        }
+    }
+
+    fn zero(&self, ty: Handle<naga::Type>) -> Result<Value, String> {
+        let scalar = |kind| match kind {
+            ScalarKind::Float | ScalarKind::AbstractFloat => Value::Float(0.0),
+            ScalarKind::Uint => Value::Uint(0),
+            ScalarKind::Sint | ScalarKind::AbstractInt => Value::Sint(0),
+            ScalarKind::Bool => Value::Bool(false),
+        };
+        Ok(match &self.module.types[ty].inner {
+            TypeInner::Scalar(s) => scalar(s.kind),
+            TypeInner::Vector { size, scalar: s } => {
+                Value::Composite(vec![scalar(s.kind); *size as usize])
+            }
+            TypeInner::Matrix {
+                columns,
+                rows,
+                scalar: s,
+            } => Value::Composite(vec![
+                Value::Composite(vec![scalar(s.kind); *rows as usize]);
+                *columns as usize
+            ]),
+            TypeInner::Struct { members, .. } => Value::Composite(
+                members
+                    .iter()
+                    .map(|m| self.zero(m.ty))
+                    .collect::<Result<_, _>>()?,
+            ),
+            other => return Err(format!("no zero value for {other:?}")),
+        })
+    }
+}
+
+//a struct of the given type with its members looked up by name
+pub fn struct_value(
+    module: &Module,
+    ty: Handle<naga::Type>,
+    member: impl Fn(&str) -> Option<Value>,
+) -> Result<Value, String> {
+    let TypeInner::Struct { members, .. } = &module.types[ty].inner else {
+        return Err(format!("{ty:?} is not a struct"));
+    };
+    members
+        .iter()
+        .map(|m| {
+            let name = m.name.as_deref().unwrap_or_default();
+            member(name).ok_or_else(|| format!("no value for member {name}"))
+        })
+        .collect::<Result<_, _>>()
+        .map(Value::Composite)
+}
+
+//applies a scalar function to a scalar or to each component of a vector
+fn map(value: &Value, f: &dyn Fn(&Value) -> Result<Value, String>) -> Result<Value, String> {
+    match value {
+        Value::Composite(items) => items
+            .iter()
+            .map(f)
+            .collect::<Result<_, _>>()
+            .map(Value::Composite),
+        scalar => f(scalar),
+    }
+}
+
+//component wise over vectors of one size, scalars standing in for every component
+fn zip(values: &[Value], f: &dyn Fn(&[Value]) -> Result<Value, String>) -> Result<Value, String> {
+    let width = values
+        .iter()
+        .filter_map(|value| value.components())
+        .map(<[Value]>::len)
+        .max();
+    let Some(width) = width else {
+        return f(values);
+    };
+    (0..width)
+        .map(|index| {
+            let column: Vec<Value> = values
+                .iter()
+                .map(|value| match value.components() {
+                    Some(items) if items.len() == width => match &items[index] {
+                        Value::Composite(_) => Err("matrices aren't evaluated".to_string()),
+                        item => Ok(item.clone()),
+                    },
+                    Some(_) => Err("vectors of different sizes".to_string()),
+                    None => Ok(value.clone()),
+                })
+                .collect::<Result<_, _>>()?;
+            f(&column)
+        })
+        .collect::<Result<_, _>>()
+        .map(Value::Composite)
+}
+
+fn binary(op: BinaryOperator, left: &Value, right: &Value) -> Result<Value, String> {
+    use BinaryOperator as Op;
+    zip(&[left.clone(), right.clone()], &|pair| {
+        Ok(match (&pair[0], &pair[1]) {
+            (Value::Float(a), Value::Float(b)) => match op {
+                Op::Add => Value::Float(a + b),
+                Op::Subtract => Value::Float(a - b),
+                Op::Multiply => Value::Float(a * b),
+                Op::Divide => Value::Float(a / b),
+                Op::Modulo => Value::Float(a % b),
+                Op::Equal => Value::Bool(a == b),
+                Op::NotEqual => Value::Bool(a != b),
+                Op::Less => Value::Bool(a < b),
+                Op::LessEqual => Value::Bool(a <= b),
+                Op::Greater => Value::Bool(a > b),
+                Op::GreaterEqual => Value::Bool(a >= b),
+                op => return Err(format!("can't evaluate float {op:?}")),
+            },
+            (Value::Uint(a), Value::Uint(b)) => match op {
+                Op::Add => Value::Uint(a.wrapping_add(*b)),
+                Op::Subtract => Value::Uint(a.wrapping_sub(*b)),
+                Op::Multiply => Value::Uint(a.wrapping_mul(*b)),
+                Op::Divide => Value::Uint(a.checked_div(*b).unwrap_or(*a)),
+                Op::Modulo => Value::Uint(a.checked_rem(*b).unwrap_or(0)),
+                Op::Equal => Value::Bool(a == b),
+                Op::NotEqual => Value::Bool(a != b),
+                Op::Less => Value::Bool(a < b),
+                Op::LessEqual => Value::Bool(a <= b),
+                Op::Greater => Value::Bool(a > b),
+                Op::GreaterEqual => Value::Bool(a >= b),
+                op => return Err(format!("can't evaluate uint {op:?}")),
+            },
+            (Value::Sint(a), Value::Sint(b)) => match op {
+                Op::Add => Value::Sint(a.wrapping_add(*b)),
+                Op::Subtract => Value::Sint(a.wrapping_sub(*b)),
+                Op::Multiply => Value::Sint(a.wrapping_mul(*b)),
+                Op::Divide => Value::Sint(a.checked_div(*b).unwrap_or(*a)),
+                Op::Modulo => Value::Sint(a.checked_rem(*b).unwrap_or(0)),
+                Op::Equal => Value::Bool(a == b),
+                Op::NotEqual => Value::Bool(a != b),
+                Op::Less => Value::Bool(a < b),
+                Op::LessEqual => Value::Bool(a <= b),
+                Op::Greater => Value::Bool(a > b),
+                Op::GreaterEqual => Value::Bool(a >= b),
+                op => return Err(format!("can't evaluate sint {op:?}")),
+            },
+            (Value::Bool(a), Value::Bool(b)) => match op {
+                Op::LogicalAnd | Op::And => Value::Bool(*a && *b),
+                Op::LogicalOr | Op::InclusiveOr => Value::Bool(*a || *b),
+                Op::Equal => Value::Bool(a == b),
+                Op::NotEqual => Value::Bool(a != b),
+                op => return Err(format!("can't evaluate bool {op:?}")),
+            },
+            (a, b) => return Err(format!("can't evaluate {a:?} {op:?} {b:?}")),
+        })
+    })
+}
+
+//the float math the shaders use, sums like length and dot fold the components afterwards
+fn math(fun: MathFunction, args: &[Value]) -> Result<Value, String> {
+    use MathFunction as M;
+    let floats = |f: fn(&[f32]) -> f32| {
+        move |column: &[Value]| -> Result<Value, String> {
+            let values: Vec<f32> = column.iter().map(Value::float).collect::<Result<_, _>>()?;
+            Ok(Value::Float(f(&values)))
+        }
+    };
+    let sum = |value: Value| -> Result<Value, String> {
+        match value {
+            Value::Composite(items) => items
+                .iter()
+                .map(Value::float)
+                .sum::<Result<f32, _>>()
+                .map(Value::Float),
+            scalar => Ok(scalar),
+        }
+    };
+    match fun {
+        M::Abs => zip(args, &floats(|x| x[0].abs())),
+        M::Min => zip(args, &floats(|x| x[0].min(x[1]))),
+        M::Max => zip(args, &floats(|x| x[0].max(x[1]))),
+        M::Clamp => zip(args, &floats(|x| x[0].max(x[1]).min(x[2]))),
+        M::Saturate => zip(args, &floats(|x| x[0].clamp(0.0, 1.0))),
+        M::Cos => zip(args, &floats(|x| x[0].cos())),
+        M::Sin => zip(args, &floats(|x| x[0].sin())),
+        M::Tan => zip(args, &floats(|x| x[0].tan())),
+        M::Acos => zip(args, &floats(|x| x[0].acos())),
+        M::Asin => zip(args, &floats(|x| x[0].asin())),
+        M::Atan => zip(args, &floats(|x| x[0].atan())),
+        M::Atan2 => zip(args, &floats(|x| x[0].atan2(x[1]))),
+        M::Ceil => zip(args, &floats(|x| x[0].ceil())),
+        M::Floor => zip(args, &floats(|x| x[0].floor())),
+        M::Round => zip(args, &floats(|x| x[0].round_ties_even())),
+        M::Fract => zip(args, &floats(|x| x[0] - x[0].floor())),
+        M::Trunc => zip(args, &floats(|x| x[0].trunc())),
+        M::Exp => zip(args, &floats(|x| x[0].exp())),
+        M::Exp2 => zip(args, &floats(|x| x[0].exp2())),
+        M::Log => zip(args, &floats(|x| x[0].ln())),
+        M::Log2 => zip(args, &floats(|x| x[0].log2())),
+        M::Pow => zip(args, &floats(|x| x[0].powf(x[1]))),
+        M::Sqrt => zip(args, &floats(|x| x[0].sqrt())),
+        M::InverseSqrt => zip(args, &floats(|x| 1.0 / x[0].sqrt())),
+        //zero stays zero
+        M::Sign => zip(
+            args,
+            &floats(|x| if x[0] == 0.0 { 0.0 } else { x[0].signum() }),
+        ),
+        M::Step => zip(args, &floats(|x| if x[1] >= x[0] { 1.0 } else { 0.0 })),
+        M::Fma => zip(args, &floats(|x| x[0] * x[1] + x[2])),
+        M::Mix => zip(args, &floats(|x| x[0] + (x[1] - x[0]) * x[2])),
+        M::SmoothStep => zip(
+            args,
+            &floats(|x| {
+                let t = ((x[2] - x[0]) / (x[1] - x[0])).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            }),
+        ),
+        M::Dot => sum(zip(args, &floats(|x| x[0] * x[1]))?),
+        M::Length => sum(zip(args, &floats(|x| x[0] * x[0]))?)?
+            .float()
+            .map(|squared| Value::Float(squared.sqrt())),
+        M::Distance => sum(zip(args, &floats(|x| (x[0] - x[1]) * (x[0] - x[1])))?)?
+            .float()
+            .map(|squared| Value::Float(squared.sqrt())),
+        other => Err(format!("can't evaluate {other:?}")),
+    }
+}
+
+fn convert_scalar(value: &Value, kind: ScalarKind, convert: bool) -> Result<Value, String> {
+    Ok(match (value, kind, convert) {
+        (Value::Float(x), ScalarKind::Float, _) => Value::Float(*x),
+        (Value::Uint(x), ScalarKind::Float, true) => Value::Float(*x as f32),
+        (Value::Sint(x), ScalarKind::Float, true) => Value::Float(*x as f32),
+        (Value::Bool(x), ScalarKind::Float, true) => Value::Float(*x as u32 as f32),
+        (Value::Float(x), ScalarKind::Uint, true) => Value::Uint(*x as u32),
+        (Value::Sint(x), ScalarKind::Uint, _) => Value::Uint(*x as u32),
+        (Value::Uint(x), ScalarKind::Uint, _) => Value::Uint(*x),
+        (Value::Float(x), ScalarKind::Sint, true) => Value::Sint(*x as i32),
+        (Value::Uint(x), ScalarKind::Sint, _) => Value::Sint(*x as i32),
+        (Value::Sint(x), ScalarKind::Sint, _) => Value::Sint(*x),
+        (Value::Float(x), ScalarKind::Uint, false) => Value::Uint(x.to_bits()),
+        (Value::Uint(x), ScalarKind::Float, false) => Value::Float(f32::from_bits(*x)),
+        (value, kind, _) => return Err(format!("can't convert {value:?} to {kind:?}")),
+    })
+}
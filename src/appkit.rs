@@ -0,0 +1,275 @@
+use metal::{MTLResourceOptions, MTLViewport};
+use objc2::rc::autoreleasepool;
+use objc2_app_kit::{NSAnyEventMask, NSEventType};
+use objc2_foundation::{NSComparisonResult, NSDate, NSDefaultRunLoopMode};
+
+use crate::bot::Bot;
+use crate::game::{CLUSTER_FRAG_COUNT, FPS, Game, VIEW_HEIGHT, VIEW_WIDTH};
+use crate::gpu::Uniforms;
+use crate::input::{Action, Input};
+use crate::maths::{Float2, Float4};
+use crate::profiler::Phase;
+use crate::scaling::{ScalePolicy, fit};
+use crate::utils::{
+    get_library, get_next_frame, grow_to_buf, init_render_with_bufs, new_render_pass_descriptor,
+    prepare_pipeline_state, simple_app, sync_drawable_size, toggle_fullscreen,
+};
+use crate::vertex_t;
+
+//mac virtual key codes
+fn action(key_code: u16) -> Option<Action> {
+    match key_code {
+        13 => Some(Action::Up),
+        1 => Some(Action::Down),
+        0 => Some(Action::Left),
+        2 => Some(Action::Right),
+        14 => Some(Action::Drain),
+        49 => Some(Action::PlaceWall),
+        12 => Some(Action::PlaceMirror),
+        3 => Some(Action::ToggleFullscreen),
+        35 => Some(Action::CycleScalePolicy),
+        50 => Some(Action::ToggleConsole),
+        99 => Some(Action::ToggleOverlay),
+        118 => Some(Action::ToggleProfiler),
+        _ => None,
+    }
+}
+
+pub fn run(mut game: Game, mut bot: Option<Bot>) {
+    //how the virtual view fits windows of other shapes, letterbox keeps every player on the same area
+    let mut scale_policy = ScalePolicy::Letterbox;
+    let mut scaled = fit(
+        Float2(VIEW_WIDTH, VIEW_HEIGHT),
+        Float2(VIEW_WIDTH, VIEW_HEIGHT),
+        scale_policy,
+    );
+    let mut frame_time = get_next_frame(FPS as f64);
+    let mut input = Input::new();
+
+    let (app, window, device, layer) =
+        simple_app(VIEW_WIDTH as f64, VIEW_HEIGHT as f64, "Colorstep");
+
+    let shaderlib = get_library(&device);
+
+    let render_pipeline = prepare_pipeline_state(&device, "box_vertex", "box_fragment", &shaderlib);
+    let goal_pipeline =
+        prepare_pipeline_state(&device, "box_vertex", "scorezone_fragment", &shaderlib);
+    let telegraph_pipeline =
+        prepare_pipeline_state(&device, "box_vertex", "telegraph_fragment", &shaderlib);
+    let overlay_pipeline =
+        prepare_pipeline_state(&device, "box_vertex", "overlay_fragment", &shaderlib);
+    let command_queue = device.new_command_queue();
+
+    let start_vert: Vec<vertex_t> = vec![vertex_t {
+        position: Float4(0.0, 0.0, 0.0, 0.0),
+        color: Float4(0.0, 0.0, 0.0, 0.0),
+    }];
+    let new_buf = |quads: usize| {
+        device.new_buffer_with_data(
+            start_vert.as_ptr() as *const _,
+            (size_of::<vertex_t>() * 4 * quads) as u64,
+            MTLResourceOptions::CPUCacheModeDefaultCache | MTLResourceOptions::StorageModeManaged,
+        )
+    };
+    let mut telegraph_buf = new_buf(32);
+    //one buffer per box layer, in the order scene.boxes() returns them, grown when a layer outgrows it
+    let mut box_bufs = [
+        new_buf(10 * 128),
+        new_buf(16),
+        new_buf(16),
+        //the last boss phase bursts three clusters at once
+        new_buf(3 * CLUSTER_FRAG_COUNT as usize),
+        new_buf(1024),
+        new_buf(16),
+        new_buf(4),
+        new_buf(128),
+    ];
+
+    loop {
+        if game.dismissed {
+            break;
+        }
+        autoreleasepool(|_| {
+            if app.windows().is_empty() {
+                unsafe {
+                    app.terminate(None);
+                }
+            }
+            if unsafe { frame_time.compare(&NSDate::now()) } == NSComparisonResult::Ascending {
+                frame_time = get_next_frame(FPS as f64);
+
+                //frontend keys type into the console while it is open
+                let typing = game.debug.console.open;
+                if !typing && input.pressed(Action::ToggleFullscreen) {
+                    toggle_fullscreen(&window);
+                }
+                if !typing && input.pressed(Action::CycleScalePolicy) {
+                    scale_policy = match scale_policy {
+                        ScalePolicy::Letterbox => ScalePolicy::Expand,
+                        ScalePolicy::Expand => ScalePolicy::Letterbox,
+                    };
+                }
+                //resizes and backing scale changes only change how much of the world is shown
+                let (drawable_width, drawable_height) = sync_drawable_size(&window, &layer);
+                scaled = fit(
+                    Float2(VIEW_WIDTH, VIEW_HEIGHT),
+                    Float2(drawable_width as f32, drawable_height as f32),
+                    scale_policy,
+                );
+                game.camera.viewport = scaled.visible;
+                if let Some(bot) = bot.as_mut() {
+                    bot.act(&game, &mut input);
+                }
+                game.step(&input);
+                input.end_frame();
+                let scene = &game.scene;
+
+                let span = game.profiler.start();
+                let uniforms = Uniforms::new(scene, scaled);
+                grow_to_buf(&scene.telegraphs, &mut telegraph_buf, &device);
+                for (verts, buf) in scene.boxes().iter().zip(box_bufs.iter_mut()) {
+                    grow_to_buf(verts, buf, &device);
+                }
+                game.profiler.record(Phase::Upload, span);
+                let span = game.profiler.start();
+
+                let command_buffer = command_queue.new_command_buffer();
+
+                let drawable = layer.next_drawable().unwrap();
+                let texture = drawable.texture();
+                let render_descriptor = new_render_pass_descriptor(&texture);
+
+                let encoder = init_render_with_bufs(
+                    &vec![],
+                    &render_descriptor,
+                    &render_pipeline,
+                    command_buffer,
+                );
+                encoder.set_viewport(MTLViewport {
+                    originX: scaled.viewport.x as f64,
+                    originY: scaled.viewport.y as f64,
+                    width: scaled.viewport.width as f64,
+                    height: scaled.viewport.height as f64,
+                    znear: 0.0,
+                    zfar: 1.0,
+                });
+                encoder.set_vertex_bytes(
+                    0,
+                    (size_of::<Uniforms>()) as u64,
+                    &uniforms as *const Uniforms as *const _,
+                );
+                encoder.set_fragment_bytes(
+                    0,
+                    (size_of::<Uniforms>()) as u64,
+                    &uniforms as *const Uniforms as *const _,
+                );
+                //cluster landing zones and blasts, drawn first so they sit under everything
+                encoder.set_render_pipeline_state(&telegraph_pipeline);
+                encoder.set_vertex_buffer(1, Some(&telegraph_buf), 0);
+                for i in 0..scene.telegraphs.len() / 4 {
+                    encoder.draw_primitives(
+                        metal::MTLPrimitiveType::TriangleStrip,
+                        (i as u64) * 4,
+                        4,
+                    );
+                }
+                encoder.set_render_pipeline_state(&render_pipeline);
+                for (verts, buf) in scene.boxes().iter().zip(box_bufs.iter()) {
+                    encoder.set_vertex_buffer(1, Some(buf), 0);
+                    for i in 0..verts.len() / 4 {
+                        encoder.draw_primitives(
+                            metal::MTLPrimitiveType::TriangleStrip,
+                            (i as u64) * 4,
+                            4,
+                        );
+                    }
+                }
+
+                encoder.set_render_pipeline_state(&goal_pipeline);
+                for verts in scene.goals.chunks(4) {
+                    encoder.set_vertex_bytes(
+                        1,
+                        (size_of::<vertex_t>() * 4) as u64,
+                        verts.as_ptr() as *const _,
+                    );
+                    encoder.draw_primitives(metal::MTLPrimitiveType::TriangleStrip, 0, 4);
+                }
+                //debug overlay and console on top of everything
+                encoder.set_render_pipeline_state(&overlay_pipeline);
+                for verts in scene.overlay.chunks(4) {
+                    encoder.set_vertex_bytes(
+                        1,
+                        (size_of::<vertex_t>() * 4) as u64,
+                        verts.as_ptr() as *const _,
+                    );
+                    encoder.draw_primitives(metal::MTLPrimitiveType::TriangleStrip, 0, 4);
+                }
+                encoder.end_encoding();
+
+                command_buffer.present_drawable(drawable);
+                command_buffer.commit();
+                game.profiler.record(Phase::Encode, span);
+            }
+
+            loop {
+                unsafe {
+                    let e = app.nextEventMatchingMask_untilDate_inMode_dequeue(
+                        NSAnyEventMask,
+                        None,
+                        NSDefaultRunLoopMode,
+                        true,
+                    );
+                    match e {
+                        Some(ref e) => match e.r#type() {
+                            NSEventType::MouseMoved => {
+                                //hue slider spans the game view, not the letterbox bars
+                                let backing = window.backingScaleFactor();
+                                let content_height = window
+                                    .contentView()
+                                    .map_or(VIEW_HEIGHT as f64, |view| view.bounds().size.height);
+                                let location = e.locationInWindow();
+                                let pixel = Float2(
+                                    (location.x * backing) as f32,
+                                    ((content_height - location.y) * backing) as f32,
+                                );
+                                if scaled.contains(pixel) {
+                                    let point = scaled.to_view(pixel);
+                                    input
+                                        .set_hue(((point.0 / scaled.visible.0 + 1.0) / 2.0) as f64);
+                                }
+                                app.sendEvent(e);
+                            }
+                            NSEventType::KeyDown => {
+                                if let Some(action) = action(e.keyCode()) {
+                                    input.press(action);
+                                }
+                                //return and delete, everything else arrives as characters
+                                match e.keyCode() {
+                                    36 | 76 => input.type_char('\n'),
+                                    51 => input.type_char('\u{8}'),
+                                    _ => {
+                                        if let Some(characters) = e.characters() {
+                                            for c in characters.to_string().chars() {
+                                                input.type_char(c);
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            NSEventType::KeyUp => {
+                                if let Some(action) = action(e.keyCode()) {
+                                    input.release(action);
+                                }
+                            }
+                            _ => app.sendEvent(e),
+                        },
+                        None => {
+                            break;
+                        }
+                    }
+                }
+            }
+        })
+    }
+    game.report();
+}
@@ -0,0 +1,228 @@
+use crate::maths::{Float2, Float4};
+use crate::rng::random;
+use crate::walls::{Wall, WallKind};
+use crate::{build_rect, obb_intersect, vertex_t};
+
+//grey is no palette hue, so fixed pillars stop every hazard
+const PILLAR_COLOR: Float4 = Float4(0.45, 0.45, 0.5, 1.0);
+const PILLAR_SIZE: Float2 = Float2(40.0, 240.0);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Zone {
+    pub center: Float2,
+    pub size: Float2,
+}
+
+impl Zone {
+    pub fn new(center: Float2, size: Float2) -> Self {
+        Zone { center, size }
+    }
+    pub fn contains(&self, point: Float2) -> bool {
+        (point.0 - self.center.0).abs() <= self.size.0 / 2.0
+            && (point.1 - self.center.1).abs() <= self.size.1 / 2.0
+    }
+    pub fn rect(&self, color: Float4) -> Vec<vertex_t> {
+        build_rect(
+            self.center.0,
+            self.center.1,
+            self.size.0,
+            self.size.1,
+            0.0,
+            color,
+        )
+    }
+}
+
+//the playable space, bounds are half extents around the origin
+#[derive(Debug, Clone)]
+pub struct Arena {
+    pub bounds: Float2,
+    //fixed walls, solid to the player and acting on hazards like placed ones
+    pub walls: Vec<Wall>,
+    //the active zone moves between these every round
+    pub delivery_zones: Vec<Zone>,
+    pub goal_spawns: Vec<Float2>,
+    delivery: usize,
+}
+
+impl Arena {
+    pub fn new(bounds: Float2, delivery_zones: Vec<Zone>, goal_spawns: Vec<Float2>) -> Self {
+        assert!(!delivery_zones.is_empty(), "arena needs a delivery zone");
+        Arena {
+            bounds,
+            walls: Vec::new(),
+            delivery_zones,
+            goal_spawns,
+            delivery: 0,
+        }
+    }
+
+    pub fn with_walls(mut self, walls: Vec<Wall>) -> Self {
+        self.walls = walls;
+        self
+    }
+
+    //goal near the top and delivery along the bottom edge to start, like the original layout
+    //two pillars in the lower half give cover from the lanes either side of the start
+    pub fn standard(bounds: Float2) -> Self {
+        let depth = 80.0;
+        let pillars = [-1.0, 1.0]
+            .iter()
+            .map(|side| {
+                Wall::new(
+                    Float2(side * bounds.0 / 2.0, -bounds.1 / 4.0),
+                    PILLAR_SIZE.0,
+                    PILLAR_SIZE.1,
+                    0.0,
+                    WallKind::Colored(PILLAR_COLOR),
+                )
+            })
+            .collect();
+        Arena::new(
+            bounds,
+            vec![
+                Zone::new(
+                    Float2(0.0, -bounds.1 + depth / 2.0),
+                    Float2(bounds.0 * 2.0, depth),
+                ),
+                Zone::new(
+                    Float2(-bounds.0 + depth / 2.0, 0.0),
+                    Float2(depth, bounds.1 * 2.0),
+                ),
+                Zone::new(
+                    Float2(bounds.0 - depth / 2.0, 0.0),
+                    Float2(depth, bounds.1 * 2.0),
+                ),
+            ],
+            vec![
+                Float2(0.0, 600.0_f32.min(bounds.1 - 100.0)),
+                Float2(-bounds.0 / 2.0, bounds.1 / 2.0),
+                Float2(bounds.0 / 2.0, bounds.1 / 2.0),
+                Float2(0.0, 0.0),
+            ],
+        )
+        .with_walls(pillars)
+    }
+
+    pub fn delivery(&self) -> Zone {
+        self.delivery_zones[self.delivery]
+    }
+
+    //any zone other than the current one, stays put when there is only one
+    pub fn move_delivery(&mut self) {
+        let count = self.delivery_zones.len();
+        if count > 1 {
+            self.delivery = (self.delivery + 1 + random::<usize>() % (count - 1)) % count;
+        }
+    }
+
+    pub fn clamp(&self, position: Float2, half_size: Float2) -> Float2 {
+        Float2(
+            position
+                .0
+                .clamp(-self.bounds.0 + half_size.0, self.bounds.0 - half_size.0),
+            position
+                .1
+                .clamp(-self.bounds.1 + half_size.1, self.bounds.1 - half_size.1),
+        )
+    }
+
+    pub fn blocked(&self, position: Float2, half_size: Float2) -> bool {
+        let rect = build_rect(
+            position.0,
+            position.1,
+            half_size.0 * 2.0,
+            half_size.1 * 2.0,
+            0.0,
+            Float4(0.0, 0.0, 0.0, 0.0),
+        );
+        self.walls
+            .iter()
+            .any(|wall| obb_intersect(&wall.rect(), &rect))
+    }
+
+    //moves one axis at a time so the player slides along walls instead of sticking to them
+    pub fn resolve(&self, from: Float2, to: Float2, half_size: Float2) -> Float2 {
+        let to = self.clamp(to, half_size);
+        let mut position = from;
+        if !self.blocked(Float2(to.0, position.1), half_size) {
+            position.0 = to.0;
+        }
+        if !self.blocked(Float2(position.0, to.1), half_size) {
+            position.1 = to.1;
+        }
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF: Float2 = Float2(25.0, 25.0);
+
+    fn open_arena() -> Arena {
+        Arena::new(
+            Float2(500.0, 400.0),
+            vec![Zone::new(Float2(0.0, -360.0), Float2(1000.0, 80.0))],
+            vec![Float2(0.0, 0.0)],
+        )
+    }
+
+    #[test]
+    fn clamp_keeps_the_whole_player_inside_the_bounds() {
+        let arena = open_arena();
+        assert_eq!(
+            arena.clamp(Float2(900.0, -900.0), HALF),
+            Float2(475.0, -375.0)
+        );
+        assert_eq!(arena.clamp(Float2(10.0, 20.0), HALF), Float2(10.0, 20.0));
+    }
+
+    #[test]
+    fn resolve_moves_freely_without_walls_and_clamps_at_the_edge() {
+        let arena = open_arena();
+        let from = Float2(0.0, 0.0);
+        assert_eq!(
+            arena.resolve(from, Float2(30.0, -40.0), HALF),
+            Float2(30.0, -40.0)
+        );
+        assert_eq!(
+            arena.resolve(Float2(470.0, 0.0), Float2(490.0, 10.0), HALF),
+            Float2(475.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn walls_block_one_axis_and_the_other_slides() {
+        //a tall wall just right of the player
+        let arena = open_arena().with_walls(vec![Wall::new(
+            Float2(60.0, 0.0),
+            20.0,
+            300.0,
+            0.0,
+            WallKind::Colored(PILLAR_COLOR),
+        )]);
+        let from = Float2(0.0, 0.0);
+        assert!(arena.blocked(Float2(30.0, 0.0), HALF));
+        assert!(!arena.blocked(from, HALF));
+        //pushing diagonally into the wall keeps the vertical part of the move
+        assert_eq!(
+            arena.resolve(from, Float2(30.0, 20.0), HALF),
+            Float2(0.0, 20.0)
+        );
+        //straight into it goes nowhere
+        assert_eq!(arena.resolve(from, Float2(30.0, 0.0), HALF), from);
+    }
+
+    #[test]
+    fn the_standard_arena_has_pillars_clear_of_the_start_and_spawns() {
+        let arena = Arena::standard(Float2(1024.0, 768.0));
+        assert_eq!(arena.walls.len(), 2);
+        assert!(!arena.blocked(Float2(0.0, 0.0), HALF));
+        for &spawn in arena.goal_spawns.iter() {
+            assert!(!arena.blocked(spawn, Float2(50.0, 50.0)));
+        }
+        assert!(arena.blocked(Float2(512.0, -192.0), HALF));
+    }
+}
@@ -0,0 +1,77 @@
+//seeded headless bot runs written out as a balance report
+use std::path::Path;
+use std::process;
+use std::str::FromStr;
+
+use lost_signal::bot::Policy;
+use lost_signal::game::Difficulty;
+use lost_signal::sim::{self, Report, SimConfig};
+
+const USAGE: &str = "usage: lost-signal-sim [--games N] [--seed FIRST] [--frames LIMIT] \
+[--policy scripted|stationary] [--difficulty easy|normal|hard] [--out REPORT.json|REPORT.csv] \
+[--trace TRACE.json]";
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}\n{USAGE}");
+    process::exit(2);
+}
+
+fn number<T: FromStr>(name: &str, value: &str) -> T {
+    value
+        .parse()
+        .unwrap_or_else(|_| fail(&format!("{name} needs a number, got {value}")))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut config = SimConfig::default();
+    let mut out = String::from("lost-signal-sim.json");
+    let mut trace = None;
+    for pair in args.chunks(2) {
+        let [name, value] = pair else {
+            fail(&format!("{} needs a value", pair[0]));
+        };
+        match name.as_str() {
+            "--games" => config.games = number(name, value),
+            "--seed" => config.first_seed = number(name, value),
+            "--frames" => config.frame_limit = number(name, value),
+            "--policy" => {
+                config.policy =
+                    Policy::named(value).unwrap_or_else(|| fail(&format!("unknown policy {value}")))
+            }
+            "--difficulty" => {
+                config.difficulty = Difficulty::named(value)
+                    .unwrap_or_else(|| fail(&format!("unknown difficulty {value}")))
+            }
+            "--out" => out = value.clone(),
+            "--trace" => trace = Some(value.clone()),
+            _ => fail(&format!("unknown option {name}")),
+        }
+    }
+    if config.games == 0 {
+        fail("--games needs at least one game");
+    }
+
+    let report = Report::run(config);
+    print!("{report}");
+    if let Err(error) = report.save(Path::new(&out)) {
+        eprintln!("couldn't write report to {out}: {error}");
+        process::exit(1);
+    }
+    println!("report written to {out}");
+
+    //the first seed again with its frame timings, for chrome://tracing or perfetto
+    if let Some(trace) = trace {
+        let profiler = sim::trace(
+            config.first_seed,
+            config.policy,
+            config.difficulty,
+            config.frame_limit,
+        );
+        if let Err(error) = profiler.save_trace(Path::new(&trace)) {
+            eprintln!("couldn't write trace to {trace}: {error}");
+            process::exit(1);
+        }
+        println!("trace of seed {} written to {trace}", config.first_seed);
+    }
+}
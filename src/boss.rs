@@ -0,0 +1,379 @@
+use hsv::hsv_to_rgb;
+
+use crate::color_convert;
+use crate::lasers::{LaserPattern, Wave};
+use crate::maths::{Float2, Float4, float2_subtract, scale2};
+
+#[derive(Debug, Clone, Copy)]
+pub enum BossAction {
+    Lasers(LaserPattern),
+    Jumprope,
+    Clusters {
+        count: u32,
+    },
+    GravityWell {
+        strength: f32,
+        radius: f32,
+        duration: f32,
+    },
+}
+
+//actions fire at their offset into the phase, the whole script repeats every period
+#[derive(Debug, Clone)]
+pub struct BossPhase {
+    pub until_health: u32,
+    pub period: f32,
+    //camera zoom while the phase runs, later phases close in on the player
+    pub zoom: f32,
+    pub script: Vec<(f32, BossAction)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GravityWell {
+    pub position: Float2,
+    pub strength: f32,
+    pub radius: f32,
+    pub lifetime: f32,
+    duration: f32,
+}
+
+impl GravityWell {
+    pub fn new(position: Float2, strength: f32, radius: f32, duration: f32) -> Self {
+        GravityWell {
+            position,
+            strength,
+            radius,
+            lifetime: duration,
+            duration,
+        }
+    }
+    //0 at the edge of the well, 1 at its center
+    pub fn proximity(&self, point: Float2) -> f32 {
+        let distance = float2_subtract(self.position, point).magnitude();
+        (1.0 - distance / self.radius).max(0.0)
+    }
+    //pull towards the center, strongest close in
+    pub fn pull(&self, point: Float2) -> Float2 {
+        let offset = float2_subtract(self.position, point);
+        let distance = offset.magnitude();
+        if distance >= self.radius || distance < 1.0 {
+            return Float2(0.0, 0.0);
+        }
+        scale2(offset, self.strength * self.proximity(point) / distance)
+    }
+    //the closer a projectile gets the more palette steps it slides towards red
+    pub fn redshift(
+        &self,
+        base_color: Float4,
+        point: Float2,
+        max_steps: u32,
+        palette_steps: u32,
+    ) -> Float4 {
+        let steps = (self.proximity(point) * max_steps as f32).ceil() as u32;
+        redshift(base_color, steps, palette_steps)
+    }
+    pub fn remaining(&self) -> f32 {
+        (self.lifetime / self.duration).clamp(0.0, 1.0)
+    }
+}
+
+//palette index of a fully saturated palette color, red is 0
+pub fn palette_index(color: Float4, palette_steps: u32) -> u32 {
+    let (r, g, b) = (color.0, color.1, color.2);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    if max - min <= f32::EPSILON {
+        return 0;
+    }
+    let hue = if max == r {
+        60.0 * ((g - b) / (max - min)).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / (max - min) + 2.0)
+    } else {
+        60.0 * ((r - g) / (max - min) + 4.0)
+    };
+    (hue / (360 / palette_steps) as f32).round() as u32 % palette_steps
+}
+
+pub fn redshift(color: Float4, steps: u32, palette: u32) -> Float4 {
+    let index = palette_index(color, palette);
+    //walk whichever way round the hue wheel reaches red first
+    let shifted = if index <= palette / 2 {
+        index.saturating_sub(steps)
+    } else if index + steps >= palette {
+        0
+    } else {
+        index + steps
+    };
+    let hue = (shifted * (360 / palette)) as f64;
+    let shifted_color = color_convert(hsv_to_rgb(hue, 1.0, 1.0));
+    Float4(shifted_color.0, shifted_color.1, shifted_color.2, color.3)
+}
+
+pub struct Boss {
+    pub position: Float2,
+    pub health: u32,
+    pub max_health: u32,
+    phases: Vec<BossPhase>,
+    phase: usize,
+    timer: f32,
+}
+
+impl Boss {
+    pub fn new(position: Float2, max_health: u32, phases: Vec<BossPhase>) -> Self {
+        Boss {
+            position,
+            health: max_health,
+            max_health,
+            phases,
+            phase: 0,
+            timer: 0.0,
+        }
+    }
+
+    //the gravity boss from the notes, three deliveries to beat
+    pub fn redshift_boss(position: Float2) -> Self {
+        let sine = Wave {
+            amplitude: 80.0,
+            wavelength: 400.0,
+        };
+        Boss::new(
+            position,
+            3,
+            vec![
+                BossPhase {
+                    until_health: 2,
+                    period: 4.0,
+                    zoom: 1.0,
+                    script: vec![
+                        (0.0, BossAction::Lasers(LaserPattern::Lanes)),
+                        (1.5, BossAction::Lasers(LaserPattern::SineLanes(sine))),
+                        (3.0, BossAction::Jumprope),
+                    ],
+                },
+                BossPhase {
+                    until_health: 1,
+                    period: 5.0,
+                    zoom: 1.15,
+                    script: vec![
+                        (
+                            0.0,
+                            BossAction::Lasers(LaserPattern::DiagonalSweep { angle: 0.3 }),
+                        ),
+                        (1.0, BossAction::Clusters { count: 2 }),
+                        (
+                            2.5,
+                            BossAction::GravityWell {
+                                strength: 900.0,
+                                radius: 350.0,
+                                duration: 4.0,
+                            },
+                        ),
+                    ],
+                },
+                BossPhase {
+                    until_health: 0,
+                    period: 4.0,
+                    zoom: 1.3,
+                    script: vec![
+                        (
+                            0.0,
+                            BossAction::GravityWell {
+                                strength: 1200.0,
+                                radius: 400.0,
+                                duration: 3.5,
+                            },
+                        ),
+                        (
+                            0.5,
+                            BossAction::Lasers(LaserPattern::ConvergingFan {
+                                count: 7,
+                                spread: 900.0,
+                            }),
+                        ),
+                        (2.0, BossAction::Clusters { count: 3 }),
+                        (3.0, BossAction::Lasers(LaserPattern::SineLanes(sine))),
+                    ],
+                },
+            ],
+        )
+    }
+
+    //actions whose offset was crossed this frame, including the start of the next loop
+    pub fn update(&mut self, delta_t: f32) -> Vec<BossAction> {
+        let Some(phase) = self.phases.get(self.phase) else {
+            return Vec::new();
+        };
+        let crossed = |from: f32, to: f32| {
+            phase
+                .script
+                .iter()
+                .filter(move |(at, _)| from <= *at && *at < to)
+                .map(|(_, action)| *action)
+        };
+        let previous = self.timer;
+        self.timer += delta_t;
+        let mut actions: Vec<BossAction> = crossed(previous, self.timer).collect();
+        if self.timer >= phase.period {
+            self.timer -= phase.period;
+            actions.extend(crossed(0.0, self.timer));
+        }
+        actions
+    }
+
+    //a delivered goal lands a hit, moving on to the next phase once its health threshold is reached
+    pub fn hit(&mut self) {
+        self.health = self.health.saturating_sub(1);
+        while self
+            .phases
+            .get(self.phase)
+            .is_some_and(|phase| self.health <= phase.until_health && self.health > 0)
+        {
+            self.phase += 1;
+            self.timer = 0.0;
+        }
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.phases.get(self.phase).map_or(1.0, |phase| phase.zoom)
+    }
+
+    pub fn defeated(&self) -> bool {
+        self.health == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::COLOR_STEPS;
+
+    fn palette(index: u32, steps: u32) -> Float4 {
+        color_convert(hsv_to_rgb((index * (360 / steps)) as f64, 1.0, 1.0))
+    }
+
+    fn phase(until_health: u32, zoom: f32) -> BossPhase {
+        BossPhase {
+            until_health,
+            period: 1.0,
+            zoom,
+            script: vec![(0.0, BossAction::Jumprope)],
+        }
+    }
+
+    #[test]
+    fn hits_move_through_the_phases_until_defeated() {
+        let mut boss = Boss::redshift_boss(Float2(0.0, 0.0));
+        assert_eq!(boss.zoom(), 1.0);
+        boss.update(1.0);
+        boss.hit();
+        assert_eq!((boss.health, boss.phase), (2, 1));
+        assert_eq!(boss.zoom(), 1.15);
+        //a new phase starts its script from the top
+        assert!(matches!(
+            boss.update(0.1)[..],
+            [BossAction::Lasers(LaserPattern::DiagonalSweep { .. })]
+        ));
+        boss.hit();
+        assert_eq!((boss.health, boss.phase), (1, 2));
+        assert_eq!(boss.zoom(), 1.3);
+        assert!(!boss.defeated());
+        //the last phase stays up through the killing hit
+        boss.hit();
+        assert!(boss.defeated());
+        assert_eq!(boss.zoom(), 1.3);
+        boss.hit();
+        assert_eq!(boss.health, 0);
+    }
+
+    #[test]
+    fn a_hit_past_several_thresholds_skips_their_phases() {
+        let mut boss = Boss::new(
+            Float2(0.0, 0.0),
+            5,
+            vec![phase(4, 1.0), phase(3, 2.0), phase(0, 3.0)],
+        );
+        boss.health = 4;
+        boss.hit();
+        assert_eq!((boss.health, boss.phase), (3, 2));
+        assert_eq!(boss.zoom(), 3.0);
+    }
+
+    #[test]
+    fn scripts_fire_on_their_offsets_and_loop() {
+        let mut boss = Boss::redshift_boss(Float2(0.0, 0.0));
+        //lanes at 0, sine lanes at 1.5 and a jumprope at 3 in a 4 second loop
+        assert!(matches!(
+            boss.update(1.0)[..],
+            [BossAction::Lasers(LaserPattern::Lanes)]
+        ));
+        assert!(boss.update(0.4).is_empty());
+        assert!(matches!(
+            boss.update(0.2)[..],
+            [BossAction::Lasers(LaserPattern::SineLanes(_))]
+        ));
+        assert!(matches!(boss.update(1.5)[..], [BossAction::Jumprope]));
+        assert!(matches!(
+            boss.update(1.0)[..],
+            [BossAction::Lasers(LaserPattern::Lanes)]
+        ));
+    }
+
+    #[test]
+    fn well_pull_falls_off_and_is_capped_by_strength() {
+        let well = GravityWell::new(Float2(0.0, 0.0), 900.0, 300.0, 4.0);
+        //nothing at or past the edge, nothing right on the center
+        assert_eq!(well.pull(Float2(300.0, 0.0)), Float2(0.0, 0.0));
+        assert_eq!(well.pull(Float2(500.0, 0.0)), Float2(0.0, 0.0));
+        assert_eq!(well.pull(Float2(0.5, 0.0)), Float2(0.0, 0.0));
+        let far = well.pull(Float2(200.0, 0.0));
+        let near = well.pull(Float2(0.0, -50.0));
+        assert!(far.0 < 0.0 && far.1 == 0.0, "{far:?}");
+        assert!(near.1 > 0.0 && near.0 == 0.0, "{near:?}");
+        //linear from nothing at the edge to the full strength at the center
+        assert!((far.magnitude() - 300.0).abs() < 1e-3);
+        assert!((near.magnitude() - 750.0).abs() < 1e-3);
+        for distance in [1.0, 10.0, 100.0, 299.0] {
+            assert!(well.pull(Float2(distance, 0.0)).magnitude() <= well.strength);
+        }
+    }
+
+    #[test]
+    fn redshift_walks_the_short_way_to_red() {
+        for index in 0..COLOR_STEPS {
+            assert_eq!(
+                palette_index(palette(index, COLOR_STEPS), COLOR_STEPS),
+                index
+            );
+        }
+        let shift = |index, steps| {
+            let shifted = redshift(palette(index, COLOR_STEPS), steps, COLOR_STEPS);
+            palette_index(shifted, COLOR_STEPS)
+        };
+        assert_eq!(shift(4, 0), 4);
+        assert_eq!(shift(2, 1), 1);
+        assert_eq!(shift(2, 5), 0);
+        assert_eq!(shift(5, 1), 6);
+        assert_eq!(shift(6, 1), 0);
+        assert_eq!(shift(0, 3), 0);
+        //a narrowed palette walks its own steps
+        let five = redshift(palette(3, 5), 1, 5);
+        assert_eq!(palette_index(five, 5), 4);
+        let faded = Float4(0.0, 0.0, 1.0, 0.5);
+        assert_eq!(redshift(faded, 1, COLOR_STEPS).3, 0.5);
+    }
+
+    #[test]
+    fn well_redshift_grows_towards_the_center() {
+        let well = GravityWell::new(Float2(0.0, 0.0), 900.0, 300.0, 4.0);
+        let blue = palette(4, COLOR_STEPS);
+        let at = |x| {
+            let shifted = well.redshift(blue, Float2(x, 0.0), 3, COLOR_STEPS);
+            palette_index(shifted, COLOR_STEPS)
+        };
+        assert_eq!(at(400.0), 4);
+        assert_eq!(at(250.0), 5);
+        assert_eq!(at(150.0), 6);
+        assert_eq!(at(0.0), 0);
+    }
+}
@@ -0,0 +1,349 @@
+use hsv::hsv_to_rgb;
+
+use crate::boss::palette_index;
+use crate::game::{Game, PLAYER_HEIGHT, PLAYER_WIDTH};
+use crate::input::{Action, Input};
+use crate::maths::{Float2, Float4, dot, float2_add, float2_subtract, scale2};
+use crate::walls::Wall;
+use crate::{build_rect, color_convert, obb_intersect, palette_t, stepped_hue};
+
+//how far ahead hazards are swept against the player, and in what steps
+const LOOKAHEAD: f32 = 0.6;
+const LOOKAHEAD_STEP: f32 = 0.05;
+//slack around the player box so near misses count as threats
+const THREAT_MARGIN: f32 = 20.0;
+//threats arriving this soon after the first can't be covered by the same hue switch
+const SAME_WAVE: f32 = 0.15;
+//switches to the goal's hue inside this distance when nothing is incoming
+const GOAL_HUE_DISTANCE: f32 = 200.0;
+//landing zones are avoided once a bomb is this far through its flight
+const CLUSTER_AVOID_PROGRESS: f32 = 0.5;
+const DODGE_WEIGHT: f32 = 3.0;
+//a movement axis is only pressed when it carries this much of the wanted direction
+const AXIS_DEAD_ZONE: f32 = 0.35;
+const ARRIVE_DISTANCE: f32 = 8.0;
+//how far ahead the path to the target is checked against arena walls
+const WALL_PROBE: f32 = 60.0;
+
+#[derive(Debug, Clone, Copy)]
+struct Threat {
+    time: f32,
+    position: Float2,
+    velocity: Float2,
+    color: Float4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    //matches incoming hazards, dodges the rest and runs goals
+    Scripted,
+    //only matches hue and never moves, for how much the hazards take on their own
+    Stationary,
+}
+
+impl Policy {
+    pub const ALL: [Policy; 2] = [Policy::Scripted, Policy::Stationary];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Policy::Scripted => "scripted",
+            Policy::Stationary => "stationary",
+        }
+    }
+    pub fn named(name: &str) -> Option<Policy> {
+        Policy::ALL.into_iter().find(|policy| policy.name() == name)
+    }
+}
+
+//plays through Input like a human would
+#[derive(Debug, Clone)]
+pub struct Bot {
+    pub policy: Policy,
+    hue: f64,
+    //the blocked axis and the way round the wall, kept until that axis is clear
+    detour: Option<(Float2, Float2)>,
+}
+
+impl Bot {
+    pub fn new(policy: Policy) -> Self {
+        Bot {
+            policy,
+            hue: 0.0,
+            detour: None,
+        }
+    }
+
+    //hazards whose swept box meets the player within the lookahead, soonest first
+    fn threats(game: &Game) -> Vec<Threat> {
+        let player = build_rect(
+            game.position.0,
+            game.position.1,
+            PLAYER_WIDTH + THREAT_MARGIN,
+            PLAYER_HEIGHT + THREAT_MARGIN,
+            0.0,
+            Float4(0.0, 0.0, 0.0, 0.0),
+        );
+        let world = &game.world;
+        let mut threats = Vec::new();
+        for entity in world.entities() {
+            let (Some(transform), Some(velocity), Some(color)) = (
+                world.transforms.get(entity),
+                world.velocities.get(entity),
+                world.colors.get(entity),
+            ) else {
+                continue;
+            };
+            let mut time = 0.0;
+            while time <= LOOKAHEAD {
+                let position = float2_add(transform.position, scale2(velocity.0, time));
+                let rect = build_rect(
+                    position.0,
+                    position.1,
+                    transform.size.0,
+                    transform.size.1,
+                    transform.rotation,
+                    color.color,
+                );
+                if obb_intersect(&player, &rect) {
+                    threats.push(Threat {
+                        time,
+                        position,
+                        velocity: velocity.0,
+                        color: color.color,
+                    });
+                    break;
+                }
+                time += LOOKAHEAD_STEP;
+            }
+        }
+        threats.sort_by(|a, b| a.time.total_cmp(&b.time));
+        threats
+    }
+
+    //sets this frame's held actions and hue from what the game shows
+    pub fn act(&mut self, game: &Game, input: &mut Input) {
+        let threats = Self::threats(game);
+        let steps = game.palette_steps();
+        //the first threat decides the hue, everything else of another color is dodged
+        let matched = threats.first().map(|threat| {
+            (
+                threat.time,
+                palette_t(palette_index(threat.color, steps), steps),
+            )
+        });
+        let goal = game
+            .goals
+            .iter()
+            .min_by(|a, b| {
+                let distance = |position| float2_subtract(position, game.position).magnitude();
+                distance(a.position).total_cmp(&distance(b.position))
+            })
+            .copied();
+        match (matched, goal) {
+            (Some((_, t)), _) => self.hue = t,
+            (None, Some(goal))
+                if game.carrying.is_none()
+                    && float2_subtract(goal.position, game.position).magnitude()
+                        < GOAL_HUE_DISTANCE =>
+            {
+                self.hue = goal.t
+            }
+            _ => (),
+        }
+        input.set_hue(self.hue);
+        if self.policy == Policy::Stationary {
+            return;
+        }
+        let color = color_convert(hsv_to_rgb(stepped_hue(self.hue, steps), 1.0, 1.0));
+
+        let target = match (game.carrying, goal) {
+            (Some(_), _) => Some(game.arena.delivery().center),
+            (None, Some(goal)) => Some(goal.position),
+            (None, None) => None,
+        };
+        let mut wanted = match target {
+            Some(target) => {
+                let offset = float2_subtract(target, game.position);
+                if offset.magnitude() > ARRIVE_DISTANCE {
+                    offset.normalized()
+                } else {
+                    Float2(0.0, 0.0)
+                }
+            }
+            None => Float2(0.0, 0.0),
+        };
+        //a press blocked by a wall on every axis it uses walks round the end of the wall nearer the target,
+        //a diagonal one is left to slide along it
+        let half = Float2(PLAYER_WIDTH / 2.0, PLAYER_HEIGHT / 2.0);
+        let probe = |direction: Float2| {
+            game.arena.blocked(
+                float2_add(game.position, scale2(direction, WALL_PROBE)),
+                half,
+            )
+        };
+        if let Some((axis, _)) = self.detour
+            && !probe(axis)
+        {
+            self.detour = None;
+        }
+        let Float2(x, y) = pressed(wanted);
+        let axes = [Float2(x, 0.0), Float2(0.0, y)];
+        let mut used = axes
+            .into_iter()
+            .filter(|axis| axis.magnitude() > 0.0)
+            .peekable();
+        if self.detour.is_none() && used.peek().is_some() && used.all(probe) {
+            let axis = if x != 0.0 { axes[0] } else { axes[1] };
+            let across = Float2(axis.1.abs(), axis.0.abs());
+            let ahead = float2_add(game.position, scale2(axis, WALL_PROBE));
+            let nearest = game.arena.walls.iter().min_by(|a, b| {
+                let distance = |wall: &Wall| float2_subtract(wall.position, ahead).magnitude();
+                distance(a).total_cmp(&distance(b))
+            });
+            let target = target.unwrap_or(game.position);
+            let length = |sign: f32| {
+                let Some(wall) = nearest else {
+                    return 0.0;
+                };
+                let reach = (wall.width * across.0 + wall.height * across.1 + PLAYER_WIDTH) / 2.0;
+                let end = float2_add(wall.position, scale2(across, sign * reach));
+                float2_subtract(end, game.position).magnitude()
+                    + float2_subtract(target, end).magnitude()
+            };
+            let mut signs = [1.0, -1.0];
+            signs.sort_by(|a, b| length(*a).total_cmp(&length(*b)));
+            let sign = signs
+                .into_iter()
+                .find(|&sign| !probe(scale2(across, sign)))
+                .unwrap_or(signs[0]);
+            self.detour = Some((axis, scale2(across, sign)));
+        }
+        if let Some((_, side)) = self.detour {
+            wanted = side;
+        }
+
+        //step sideways out of the path of mismatched hazards, hardest for the ones closest to landing
+        let first = matched.map_or(0.0, |(time, _)| time);
+        for threat in threats.iter().filter(|threat| threat.color != color) {
+            if matched.is_some() && threat.time > first + SAME_WAVE {
+                continue;
+            }
+            let away = float2_subtract(game.position, threat.position);
+            let across = Float2(-threat.velocity.1, threat.velocity.0);
+            if across.magnitude() == 0.0 {
+                continue;
+            }
+            let side = if dot(across, away) >= 0.0 {
+                across
+            } else {
+                scale2(across, -1.0)
+            };
+            let urgency = 1.0 - threat.time / LOOKAHEAD;
+            wanted = float2_add(wanted, scale2(side.normalized(), DODGE_WEIGHT * urgency));
+        }
+        for bomb in game.clusters.iter() {
+            if bomb.color == color || bomb.progress() < CLUSTER_AVOID_PROGRESS {
+                continue;
+            }
+            let away = float2_subtract(game.position, bomb.end_pos);
+            let reach = bomb.blast_radius + PLAYER_WIDTH;
+            if away.magnitude() > 0.0 && away.magnitude() < reach {
+                wanted = float2_add(wanted, scale2(away.normalized(), DODGE_WEIGHT));
+            }
+        }
+
+        let Float2(x, y) = pressed(wanted);
+        for (component, negative, positive) in [
+            (x, Action::Left, Action::Right),
+            (y, Action::Down, Action::Up),
+        ] {
+            input.release(negative);
+            input.release(positive);
+            if component != 0.0 {
+                input.press(if component > 0.0 { positive } else { negative });
+            }
+        }
+    }
+}
+
+//the -1, 0 or 1 per axis a wanted direction turns into, an axis needs enough of the direction to count
+fn pressed(wanted: Float2) -> Float2 {
+    let length = wanted.magnitude();
+    let axis = |component: f32| {
+        if length > 0.0 && component.abs() / length >= AXIS_DEAD_ZONE {
+            component.signum()
+        } else {
+            0.0
+        }
+    };
+    Float2(axis(wanted.0), axis(wanted.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::COLOR_STEPS;
+    use crate::game::Difficulty;
+
+    //a laser sized hazard coming in from the right just above the player
+    fn incoming(game: &mut Game, color: Float4) {
+        let position = float2_add(game.position, Float2(300.0, 10.0));
+        game.world.spawn_fragment(
+            position,
+            Float2(-450.0, 0.0),
+            Float2(100.0, 10.0),
+            color,
+            10.0,
+            1.0,
+        );
+    }
+
+    fn game() -> Game {
+        let mut game = Game::with_difficulty(Difficulty::NORMAL);
+        game.verbose = false;
+        game.goals.clear();
+        game
+    }
+
+    #[test]
+    fn an_incoming_hazard_decides_the_hue() {
+        let mut game = game();
+        let color = color_convert(hsv_to_rgb(
+            stepped_hue(palette_t(3, COLOR_STEPS), COLOR_STEPS),
+            1.0,
+            1.0,
+        ));
+        incoming(&mut game, color);
+        let mut input = Input::default();
+        Bot::new(Policy::Stationary).act(&game, &mut input);
+        assert_eq!(
+            stepped_hue(input.hue, COLOR_STEPS),
+            stepped_hue(palette_t(3, COLOR_STEPS), COLOR_STEPS)
+        );
+    }
+
+    #[test]
+    fn a_hazard_that_cant_be_matched_is_stepped_out_of() {
+        //grey is no palette hue, so the only way through is sideways
+        let grey = Float4(0.5, 0.5, 0.5, 1.0);
+        let fragment_loss = |policy| {
+            let mut game = game();
+            incoming(&mut game, grey);
+            let mut bot = Bot::new(policy);
+            let mut input = Input::default();
+            bot.act(&game, &mut input);
+            //it sweeps along the player's row from above, so the way out is down
+            if policy == Policy::Scripted {
+                assert!(input.held(Action::Down) && !input.held(Action::Up));
+            }
+            for _ in 0..60 {
+                bot.act(&game, &mut input);
+                game.step(&input);
+                input.end_frame();
+            }
+            game.signal.totals().fragment
+        };
+        assert_eq!(fragment_loss(Policy::Scripted), 0.0);
+        assert!(fragment_loss(Policy::Stationary) > 0.0);
+    }
+}
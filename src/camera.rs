@@ -0,0 +1,220 @@
+use crate::maths::{Float2, apply_rotation_float2, float2_add, float2_subtract, scale2};
+use crate::rng::random;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CameraTuning {
+    //fraction of the distance to the target covered per second
+    pub follow_rate: f32,
+    pub zoom_rate: f32,
+    //trauma lost per second, shake strength goes with trauma squared
+    pub trauma_decay: f32,
+    pub max_shake_offset: f32,
+    pub max_shake_angle: f32,
+}
+
+impl Default for CameraTuning {
+    fn default() -> Self {
+        CameraTuning {
+            follow_rate: 6.0,
+            zoom_rate: 2.0,
+            trauma_decay: 1.5,
+            max_shake_offset: 24.0,
+            max_shake_angle: 0.04,
+        }
+    }
+}
+
+//viewport is the half extent shown at zoom 1, bounds the half extent of the world around the origin
+#[derive(Debug, Clone)]
+pub struct Camera2D {
+    pub position: Float2,
+    pub zoom: f32,
+    pub rotation: f32,
+    pub trauma: f32,
+    pub viewport: Float2,
+    pub bounds: Option<Float2>,
+    pub tuning: CameraTuning,
+    target_zoom: f32,
+    shake_offset: Float2,
+    shake_angle: f32,
+}
+
+impl Camera2D {
+    pub fn new(viewport: Float2) -> Self {
+        Camera2D {
+            position: Float2(0.0, 0.0),
+            zoom: 1.0,
+            rotation: 0.0,
+            trauma: 0.0,
+            viewport,
+            bounds: None,
+            tuning: CameraTuning::default(),
+            target_zoom: 1.0,
+            shake_offset: Float2(0.0, 0.0),
+            shake_angle: 0.0,
+        }
+    }
+    pub fn with_bounds(mut self, bounds: Float2) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+    pub fn zoom_to(&mut self, zoom: f32) {
+        self.target_zoom = zoom;
+    }
+
+    //half extent of the world currently on screen
+    pub fn visible(&self) -> Float2 {
+        scale2(self.viewport, 1.0 / self.zoom)
+    }
+
+    //eases towards the target and zoom, keeps the view inside the bounds and samples this frame's shake
+    pub fn update(&mut self, target: Float2, delta_t: f32) {
+        let follow = (self.tuning.follow_rate * delta_t).min(1.0);
+        self.position = float2_add(
+            self.position,
+            scale2(float2_subtract(target, self.position), follow),
+        );
+        let zoom_step = (self.tuning.zoom_rate * delta_t).min(1.0);
+        self.zoom += (self.target_zoom - self.zoom) * zoom_step;
+        if let Some(bounds) = self.bounds {
+            //a view wider than the world stays centered on it
+            let slack = float2_subtract(bounds, self.visible());
+            self.position = Float2(
+                self.position.0.clamp(-slack.0.max(0.0), slack.0.max(0.0)),
+                self.position.1.clamp(-slack.1.max(0.0), slack.1.max(0.0)),
+            );
+        }
+
+        self.trauma = (self.trauma - self.tuning.trauma_decay * delta_t).max(0.0);
+        let shake = self.trauma * self.trauma;
+        let jitter = || random::<f32>() * 2.0 - 1.0;
+        self.shake_offset = Float2(
+            jitter() * shake * self.tuning.max_shake_offset,
+            jitter() * shake * self.tuning.max_shake_offset,
+        );
+        self.shake_angle = jitter() * shake * self.tuning.max_shake_angle;
+    }
+
+    //world position relative to the view, rotated and zoomed, in viewport units
+    pub fn world_to_view(&self, point: Float2) -> Float2 {
+        let eye = float2_add(self.position, self.shake_offset);
+        let rotated = apply_rotation_float2(
+            float2_subtract(point, eye),
+            -(self.rotation + self.shake_angle),
+        );
+        scale2(rotated, self.zoom)
+    }
+
+    //inverse of world_to_view, puts screen anchored quads under the same projection as the world
+    pub fn view_to_world(&self, point: Float2) -> Float2 {
+        let eye = float2_add(self.position, self.shake_offset);
+        float2_add(
+            apply_rotation_float2(
+                scale2(point, 1.0 / self.zoom),
+                self.rotation + self.shake_angle,
+            ),
+            eye,
+        )
+    }
+
+    //column major, maps world space straight to clip space
+    pub fn view_projection(&self) -> [[f32; 4]; 4] {
+        let scale_x = self.zoom / self.viewport.0;
+        let scale_y = self.zoom / self.viewport.1;
+        let angle = -(self.rotation + self.shake_angle);
+        let (sin, cos) = angle.sin_cos();
+        let eye = float2_add(self.position, self.shake_offset);
+        [
+            [scale_x * cos, scale_y * sin, 0.0, 0.0],
+            [-scale_x * sin, scale_y * cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [
+                -scale_x * (cos * eye.0 - sin * eye.1),
+                -scale_y * (sin * eye.0 + cos * eye.1),
+                0.0,
+                1.0,
+            ],
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+
+    fn close(a: Float2, b: Float2) -> bool {
+        (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3
+    }
+
+    //clip space x and y of a world point
+    fn project(matrix: &[[f32; 4]; 4], point: Float2) -> Float2 {
+        Float2(
+            matrix[0][0] * point.0 + matrix[1][0] * point.1 + matrix[3][0],
+            matrix[0][1] * point.0 + matrix[1][1] * point.1 + matrix[3][1],
+        )
+    }
+
+    #[test]
+    fn following_stops_at_the_world_edge() {
+        let mut camera = Camera2D::new(Float2(100.0, 50.0)).with_bounds(Float2(300.0, 200.0));
+        for _ in 0..10 {
+            camera.update(Float2(1000.0, -1000.0), 1.0);
+        }
+        assert!(close(camera.position, Float2(200.0, -150.0)));
+        //zoomed in shows less, so the view can get closer to the edge
+        camera.zoom_to(2.0);
+        for _ in 0..10 {
+            camera.update(Float2(1000.0, -1000.0), 1.0);
+        }
+        assert!(close(camera.position, Float2(250.0, -175.0)));
+    }
+
+    #[test]
+    fn a_world_smaller_than_the_view_stays_centered() {
+        let mut camera = Camera2D::new(Float2(400.0, 300.0)).with_bounds(Float2(300.0, 200.0));
+        camera.update(Float2(250.0, 150.0), 1.0);
+        assert_eq!(camera.position, Float2(0.0, 0.0));
+    }
+
+    #[test]
+    fn the_hard_world_is_larger_than_the_view_and_bounds_the_camera() {
+        use crate::game::{Difficulty, Game, VIEW_HEIGHT, VIEW_WIDTH};
+
+        let game = Game::with_difficulty(Difficulty::HARD);
+        assert!(game.arena.bounds.0 > VIEW_WIDTH && game.arena.bounds.1 > VIEW_HEIGHT);
+        assert_eq!(game.camera.bounds, Some(game.arena.bounds));
+    }
+
+    #[test]
+    fn view_projection_agrees_with_world_to_view_under_zoom_and_shake() {
+        rng::seed(7);
+        let viewport = Float2(100.0, 50.0);
+        let mut camera = Camera2D::new(viewport);
+        camera.zoom_to(2.0);
+        for _ in 0..10 {
+            camera.update(Float2(30.0, -20.0), 1.0);
+        }
+        //the visible corner lands on the clip space corner
+        let corner = float2_add(camera.position, camera.visible());
+        assert!(close(
+            project(&camera.view_projection(), corner),
+            Float2(1.0, 1.0)
+        ));
+
+        camera.add_trauma(1.0);
+        camera.update(Float2(30.0, -20.0), 0.01);
+        assert_ne!(camera.world_to_view(camera.position), Float2(0.0, 0.0));
+        let matrix = camera.view_projection();
+        for point in [Float2(30.0, -20.0), Float2(-40.0, 10.0), Float2(75.0, 5.0)] {
+            let view = camera.world_to_view(point);
+            let clip = Float2(view.0 / viewport.0, view.1 / viewport.1);
+            assert!(close(project(&matrix, point), clip));
+            assert!(close(camera.view_to_world(view), point));
+        }
+    }
+}
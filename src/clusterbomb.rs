@@ -0,0 +1,386 @@
+use std::f32::consts::PI;
+
+use hsv::hsv_to_rgb;
+
+use crate::maths::{Float2, Float4, float2_add, float2_subtract};
+use crate::rng::random;
+use crate::{color_convert, stepped_hue};
+
+pub const CLUSTER_GRAVITY: f32 = 700.0;
+pub const CLUSTER_MAX_LAUNCH_SPEED: f32 = 1000.0;
+
+//  spiral arms go from half speed on the first fragment to full speed on the last
+const SPIRAL_MIN_SPEED: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FragmentPattern {
+    Ring,
+    Spiral,
+    Spread,
+}
+
+impl FragmentPattern {
+    pub fn random() -> Self {
+        match (random::<f32>() * 3.0) as u32 {
+            0 => FragmentPattern::Ring,
+            1 => FragmentPattern::Spiral,
+            _ => FragmentPattern::Spread,
+        }
+    }
+
+    pub fn velocities(&self, count: u32, speed: f32) -> Vec<Float2> {
+        let theta_step = 2.0 * PI / count as f32;
+        (0..count)
+            .map(|i| {
+                let (theta, frag_speed) = match self {
+                    FragmentPattern::Ring => (i as f32 * theta_step, speed),
+                    FragmentPattern::Spiral => (
+                        i as f32 * theta_step,
+                        speed
+                            * (SPIRAL_MIN_SPEED
+                                + (1.0 - SPIRAL_MIN_SPEED) * i as f32 / count as f32),
+                    ),
+                    FragmentPattern::Spread => (
+                        random::<f32>() * 2.0 * PI,
+                        speed * (SPIRAL_MIN_SPEED + random::<f32>() * (1.0 - SPIRAL_MIN_SPEED)),
+                    ),
+                };
+                Float2(theta.cos() * frag_speed, theta.sin() * frag_speed)
+            })
+            .collect()
+    }
+}
+
+//how a lob is constrained, flight time in seconds or apex as an absolute world height
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlightSpec {
+    Time(f32),
+    Apex(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrajectoryError {
+    NonFinite,
+    //the solvers divide by it and a bomb has to come back down
+    NonPositiveGravity,
+    NonPositiveTime,
+    ApexBelowEndpoints,
+    TooFast { speed: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trajectory {
+    pub x_vel: f32,
+    pub y_vel: f32,
+    pub y_accel: f32,
+    pub end_t: f32,
+}
+
+impl Trajectory {
+    pub fn position_at(&self, start_pos: Float2, t: f32) -> Float2 {
+        float2_add(
+            start_pos,
+            Float2(
+                self.x_vel * t,
+                (self.y_vel * t) - 0.5 * (self.y_accel * t.powf(2.0)),
+            ),
+        )
+    }
+    pub fn launch_speed(&self) -> f32 {
+        Float2(self.x_vel, self.y_vel).magnitude()
+    }
+}
+
+pub fn solve_trajectory(
+    start_pos: Float2,
+    target: Float2,
+    spec: FlightSpec,
+    gravity: f32,
+) -> Result<Trajectory, TrajectoryError> {
+    let pos_diff = float2_subtract(target, start_pos);
+    let spec_value = match spec {
+        FlightSpec::Time(val) | FlightSpec::Apex(val) => val,
+    };
+    let finite = pos_diff.0.is_finite()
+        && pos_diff.1.is_finite()
+        && spec_value.is_finite()
+        && gravity.is_finite();
+    if !finite {
+        return Err(TrajectoryError::NonFinite);
+    }
+    if gravity <= 0.0 {
+        return Err(TrajectoryError::NonPositiveGravity);
+    }
+
+    let (y_vel, end_t) = match spec {
+        FlightSpec::Time(end_t) => {
+            if end_t <= 0.0 {
+                return Err(TrajectoryError::NonPositiveTime);
+            }
+            //dy = v * t - g * t^2 / 2
+            (
+                (pos_diff.1 + 0.5 * gravity * end_t.powf(2.0)) / end_t,
+                end_t,
+            )
+        }
+        FlightSpec::Apex(apex_y) => {
+            if apex_y < start_pos.1.max(target.1) {
+                return Err(TrajectoryError::ApexBelowEndpoints);
+            }
+            //rise to the apex, then fall from it onto the target
+            let rise = apex_y - start_pos.1;
+            let fall = apex_y - target.1;
+            let y_vel = (2.0 * gravity * rise).sqrt();
+            let end_t = (y_vel + (2.0 * gravity * fall).sqrt()) / gravity;
+            if end_t <= 0.0 {
+                return Err(TrajectoryError::NonPositiveTime);
+            }
+            (y_vel, end_t)
+        }
+    };
+
+    let trajectory = Trajectory {
+        x_vel: pos_diff.0 / end_t,
+        y_vel,
+        y_accel: gravity,
+        end_t,
+    };
+    let speed = trajectory.launch_speed();
+    if speed > CLUSTER_MAX_LAUNCH_SPEED {
+        return Err(TrajectoryError::TooFast { speed });
+    }
+    Ok(trajectory)
+}
+
+//even split between timed lobs and lobs over a random apex above both ends
+pub fn random_spec(start_pos: Float2, target: Float2, min_t: f32, max_t: f32) -> FlightSpec {
+    if random::<bool>() {
+        FlightSpec::Time(min_t + random::<f32>() * (max_t - min_t))
+    } else {
+        FlightSpec::Apex(start_pos.1.max(target.1) + 150.0 + random::<f32>() * 450.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct Clusterbomb {
+    pub start_pos: Float2,
+    pub end_pos: Float2,
+    trajectory: Trajectory,
+    pub color: Float4,
+    pub t: f32,
+    pub blast_radius: f32,
+    pub pattern: FragmentPattern,
+}
+
+impl Clusterbomb {
+    pub fn new(start_pos: Float2, trajectory: Trajectory, color: Float4) -> Self {
+        Clusterbomb {
+            start_pos,
+            end_pos: trajectory.position_at(start_pos, trajectory.end_t),
+            trajectory,
+            color,
+            t: 0.0,
+            blast_radius: 0.0,
+            pattern: FragmentPattern::Ring,
+        }
+    }
+    pub fn update(&mut self, delta_t: f32) -> Float2 {
+        self.t += delta_t;
+        self.position()
+    }
+    pub fn position(&self) -> Float2 {
+        self.position_at(self.t)
+    }
+    //seconds since launch, for drawing the rest of the flight
+    pub fn position_at(&self, t: f32) -> Float2 {
+        self.trajectory.position_at(self.start_pos, t)
+    }
+    pub fn from_positions(
+        start_pos: Float2,
+        end_pos: Float2,
+        spec: FlightSpec,
+        color: Float4,
+    ) -> Result<Self, TrajectoryError> {
+        let trajectory = solve_trajectory(start_pos, end_pos, spec, CLUSTER_GRAVITY)?;
+        Ok(Clusterbomb::new(start_pos, trajectory, color))
+    }
+    //random palette color and fragment pattern, None when the solver can't make the shot
+    pub fn lob(
+        start_pos: Float2,
+        target: Float2,
+        min_t: f32,
+        max_t: f32,
+        blast_radius: f32,
+        palette_steps: u32,
+    ) -> Option<Self> {
+        let spec = random_spec(start_pos, target, min_t, max_t);
+        let color = color_convert(hsv_to_rgb(stepped_hue(random(), palette_steps), 1.0, 1.0));
+        Clusterbomb::from_positions(start_pos, target, spec, color)
+            .ok()
+            .map(|bomb| bomb.with_blast(blast_radius, FragmentPattern::random()))
+    }
+    pub fn with_blast(mut self, blast_radius: f32, pattern: FragmentPattern) -> Self {
+        self.blast_radius = blast_radius;
+        self.pattern = pattern;
+        self
+    }
+    pub fn end_t(&self) -> f32 {
+        self.trajectory.end_t
+    }
+    //0 at launch, 1 on detonation, drives the ground telegraph fill
+    pub fn progress(&self) -> f32 {
+        (self.t / self.end_t()).clamp(0.0, 1.0)
+    }
+    pub fn detonated(&self) -> bool {
+        self.t >= self.end_t()
+    }
+}
+
+//expanding flash left behind by a detonation, damage is only dealt on the detonation frame
+pub struct Blast {
+    pub position: Float2,
+    pub radius: f32,
+    pub color: Float4,
+    pub lifetime: f32,
+}
+
+impl Blast {
+    pub fn new(bomb: &Clusterbomb) -> Self {
+        Blast {
+            position: bomb.end_pos,
+            radius: bomb.blast_radius,
+            color: bomb.color,
+            lifetime: 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLOR: Float4 = Float4(1.0, 0.0, 0.0, 1.0);
+
+    fn assert_lands(bomb: &mut Clusterbomb) {
+        let end_t = bomb.end_t();
+        let landed = bomb.update(end_t);
+        assert!(
+            (landed.0 - bomb.end_pos.0).abs() < 0.01,
+            "{landed:?} vs {:?}",
+            bomb.end_pos
+        );
+        assert!(
+            (landed.1 - bomb.end_pos.1).abs() < 0.01,
+            "{landed:?} vs {:?}",
+            bomb.end_pos
+        );
+        assert!(bomb.detonated());
+    }
+
+    #[test]
+    fn timed_lob_lands_on_target() {
+        let target = Float2(300.0, -200.0);
+        let mut bomb = Clusterbomb::from_positions(
+            Float2(-400.0, 100.0),
+            target,
+            FlightSpec::Time(2.5),
+            COLOR,
+        )
+        .unwrap();
+        assert_eq!(bomb.end_t(), 2.5);
+        assert!((bomb.end_pos.0 - target.0).abs() < 0.01);
+        assert!((bomb.end_pos.1 - target.1).abs() < 0.01);
+        assert_lands(&mut bomb);
+    }
+
+    #[test]
+    fn apex_lob_lands_on_target_and_peaks_at_apex() {
+        let start = Float2(0.0, -300.0);
+        let target = Float2(200.0, 100.0);
+        let mut bomb =
+            Clusterbomb::from_positions(start, target, FlightSpec::Apex(400.0), COLOR).unwrap();
+        let peak_t = bomb.trajectory.y_vel / CLUSTER_GRAVITY;
+        let peak = bomb.trajectory.position_at(start, peak_t);
+        assert!((peak.1 - 400.0).abs() < 0.01);
+        assert_lands(&mut bomb);
+    }
+
+    #[test]
+    fn incremental_updates_land_on_target() {
+        let mut bomb = Clusterbomb::from_positions(
+            Float2(500.0, 500.0),
+            Float2(-100.0, -50.0),
+            FlightSpec::Time(3.0),
+            COLOR,
+        )
+        .unwrap();
+        let mut landed = Float2(0.0, 0.0);
+        for _ in 0..180 {
+            landed = bomb.update(1.0 / 60.0);
+        }
+        assert!((landed.0 - bomb.end_pos.0).abs() < 0.5);
+        assert!((landed.1 - bomb.end_pos.1).abs() < 0.5);
+    }
+
+    #[test]
+    fn new_matches_update() {
+        let trajectory = Trajectory {
+            x_vel: 120.0,
+            y_vel: 600.0,
+            y_accel: 300.0,
+            end_t: 1.75,
+        };
+        let mut bomb = Clusterbomb::new(Float2(-20.0, 40.0), trajectory, COLOR);
+        assert_lands(&mut bomb);
+    }
+
+    #[test]
+    fn impossible_shots_are_rejected() {
+        let start = Float2(0.0, 0.0);
+        assert_eq!(
+            solve_trajectory(
+                start,
+                Float2(10.0, 0.0),
+                FlightSpec::Time(0.0),
+                CLUSTER_GRAVITY
+            ),
+            Err(TrajectoryError::NonPositiveTime)
+        );
+        assert_eq!(
+            solve_trajectory(
+                start,
+                Float2(10.0, 200.0),
+                FlightSpec::Apex(100.0),
+                CLUSTER_GRAVITY
+            ),
+            Err(TrajectoryError::ApexBelowEndpoints)
+        );
+        assert_eq!(
+            solve_trajectory(
+                start,
+                Float2(f32::NAN, 0.0),
+                FlightSpec::Time(1.0),
+                CLUSTER_GRAVITY
+            ),
+            Err(TrajectoryError::NonFinite)
+        );
+        for gravity in [0.0, -CLUSTER_GRAVITY] {
+            for spec in [FlightSpec::Time(1.0), FlightSpec::Apex(100.0)] {
+                assert_eq!(
+                    solve_trajectory(start, Float2(10.0, 0.0), spec, gravity),
+                    Err(TrajectoryError::NonPositiveGravity)
+                );
+            }
+        }
+        //the old fixed launch speed produced a NaN y velocity here
+        assert!(matches!(
+            solve_trajectory(
+                start,
+                Float2(5000.0, 0.0),
+                FlightSpec::Time(3.0),
+                CLUSTER_GRAVITY
+            ),
+            Err(TrajectoryError::TooFast { .. })
+        ));
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write as _};
+use std::path::Path;
+
+use chrono::{Datelike, Local, NaiveDate};
+
+use crate::game::{FPS, Game};
+
+//one line per scored daily attempt, the first run of a day is the one that counts
+pub const SCORES_FILE: &str = "lost-signal-scores.txt";
+//the last daily result, ready to paste
+const SUMMARY_FILE: &str = "lost-signal-daily.txt";
+
+const FAST_LASERS: f32 = 1.3;
+const FAST_JUMPROPES: f32 = 1.3;
+const FEW_COLORS: u32 = 5;
+const EXTRA_LANES: usize = 3;
+//more than this and some days stop being fun
+const MAX_MODIFIERS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    FastLasers,
+    FastJumpropes,
+    NoClusters,
+    FiveColors,
+    ExtraLanes,
+}
+
+impl Modifier {
+    pub const ALL: [Modifier; 5] = [
+        Modifier::FastLasers,
+        Modifier::FastJumpropes,
+        Modifier::NoClusters,
+        Modifier::FiveColors,
+        Modifier::ExtraLanes,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Modifier::FastLasers => "fast lasers",
+            Modifier::FastJumpropes => "fast jumpropes",
+            Modifier::NoClusters => "no clusters",
+            Modifier::FiveColors => "five colors",
+            Modifier::ExtraLanes => "extra lanes",
+        }
+    }
+}
+
+//splitmix64, spreads neighbouring days far apart
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+//the same seed and modifiers for everyone playing on the same local date
+#[derive(Debug, Clone, PartialEq)]
+pub struct Daily {
+    pub date: NaiveDate,
+    pub seed: u64,
+    pub modifiers: Vec<Modifier>,
+    //false once the day's attempt is recorded, or after a console command
+    pub scored: bool,
+}
+
+impl Daily {
+    pub fn for_date(date: NaiveDate) -> Self {
+        let seed = mix(date.num_days_from_ce() as u64);
+        let bits = mix(seed);
+        let mut modifiers: Vec<Modifier> = Modifier::ALL
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| bits >> index & 1 == 1)
+            .map(|(_, &modifier)| modifier)
+            .take(MAX_MODIFIERS)
+            .collect();
+        if modifiers.is_empty() {
+            modifiers.push(Modifier::ALL[(bits >> 32) as usize % Modifier::ALL.len()]);
+        }
+        Daily {
+            date,
+            seed,
+            modifiers,
+            scored: true,
+        }
+    }
+
+    //a practice run when today's attempt is already in the scores file
+    pub fn today() -> Self {
+        let mut daily = Daily::for_date(Local::now().date_naive());
+        daily.scored = !Scores::load(Path::new(SCORES_FILE)).played(daily.date);
+        daily
+    }
+
+    pub fn has(&self, modifier: Modifier) -> bool {
+        self.modifiers.contains(&modifier)
+    }
+
+    pub fn palette_steps(&self) -> Option<u32> {
+        self.has(Modifier::FiveColors).then_some(FEW_COLORS)
+    }
+
+    //speed and lane modifiers, the seed and palette go in before the game is made
+    pub fn apply(&self, game: &mut Game) {
+        if self.has(Modifier::FastLasers) {
+            game.laser_speed *= FAST_LASERS;
+        }
+        if self.has(Modifier::FastJumpropes) {
+            game.jumprope_speed *= FAST_JUMPROPES;
+        }
+        if self.has(Modifier::ExtraLanes) {
+            game.current_spawns += EXTRA_LANES;
+        }
+    }
+
+    pub fn modifier_names(&self) -> String {
+        let names: Vec<&str> = self.modifiers.iter().map(Modifier::name).collect();
+        names.join(" + ")
+    }
+
+    pub fn result(&self, game: &Game) -> DailyScore {
+        let breakdown = game.scoring.breakdown;
+        DailyScore {
+            date: self.date,
+            total: breakdown.total(),
+            deliveries: breakdown.deliveries,
+            hits: breakdown.hits,
+            seconds: (game.frames as f32 / FPS) as u32,
+        }
+    }
+
+    //plain text to paste anywhere, best is the record before this run
+    pub fn summary(&self, score: &DailyScore, best: Option<u32>) -> String {
+        let mut text = format!(
+            "lost signal daily {}\n{}\n",
+            self.date,
+            self.modifier_names()
+        );
+        let _ = writeln!(
+            text,
+            "{} points, {} deliveries, {} hits, {}:{:02}",
+            score.total,
+            score.deliveries,
+            score.hits,
+            score.seconds / 60,
+            score.seconds % 60
+        );
+        if !self.scored {
+            text.push_str("practice run, not scored\n");
+        } else if let Some(best) = best {
+            if score.total > best {
+                text.push_str("new best daily score\n");
+            } else {
+                let _ = writeln!(text, "best daily score {best}");
+            }
+        }
+        text
+    }
+
+    //records a scored attempt, then prints and saves the summary
+    pub fn finish(&self, game: &Game) {
+        let path = Path::new(SCORES_FILE);
+        let scores = Scores::load(path);
+        let score = self.result(game);
+        if self.scored
+            && let Err(error) = Scores::record(path, &score)
+        {
+            println!("couldn't save daily score to {SCORES_FILE}: {error}");
+        }
+        let summary = self.summary(&score, scores.best());
+        print!("{summary}");
+        if let Err(error) = fs::write(SUMMARY_FILE, &summary) {
+            println!("couldn't save summary to {SUMMARY_FILE}: {error}");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyScore {
+    pub date: NaiveDate,
+    pub total: u32,
+    pub deliveries: u32,
+    pub hits: u32,
+    pub seconds: u32,
+}
+
+impl DailyScore {
+    fn line(&self) -> String {
+        format!(
+            "daily {} total={} deliveries={} hits={} seconds={}",
+            self.date, self.total, self.deliveries, self.hits, self.seconds
+        )
+    }
+
+    fn parse(line: &str) -> Option<DailyScore> {
+        let mut words = line.split_whitespace();
+        if words.next()? != "daily" {
+            return None;
+        }
+        let date = words.next()?.parse().ok()?;
+        let mut value = |key: &str| -> Option<u32> {
+            let (name, value) = words.next()?.split_once('=')?;
+            if name != key {
+                return None;
+            }
+            value.parse().ok()
+        };
+        Some(DailyScore {
+            date,
+            total: value("total")?,
+            deliveries: value("deliveries")?,
+            hits: value("hits")?,
+            seconds: value("seconds")?,
+        })
+    }
+}
+
+//the local high score file, lines it can't read are left alone and skipped
+#[derive(Debug, Clone, Default)]
+pub struct Scores {
+    pub daily: Vec<DailyScore>,
+}
+
+impl Scores {
+    pub fn load(path: &Path) -> Self {
+        let text = fs::read_to_string(path).unwrap_or_default();
+        Scores {
+            daily: text.lines().filter_map(DailyScore::parse).collect(),
+        }
+    }
+
+    pub fn played(&self, date: NaiveDate) -> bool {
+        self.daily.iter().any(|score| score.date == date)
+    }
+
+    pub fn best(&self) -> Option<u32> {
+        self.daily.iter().map(|score| score.total).max()
+    }
+
+    pub fn record(path: &Path, score: &DailyScore) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", score.line())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn the_date_decides_the_seed_and_modifiers() {
+        let today = Daily::for_date(date(2026, 10, 19));
+        assert_eq!(today, Daily::for_date(date(2026, 10, 19)));
+        assert_ne!(today.seed, Daily::for_date(date(2026, 10, 20)).seed);
+        for day in 1..=28 {
+            let daily = Daily::for_date(date(2026, 2, day));
+            assert!((1..=MAX_MODIFIERS).contains(&daily.modifiers.len()));
+        }
+    }
+
+    #[test]
+    fn daily_games_replay_the_same_and_follow_their_modifiers() {
+        use crate::input::Input;
+
+        let mut daily = Daily::for_date(date(2026, 10, 19));
+        daily.modifiers = vec![Modifier::NoClusters, Modifier::FiveColors];
+        let play = || {
+            let mut game = Game::daily(daily.clone());
+            game.verbose = false;
+            game.score = 20;
+            for _ in 0..300 {
+                game.step(&Input::default());
+            }
+            game
+        };
+        let (first, second) = (play(), play());
+        assert_eq!(
+            first.world.positions(crate::entities::HazardKind::Laser),
+            second.world.positions(crate::entities::HazardKind::Laser)
+        );
+        assert!(first.clusters.is_empty());
+        //five colors means hues 72 degrees apart
+        for goal in first.goals.iter() {
+            assert_eq!(goal.hue(first.palette_steps()) % 72.0, 0.0);
+        }
+    }
+
+    #[test]
+    fn other_games_on_the_thread_leave_the_daily_palette_alone() {
+        use hsv::hsv_to_rgb;
+
+        use crate::game::Difficulty;
+        use crate::{COLOR_STEPS, color_convert, stepped_hue};
+
+        let mut daily = Daily::for_date(date(2026, 10, 19));
+        daily.modifiers = vec![Modifier::FiveColors];
+        let mut game = Game::daily(daily);
+        //a probe or a sim run built and dropped while the daily is still being played
+        drop(Game::with_difficulty(Difficulty::NORMAL));
+        assert_eq!(game.palette_steps(), 5);
+        //a hue that lands on different steps of the full and the five color palette
+        game.hue = 0.5;
+        assert_ne!(stepped_hue(game.hue, 5), stepped_hue(game.hue, COLOR_STEPS));
+        let five = color_convert(hsv_to_rgb(stepped_hue(game.hue, 5), 1.0, 1.0));
+        assert_eq!(game.color(), five);
+    }
+
+    #[test]
+    fn scores_are_recorded_once_a_day_and_read_back() {
+        let path =
+            std::env::temp_dir().join(format!("lost-signal-scores-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let score = DailyScore {
+            date: date(2026, 10, 19),
+            total: 120,
+            deliveries: 9,
+            hits: 2,
+            seconds: 95,
+        };
+        Scores::record(&path, &score).unwrap();
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "garbage line\n").unwrap();
+        Scores::record(&path, &DailyScore { total: 80, ..score }).unwrap();
+        let scores = Scores::load(&path);
+        assert_eq!(scores.daily, vec![score, DailyScore { total: 80, ..score }]);
+        assert!(scores.played(date(2026, 10, 19)));
+        assert!(!scores.played(date(2026, 10, 20)));
+        assert_eq!(scores.best(), Some(120));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn summaries_say_how_the_run_went() {
+        let mut daily = Daily::for_date(date(2026, 10, 19));
+        let score = DailyScore {
+            date: daily.date,
+            total: 150,
+            deliveries: 11,
+            hits: 3,
+            seconds: 125,
+        };
+        let summary = daily.summary(&score, Some(120));
+        assert!(summary.starts_with("lost signal daily 2026-10-19\n"));
+        assert!(summary.contains("150 points, 11 deliveries, 3 hits, 2:05"));
+        assert!(summary.contains("new best"));
+        daily.scored = false;
+        assert!(daily.summary(&score, None).contains("practice"));
+    }
+}
@@ -0,0 +1,454 @@
+use std::collections::VecDeque;
+
+use crate::COLOR_STEPS;
+use crate::game::Game;
+use crate::hud;
+use crate::lasers::lane_center;
+use crate::maths::{Float2, Float4, float2_add, scale2};
+use crate::profiler::{BINS, Phase};
+use crate::signal::DEATH_THRESHOLD;
+use crate::vertex_t;
+
+//console lines kept on screen, oldest dropped first
+const HISTORY_LIMIT: usize = 8;
+//font pixel in view units, a glyph row is two screen pixels in the 1x window
+const FONT_PIXEL: f32 = 4.0;
+//line thickness in screen units, divided by zoom so it stays the same on screen
+const LINE_WIDTH: f32 = 3.0;
+//velocity lines show where a hazard will be this many seconds from now
+const VELOCITY_SECONDS: f32 = 0.25;
+const TRAJECTORY_DOTS: u32 = 12;
+
+const TEXT_COLOR: Float4 = Float4(1.0, 1.0, 1.0, 1.0);
+const PANEL_COLOR: Float4 = Float4(0.0, 0.0, 0.0, 0.7);
+const COLLIDER_COLOR: Float4 = Float4(0.0, 1.0, 0.3, 0.9);
+const PLAYER_COLOR: Float4 = Float4(1.0, 1.0, 1.0, 0.9);
+const VELOCITY_COLOR: Float4 = Float4(1.0, 0.9, 0.0, 0.9);
+const LANE_COLOR: Float4 = Float4(0.3, 0.6, 1.0, 0.35);
+const ERROR_COLOR: Float4 = Float4(1.0, 0.35, 0.3, 1.0);
+const HISTOGRAM_COLOR: Float4 = Float4(0.3, 0.8, 1.0, 1.0);
+//blank characters left after each profiler line for its histogram
+const HISTOGRAM_CHARS: usize = 6;
+
+pub const HELP: &str = "score N, spawn laser|jumprope|cluster [HUE], signal X, god [on|off], \
+seed N, overlay [on|off], profiler [on|off]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpawnKind {
+    Laser,
+    Jumprope,
+    Cluster,
+}
+
+//a parsed console line, hues are palette steps from 0 to COLOR_STEPS - 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Score(u32),
+    Spawn(SpawnKind, Option<u32>),
+    Signal(f32),
+    //None flips the current setting
+    God(Option<bool>),
+    Seed(u64),
+    Overlay(Option<bool>),
+    Profiler(Option<bool>),
+    Help,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or("empty command")?;
+        let argument = words.next();
+        let number = |what: &str| -> Result<&str, String> {
+            argument.ok_or_else(|| format!("{name} needs {what}"))
+        };
+        let toggle = || match argument {
+            None => Ok(None),
+            Some("on") => Ok(Some(true)),
+            Some("off") => Ok(Some(false)),
+            Some(other) => Err(format!("{name} takes on or off, not {other}")),
+        };
+        let command = match name {
+            "score" => Command::Score(
+                number("a score")?
+                    .parse()
+                    .map_err(|_| "score needs a whole number")?,
+            ),
+            "spawn" => {
+                let kind = match argument {
+                    Some("laser") => SpawnKind::Laser,
+                    Some("jumprope") => SpawnKind::Jumprope,
+                    Some("cluster") => SpawnKind::Cluster,
+                    _ => return Err("spawn laser, jumprope or cluster".to_string()),
+                };
+                let hue = match words.next() {
+                    Some(hue) => match hue.parse::<u32>() {
+                        Ok(hue) if hue < COLOR_STEPS => Some(hue),
+                        _ => {
+                            return Err(format!(
+                                "hue is a palette step from 0 to {}",
+                                COLOR_STEPS - 1
+                            ));
+                        }
+                    },
+                    None => None,
+                };
+                Command::Spawn(kind, hue)
+            }
+            "signal" => {
+                let lost: f32 = number("an amount")?
+                    .parse()
+                    .map_err(|_| "signal needs a number")?;
+                if !(0.0..=DEATH_THRESHOLD).contains(&lost) {
+                    return Err(format!("signal lost is between 0 and {DEATH_THRESHOLD}"));
+                }
+                Command::Signal(lost)
+            }
+            "god" => Command::God(toggle()?),
+            "seed" => Command::Seed(
+                number("a seed")?
+                    .parse()
+                    .map_err(|_| "seed needs a whole number")?,
+            ),
+            "overlay" => Command::Overlay(toggle()?),
+            "profiler" => Command::Profiler(toggle()?),
+            "help" => Command::Help,
+            _ => return Err(format!("unknown command {name}, try help")),
+        };
+        match words.next() {
+            Some(extra) => Err(format!("unexpected {extra}")),
+            None => Ok(command),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Console {
+    pub open: bool,
+    pub line: String,
+    pub history: VecDeque<String>,
+}
+
+impl Console {
+    //edits the line with this frame's typing, returns it once enter is pressed
+    pub fn feed(&mut self, typed: &str) -> Option<String> {
+        let mut submitted = None;
+        for c in typed.chars() {
+            match c {
+                '\n' => submitted = Some(std::mem::take(&mut self.line)),
+                '\u{8}' => {
+                    self.line.pop();
+                }
+                //the toggle key types a backquote on the way in
+                '`' => (),
+                c if !c.is_control() => self.line.push(c),
+                _ => (),
+            }
+        }
+        submitted
+    }
+
+    pub fn print(&mut self, line: String) {
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(line);
+    }
+}
+
+//developer tools, all off in a normal run
+#[derive(Debug, Clone, Default)]
+pub struct DebugTools {
+    pub overlay: bool,
+    //hits are ignored and the signal is never lost
+    pub god: bool,
+    //per phase frame timings from game.profiler
+    pub profiler: bool,
+    pub console: Console,
+}
+
+//colliders, velocities, cluster flights and spawn lanes in world space, then the stats and console text
+pub fn overlay(game: &Game) -> Vec<vertex_t> {
+    let mut out = Vec::new();
+    let camera = &game.camera;
+    let thickness = LINE_WIDTH / camera.zoom;
+    if game.debug.overlay {
+        let bounds = game.arena.bounds;
+        let lanes = game.lanes();
+        for lane in 0..lanes {
+            let y = lane_center(lane, lanes, bounds.1);
+            hud::line(
+                &mut out,
+                Float2(-bounds.0, y),
+                Float2(bounds.0, y),
+                thickness,
+                LANE_COLOR,
+            );
+        }
+        let world = &game.world;
+        for entity in world.entities() {
+            if let Some(rect) = world.collider_rect(entity) {
+                hud::outline(&mut out, &rect, thickness, COLLIDER_COLOR);
+            }
+            if let (Some(transform), Some(velocity)) =
+                (world.transforms.get(entity), world.velocities.get(entity))
+            {
+                let ahead = float2_add(transform.position, scale2(velocity.0, VELOCITY_SECONDS));
+                hud::line(
+                    &mut out,
+                    transform.position,
+                    ahead,
+                    thickness,
+                    VELOCITY_COLOR,
+                );
+            }
+        }
+        for bomb in game.clusters.iter() {
+            for dot in 0..=TRAJECTORY_DOTS {
+                let t = bomb.t + (bomb.end_t() - bomb.t) * dot as f32 / TRAJECTORY_DOTS as f32;
+                let position = bomb.position_at(t);
+                out.extend(crate::build_rect(
+                    position.0,
+                    position.1,
+                    thickness * 3.0,
+                    thickness * 3.0,
+                    0.0,
+                    bomb.color,
+                ));
+            }
+        }
+        if let Some(player) = game.scene.player.get(..4) {
+            hud::outline(&mut out, player, thickness, PLAYER_COLOR);
+        }
+    }
+
+    //screen anchored text, built in view units and carried into the world under the camera
+    let mut text = Vec::new();
+    let viewport = camera.viewport;
+    let margin = FONT_PIXEL * 4.0;
+    if game.debug.overlay {
+        let totals = game.signal.totals();
+        let lines = [
+            format!(
+                "frame {}  score {}  total {}",
+                game.frames,
+                game.score,
+                game.scoring.total()
+            ),
+            format!(
+                "signal {:.3}  radius {:.0}{}",
+                game.signal.lost(),
+                game.signal.radius(),
+                if game.debug.god { "  god" } else { "" }
+            ),
+            format!(
+                "hazards {}  clusters {}  walls {}",
+                game.world.entities().len(),
+                game.clusters.len(),
+                game.walls.len()
+            ),
+            format!(
+                "laser {:.0}  jumprope {:.0}  lanes {}",
+                game.laser_speed,
+                game.jumprope_speed,
+                game.lanes()
+            ),
+            format!(
+                "lost to laser {:.2} rope {:.2} graze {:.2} blast {:.2}",
+                totals.laser,
+                totals.jumprope,
+                totals.graze,
+                totals.blast + totals.fragment
+            ),
+        ];
+        hud::text_block(
+            &mut text,
+            &lines,
+            Float2(-viewport.0 + margin, viewport.1 - margin),
+            FONT_PIXEL,
+            TEXT_COLOR,
+            PANEL_COLOR,
+        );
+    }
+    if game.debug.profiler {
+        profiler_panel(game, &mut text, margin);
+    }
+    //always shown, a rejected tuning edit shouldn't go unnoticed
+    if !game.tuning_errors.is_empty() {
+        let mut lines = vec!["tuning not applied, keeping the previous values".to_string()];
+        lines.extend(game.tuning_errors.iter().cloned());
+        let width = lines
+            .iter()
+            .map(|line| hud::text_width(line, FONT_PIXEL))
+            .fold(0.0, f32::max);
+        hud::text_block(
+            &mut text,
+            &lines,
+            Float2(-width / 2.0, viewport.1 - margin),
+            FONT_PIXEL,
+            ERROR_COLOR,
+            PANEL_COLOR,
+        );
+    }
+    let console = &game.debug.console;
+    if console.open {
+        let mut lines: Vec<String> = console.history.iter().cloned().collect();
+        lines.push(format!("> {}_", console.line));
+        let height = (lines.len() as f32 * hud::LINE_HEIGHT + 4.0) * FONT_PIXEL;
+        hud::text_block(
+            &mut text,
+            &lines,
+            Float2(-viewport.0 + margin, -viewport.1 + margin + height),
+            FONT_PIXEL,
+            TEXT_COLOR,
+            PANEL_COLOR,
+        );
+    }
+    for vert in text.iter_mut() {
+        let world = camera.view_to_world(Float2(vert.position.0, vert.position.1));
+        vert.position.0 = world.0;
+        vert.position.1 = world.1;
+    }
+    out.extend(text);
+    out
+}
+
+//top right, rolling microseconds per phase with a histogram of the window beside each line
+fn profiler_panel(game: &Game, out: &mut Vec<vertex_t>, margin: f32) {
+    let viewport = game.camera.viewport;
+    let stats: Vec<_> = Phase::ALL
+        .iter()
+        .map(|phase| (phase, game.profiler.stats(*phase)))
+        .collect();
+    let blank = " ".repeat(HISTOGRAM_CHARS);
+    let mut lines = vec![format!(
+        "{:<10}{:>7}{:>7}{:>7}{blank}",
+        "us", "mean", "p95", "max"
+    )];
+    for (phase, stats) in stats.iter() {
+        lines.push(match stats {
+            Some(stats) => format!(
+                "{:<10}{:>7.0}{:>7.0}{:>7.0}{blank}",
+                phase.name(),
+                stats.mean,
+                stats.p95,
+                stats.max
+            ),
+            None => format!("{:<10}{:>7}{:>7}{:>7}{blank}", phase.name(), "-", "-", "-"),
+        });
+    }
+    let padding = FONT_PIXEL * 2.0;
+    let width = hud::text_width(&lines[0], FONT_PIXEL) + padding * 2.0;
+    let top_left = Float2(viewport.0 - margin - width, viewport.1 - margin);
+    hud::text_block(out, &lines, top_left, FONT_PIXEL, TEXT_COLOR, PANEL_COLOR);
+
+    let line_height = hud::LINE_HEIGHT * FONT_PIXEL;
+    let bar_width = hud::text_width(&blank, FONT_PIXEL) / (BINS + 2) as f32;
+    let left = top_left.0 + width - padding - bar_width * (BINS + 1) as f32;
+    for (row, (_, stats)) in stats.iter().enumerate() {
+        let Some(stats) = stats else {
+            continue;
+        };
+        let tallest = stats.histogram.iter().copied().max().unwrap_or(0).max(1);
+        //bars stand on the glyph baseline of their line, the header takes the first line
+        let bottom = top_left.1 - padding - (row + 1) as f32 * line_height - 7.0 * FONT_PIXEL;
+        for (bin, &count) in stats.histogram.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let height = 7.0 * FONT_PIXEL * count as f32 / tallest as f32;
+            hud::panel(
+                out,
+                Float2(left + bin as f32 * bar_width, bottom + height),
+                Float2(bar_width * 0.8, height),
+                HISTOGRAM_COLOR,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_parse_with_their_arguments() {
+        assert_eq!(Command::parse("score 12"), Ok(Command::Score(12)));
+        assert_eq!(
+            Command::parse("  spawn   laser 3 "),
+            Ok(Command::Spawn(SpawnKind::Laser, Some(3)))
+        );
+        assert_eq!(
+            Command::parse("spawn cluster"),
+            Ok(Command::Spawn(SpawnKind::Cluster, None))
+        );
+        assert_eq!(Command::parse("signal 0.5"), Ok(Command::Signal(0.5)));
+        assert_eq!(Command::parse("god"), Ok(Command::God(None)));
+        assert_eq!(Command::parse("god off"), Ok(Command::God(Some(false))));
+        assert_eq!(Command::parse("seed 42"), Ok(Command::Seed(42)));
+        assert_eq!(
+            Command::parse("overlay on"),
+            Ok(Command::Overlay(Some(true)))
+        );
+        assert_eq!(Command::parse("profiler"), Ok(Command::Profiler(None)));
+    }
+
+    #[test]
+    fn bad_commands_say_what_was_wrong() {
+        for line in [
+            "",
+            "teleport",
+            "score",
+            "score -1",
+            "spawn boss",
+            "spawn laser 7",
+            "signal 2",
+            "god maybe",
+            "seed 1 2",
+        ] {
+            assert!(Command::parse(line).is_err(), "{line:?} parsed");
+        }
+    }
+
+    #[test]
+    fn console_edits_and_submits_lines() {
+        let mut console = Console::default();
+        assert_eq!(console.feed("`scorf\u{8}e 3"), None);
+        assert_eq!(console.line, "score 3");
+        assert_eq!(console.feed("\n").as_deref(), Some("score 3"));
+        assert!(console.line.is_empty());
+    }
+
+    #[test]
+    fn console_commands_change_the_game() {
+        let mut game = Game::with_difficulty(crate::game::Difficulty::NORMAL);
+        game.verbose = false;
+        game.command(Command::Score(9));
+        assert_eq!(game.score, 9);
+        game.command(Command::God(None));
+        game.command(Command::Signal(DEATH_THRESHOLD));
+        assert!(!game.over());
+        game.command(Command::God(Some(false)));
+        assert!(game.over());
+        let clusters = game.clusters.len();
+        game.command(Command::Spawn(SpawnKind::Cluster, Some(2)));
+        assert_eq!(game.clusters.len(), clusters + 1);
+    }
+
+    #[test]
+    fn a_lost_signal_holds_the_game_over_panel_until_enter() {
+        let mut game = Game::with_difficulty(crate::game::Difficulty::NORMAL);
+        game.verbose = false;
+        game.command(Command::Signal(DEATH_THRESHOLD));
+        let mut input = crate::input::Input::default();
+        let frames = game.frames;
+        game.step(&input);
+        game.step(&input);
+        assert_eq!(game.frames, frames);
+        assert!(!game.scene.overlay.is_empty());
+        assert!(!game.dismissed);
+        input.type_char('\n');
+        game.step(&input);
+        assert!(game.dismissed);
+    }
+}
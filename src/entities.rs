@@ -0,0 +1,710 @@
+use crate::boss::GravityWell;
+use crate::lasers::Laser;
+use crate::maths::{
+    Float2, Float4, apply_rotation_float2, dot, float2_add, float2_subtract, scale2,
+};
+use crate::signal::HitSource;
+use crate::walls::{Wall, WallKind, reflect};
+use crate::{build_rect, obb_intersect, vertex_t};
+
+//friction is given as the velocity kept per 60th of a second, whatever the frame rate
+const FRICTION_STEP: f32 = 1.0 / 60.0;
+
+//generational handle, a despawned entity's slot can be reused without old handles reaching it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+//one column per component, each slot tagged with the generation it was written for
+pub struct Storage<T> {
+    items: Vec<Option<(u32, T)>>,
+}
+
+impl<T> Storage<T> {
+    fn new() -> Self {
+        Storage { items: Vec::new() }
+    }
+    pub fn insert(&mut self, entity: Entity, value: T) {
+        let index = entity.index as usize;
+        if self.items.len() <= index {
+            self.items.resize_with(index + 1, || None);
+        }
+        self.items[index] = Some((entity.generation, value));
+    }
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        match self.items.get(entity.index as usize) {
+            Some(Some((generation, value))) if *generation == entity.generation => Some(value),
+            _ => None,
+        }
+    }
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.items.get_mut(entity.index as usize) {
+            Some(Some((generation, value))) if *generation == entity.generation => Some(value),
+            _ => None,
+        }
+    }
+    fn remove(&mut self, entity: Entity) {
+        if self.get(entity).is_some() {
+            self.items[entity.index as usize] = None;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    pub position: Float2,
+    pub rotation: f32,
+    pub size: Float2,
+}
+
+//units per second
+#[derive(Debug, Clone, Copy)]
+pub struct Velocity(pub Float2);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Friction(pub f32);
+
+//base is the spawn color, color is what it currently reads as after effects like redshift
+#[derive(Debug, Clone, Copy)]
+pub struct PaletteColor {
+    pub color: Float4,
+    pub base: Float4,
+}
+
+impl PaletteColor {
+    pub fn new(color: Float4) -> Self {
+        PaletteColor { color, base: color }
+    }
+}
+
+//box collider, rotated with the transform
+#[derive(Debug, Clone, Copy)]
+pub struct Collider {
+    pub size: Float2,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Lifetime(pub f32);
+
+//which signal weight applies when this touches the player
+#[derive(Debug, Clone, Copy)]
+pub struct Damage(pub HitSource);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HazardKind {
+    Laser,
+    Jumprope,
+    Fragment,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Contact {
+    //point is the closest spot on the hazard, so wide hazards push straight out
+    Hit {
+        entity: Entity,
+        source: HitSource,
+        point: Float2,
+    },
+    Graze(HitSource),
+}
+
+pub struct World {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    pub transforms: Storage<Transform>,
+    pub velocities: Storage<Velocity>,
+    pub frictions: Storage<Friction>,
+    pub colors: Storage<PaletteColor>,
+    pub colliders: Storage<Collider>,
+    pub lifetimes: Storage<Lifetime>,
+    pub damages: Storage<Damage>,
+    pub hazards: Storage<HazardKind>,
+    pub lasers: Storage<Laser>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        World {
+            generations: Vec::new(),
+            alive: Vec::new(),
+            free: Vec::new(),
+            transforms: Storage::new(),
+            velocities: Storage::new(),
+            frictions: Storage::new(),
+            colors: Storage::new(),
+            colliders: Storage::new(),
+            lifetimes: Storage::new(),
+            damages: Storage::new(),
+            hazards: Storage::new(),
+            lasers: Storage::new(),
+        }
+    }
+
+    pub fn spawn(&mut self) -> Entity {
+        if let Some(index) = self.free.pop() {
+            self.alive[index as usize] = true;
+            return Entity {
+                index,
+                generation: self.generations[index as usize],
+            };
+        }
+        self.generations.push(0);
+        self.alive.push(true);
+        Entity {
+            index: self.generations.len() as u32 - 1,
+            generation: 0,
+        }
+    }
+
+    //stale handles and double despawns are ignored
+    pub fn despawn(&mut self, entity: Entity) {
+        if !self.is_alive(entity) {
+            return;
+        }
+        self.transforms.remove(entity);
+        self.velocities.remove(entity);
+        self.frictions.remove(entity);
+        self.colors.remove(entity);
+        self.colliders.remove(entity);
+        self.lifetimes.remove(entity);
+        self.damages.remove(entity);
+        self.hazards.remove(entity);
+        self.lasers.remove(entity);
+        let index = entity.index as usize;
+        self.alive[index] = false;
+        self.generations[index] += 1;
+        self.free.push(entity.index);
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.generations.get(entity.index as usize) == Some(&entity.generation)
+            && self.alive[entity.index as usize]
+    }
+
+    pub fn entities(&self) -> Vec<Entity> {
+        (0..self.generations.len())
+            .filter(|&index| self.alive[index])
+            .map(|index| Entity {
+                index: index as u32,
+                generation: self.generations[index],
+            })
+            .collect()
+    }
+
+    pub fn count(&self, kind: HazardKind) -> usize {
+        self.entities()
+            .into_iter()
+            .filter(|&entity| self.hazards.get(entity) == Some(&kind))
+            .count()
+    }
+
+    pub fn positions(&self, kind: HazardKind) -> Vec<Float2> {
+        self.entities()
+            .into_iter()
+            .filter(|&entity| self.hazards.get(entity) == Some(&kind))
+            .filter_map(|entity| self.transforms.get(entity).map(|t| t.position))
+            .collect()
+    }
+
+    //rect in the same vertex layout the renderer and the rect tests use
+    pub fn rect(&self, entity: Entity) -> Option<Vec<vertex_t>> {
+        let transform = self.transforms.get(entity)?;
+        let color = self.colors.get(entity)?;
+        Some(build_rect(
+            transform.position.0,
+            transform.position.1,
+            transform.size.0,
+            transform.size.1,
+            transform.rotation,
+            color.color,
+        ))
+    }
+
+    //the box collisions are tested against, the debug overlay outlines it
+    pub fn collider_rect(&self, entity: Entity) -> Option<Vec<vertex_t>> {
+        let transform = self.transforms.get(entity)?;
+        let collider = self.colliders.get(entity)?;
+        let color = self.colors.get(entity)?;
+        Some(build_rect(
+            transform.position.0,
+            transform.position.1,
+            collider.size.0,
+            collider.size.1,
+            transform.rotation,
+            color.color,
+        ))
+    }
+
+    fn closest_point(&self, entity: Entity, point: Float2) -> Option<Float2> {
+        let transform = self.transforms.get(entity)?;
+        let half = scale2(self.colliders.get(entity)?.size, 0.5);
+        let local = apply_rotation_float2(
+            float2_subtract(point, transform.position),
+            -transform.rotation,
+        );
+        let clamped = Float2(
+            local.0.clamp(-half.0, half.0),
+            local.1.clamp(-half.1, half.1),
+        );
+        Some(float2_add(
+            transform.position,
+            apply_rotation_float2(clamped, transform.rotation),
+        ))
+    }
+
+    fn spawn_hazard(
+        &mut self,
+        kind: HazardKind,
+        transform: Transform,
+        color: Float4,
+        damage: Damage,
+    ) -> Entity {
+        let entity = self.spawn();
+        self.transforms.insert(entity, transform);
+        self.colors.insert(entity, PaletteColor::new(color));
+        self.colliders.insert(
+            entity,
+            Collider {
+                size: transform.size,
+            },
+        );
+        self.damages.insert(entity, damage);
+        self.hazards.insert(entity, kind);
+        entity
+    }
+
+    pub fn spawn_laser(&mut self, laser: Laser, color: Float4, size: Float2) -> Entity {
+        let transform = Transform {
+            position: laser.position(),
+            rotation: laser.rotation(),
+            size,
+        };
+        let entity = self.spawn_hazard(
+            HazardKind::Laser,
+            transform,
+            color,
+            Damage(HitSource::Laser),
+        );
+        self.velocities.insert(entity, Velocity(laser.velocity()));
+        self.lasers.insert(entity, laser);
+        entity
+    }
+
+    pub fn spawn_jumprope(
+        &mut self,
+        position: Float2,
+        size: Float2,
+        speed: f32,
+        color: Float4,
+    ) -> Entity {
+        let transform = Transform {
+            position,
+            rotation: 0.0,
+            size,
+        };
+        let entity = self.spawn_hazard(
+            HazardKind::Jumprope,
+            transform,
+            color,
+            Damage(HitSource::Jumprope),
+        );
+        self.velocities
+            .insert(entity, Velocity(Float2(0.0, -speed)));
+        entity
+    }
+
+    pub fn spawn_fragment(
+        &mut self,
+        position: Float2,
+        velocity: Float2,
+        size: Float2,
+        color: Float4,
+        lifetime: f32,
+        friction: f32,
+    ) -> Entity {
+        let transform = Transform {
+            position,
+            rotation: 0.0,
+            size,
+        };
+        let entity = self.spawn_hazard(
+            HazardKind::Fragment,
+            transform,
+            color,
+            Damage(HitSource::Fragment),
+        );
+        self.velocities.insert(entity, Velocity(velocity));
+        self.frictions.insert(entity, Friction(friction));
+        self.lifetimes.insert(entity, Lifetime(lifetime));
+        entity
+    }
+}
+
+//laser paths drive their own transform, everything else integrates its velocity
+pub fn movement_system(world: &mut World, delta_t: f32) {
+    for entity in world.entities() {
+        if let Some(laser) = world.lasers.get_mut(entity) {
+            let position = laser.update(delta_t);
+            let (rotation, velocity) = (laser.rotation(), laser.velocity());
+            if let Some(transform) = world.transforms.get_mut(entity) {
+                transform.position = position;
+                transform.rotation = rotation;
+            }
+            world.velocities.insert(entity, Velocity(velocity));
+            continue;
+        }
+        let Some(Velocity(mut velocity)) = world.velocities.get(entity).copied() else {
+            continue;
+        };
+        if let Some(Friction(friction)) = world.frictions.get(entity).copied() {
+            velocity = scale2(velocity, friction.powf(delta_t / FRICTION_STEP));
+            world.velocities.insert(entity, Velocity(velocity));
+        }
+        if let Some(transform) = world.transforms.get_mut(entity) {
+            transform.position = float2_add(transform.position, scale2(velocity, delta_t));
+        }
+    }
+}
+
+//bends projectiles towards each well and redshifts them, jumpropes are too big to be pulled
+pub fn gravity_system(
+    world: &mut World,
+    wells: &[GravityWell],
+    delta_t: f32,
+    redshift_steps: u32,
+    palette_steps: u32,
+) {
+    for entity in world.entities() {
+        if world.hazards.get(entity) == Some(&HazardKind::Jumprope) {
+            continue;
+        }
+        let Some(position) = world.transforms.get(entity).map(|t| t.position) else {
+            continue;
+        };
+        let Some(mut palette) = world.colors.get(entity).copied() else {
+            continue;
+        };
+        palette.color = palette.base;
+        for well in wells {
+            let pull = well.pull(position);
+            if let Some(laser) = world.lasers.get_mut(entity) {
+                laser.steer(pull, delta_t);
+            } else if let Some(velocity) = world.velocities.get_mut(entity) {
+                velocity.0 = float2_add(velocity.0, scale2(pull, delta_t));
+            }
+            palette.color = well.redshift(palette.color, position, redshift_steps, palette_steps);
+        }
+        world.colors.insert(entity, palette);
+    }
+}
+
+//mirrors reflect anything moving into them, colored walls stop every other color
+pub fn wall_system(world: &mut World, walls: &[Wall], wall_rects: &[Vec<vertex_t>]) {
+    for entity in world.entities() {
+        if world.hazards.get(entity) == Some(&HazardKind::Jumprope) {
+            continue;
+        }
+        let Some(rect) = world.collider_rect(entity) else {
+            continue;
+        };
+        let position = world.transforms.get(entity).map(|t| t.position).unwrap();
+        let color = world.colors.get(entity).map(|c| c.color).unwrap();
+        let mut blocked = false;
+        for (wall, wall_rect) in walls.iter().zip(wall_rects.iter()) {
+            if !obb_intersect(wall_rect, &rect) {
+                continue;
+            }
+            if wall.kind != WallKind::Mirror {
+                blocked |= wall.blocks(color);
+                continue;
+            }
+            let normal = wall.normal_towards(position);
+            if let Some(laser) = world.lasers.get_mut(entity) {
+                laser.reflect(normal);
+            } else if let Some(velocity) = world.velocities.get_mut(entity)
+                && dot(velocity.0, normal) < 0.0
+            {
+                velocity.0 = reflect(velocity.0, normal);
+            }
+        }
+        if blocked {
+            world.despawn(entity);
+        }
+    }
+}
+
+//contacts against the player, a mismatched hazard is left for the caller to consume once the hit lands
+pub fn collision_system(
+    world: &mut World,
+    player_position: Float2,
+    player_rect: &[vertex_t],
+) -> Vec<Contact> {
+    let mut contacts = Vec::new();
+    for entity in world.entities() {
+        let (Some(rect), Some(Damage(source))) = (
+            world.collider_rect(entity),
+            world.damages.get(entity).copied(),
+        ) else {
+            continue;
+        };
+        if !obb_intersect(player_rect, &rect) {
+            continue;
+        }
+        if player_rect[0].color != rect[0].color {
+            let point = world
+                .closest_point(entity, player_position)
+                .unwrap_or(player_position);
+            contacts.push(Contact::Hit {
+                entity,
+                source,
+                point,
+            });
+        } else {
+            contacts.push(Contact::Graze(source));
+        }
+    }
+    contacts
+}
+
+pub fn lifetime_system(world: &mut World, delta_t: f32) {
+    for entity in world.entities() {
+        if let Some(lifetime) = world.lifetimes.get_mut(entity) {
+            lifetime.0 -= delta_t;
+            if lifetime.0 <= 0.0 {
+                world.despawn(entity);
+            }
+        }
+    }
+}
+
+//only despawn once past the margin and still heading outwards, patterns spawn off screen
+pub fn bounds_system(world: &mut World, bounds: Float2, margin: f32) {
+    for entity in world.entities() {
+        let (Some(transform), Some(Velocity(velocity))) =
+            (world.transforms.get(entity), world.velocities.get(entity))
+        else {
+            continue;
+        };
+        let position = transform.position;
+        let leaving = (position.0 > bounds.0 + margin && velocity.0 >= 0.0)
+            || (position.0 < -bounds.0 - margin && velocity.0 <= 0.0)
+            || (position.1 > bounds.1 + margin && velocity.1 >= 0.0)
+            || (position.1 < -bounds.1 - margin && velocity.1 <= 0.0);
+        if leaving {
+            world.despawn(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Float4 = Float4(1.0, 0.0, 0.0, 1.0);
+    const BLUE: Float4 = Float4(0.0, 0.0, 1.0, 1.0);
+    const SIZE: Float2 = Float2(20.0, 20.0);
+
+    fn fragment(world: &mut World, position: Float2, velocity: Float2, color: Float4) -> Entity {
+        world.spawn_fragment(position, velocity, SIZE, color, 1.0, 1.0)
+    }
+
+    fn wall(kind: WallKind) -> (Vec<Wall>, Vec<Vec<vertex_t>>) {
+        let wall = Wall::new(Float2(0.0, 0.0), 10.0, 200.0, 0.0, kind);
+        let rect = wall.rect();
+        (vec![wall], vec![rect])
+    }
+
+    #[test]
+    fn stale_handles_miss_once_the_slot_is_reused() {
+        let mut world = World::new();
+        let old = fragment(&mut world, Float2(0.0, 0.0), Float2(0.0, 0.0), RED);
+        world.despawn(old);
+        assert!(!world.is_alive(old));
+        assert!(world.transforms.get(old).is_none());
+        let new = fragment(&mut world, Float2(5.0, 0.0), Float2(0.0, 0.0), BLUE);
+        assert_eq!(new.index, old.index);
+        assert_eq!(new.generation, old.generation + 1);
+        assert!(world.transforms.get(old).is_none());
+        assert!(world.transforms.get_mut(old).is_none());
+        //despawning through the stale handle leaves the new entity alone
+        world.despawn(old);
+        assert!(world.is_alive(new));
+        assert_eq!(
+            world.transforms.get(new).unwrap().position,
+            Float2(5.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn despawning_twice_frees_the_slot_once() {
+        let mut world = World::new();
+        let entity = world.spawn();
+        world.despawn(entity);
+        world.despawn(entity);
+        let (first, second) = (world.spawn(), world.spawn());
+        assert_ne!(first.index, second.index);
+        assert_eq!(world.entities().len(), 2);
+    }
+
+    #[test]
+    fn count_and_positions_are_per_kind() {
+        let mut world = World::new();
+        world.spawn_jumprope(Float2(0.0, 100.0), Float2(500.0, 20.0), 50.0, RED);
+        let gone = fragment(&mut world, Float2(1.0, 2.0), Float2(0.0, 0.0), RED);
+        fragment(&mut world, Float2(3.0, 4.0), Float2(0.0, 0.0), RED);
+        fragment(&mut world, Float2(5.0, 6.0), Float2(0.0, 0.0), RED);
+        world.despawn(gone);
+        assert_eq!(world.count(HazardKind::Jumprope), 1);
+        assert_eq!(world.count(HazardKind::Fragment), 2);
+        assert_eq!(world.count(HazardKind::Laser), 0);
+        assert_eq!(
+            world.positions(HazardKind::Fragment),
+            vec![Float2(3.0, 4.0), Float2(5.0, 6.0)]
+        );
+        assert_eq!(
+            world.positions(HazardKind::Jumprope),
+            vec![Float2(0.0, 100.0)]
+        );
+    }
+
+    #[test]
+    fn movement_integrates_velocity_after_friction() {
+        let mut world = World::new();
+        let rope = world.spawn_jumprope(Float2(0.0, 100.0), Float2(500.0, 20.0), 120.0, RED);
+        let slowed = world.spawn_fragment(Float2(0.0, 0.0), Float2(60.0, 0.0), SIZE, RED, 1.0, 0.5);
+        movement_system(&mut world, FRICTION_STEP);
+        assert_eq!(
+            world.transforms.get(rope).unwrap().position,
+            Float2(0.0, 98.0)
+        );
+        //half the velocity kept over one friction step, then moved with what is left
+        assert_eq!(world.velocities.get(slowed).unwrap().0, Float2(30.0, 0.0));
+        assert_eq!(
+            world.transforms.get(slowed).unwrap().position,
+            Float2(0.5, 0.0)
+        );
+    }
+
+    #[test]
+    fn lifetimes_run_out() {
+        let mut world = World::new();
+        let entity = world.spawn_fragment(Float2(0.0, 0.0), Float2(0.0, 0.0), SIZE, RED, 0.1, 1.0);
+        lifetime_system(&mut world, 0.05);
+        assert!(world.is_alive(entity));
+        lifetime_system(&mut world, 0.06);
+        assert!(!world.is_alive(entity));
+    }
+
+    #[test]
+    fn only_hazards_leaving_past_the_margin_are_removed() {
+        let mut world = World::new();
+        let bounds = Float2(100.0, 100.0);
+        let leaving = fragment(&mut world, Float2(160.0, 0.0), Float2(10.0, 0.0), RED);
+        let arriving = fragment(&mut world, Float2(-160.0, 0.0), Float2(10.0, 0.0), RED);
+        let inside_margin = fragment(&mut world, Float2(0.0, 140.0), Float2(0.0, 10.0), RED);
+        bounds_system(&mut world, bounds, 50.0);
+        assert!(!world.is_alive(leaving));
+        assert!(world.is_alive(arriving));
+        assert!(world.is_alive(inside_margin));
+    }
+
+    #[test]
+    fn mismatched_contacts_hit_and_matching_ones_graze() {
+        let mut world = World::new();
+        let player = build_rect(0.0, 0.0, 50.0, 50.0, 0.0, RED);
+        let graze = fragment(&mut world, Float2(10.0, 0.0), Float2(0.0, 0.0), RED);
+        let hit = fragment(&mut world, Float2(0.0, 30.0), Float2(0.0, 0.0), BLUE);
+        fragment(&mut world, Float2(200.0, 0.0), Float2(0.0, 0.0), BLUE);
+        let contacts = collision_system(&mut world, Float2(0.0, 0.0), &player);
+        assert_eq!(contacts.len(), 2);
+        assert!(matches!(contacts[0], Contact::Graze(HitSource::Fragment)));
+        //the hit is pushed out from the hazard's nearest edge
+        assert!(matches!(
+            contacts[1],
+            Contact::Hit {
+                entity,
+                source: HitSource::Fragment,
+                point: Float2(0.0, 20.0)
+            } if entity == hit
+        ));
+        //consuming a hazard is up to the game
+        assert!(world.is_alive(graze));
+        assert!(world.is_alive(hit));
+    }
+
+    #[test]
+    fn hazards_pass_through_during_i_frames_and_are_used_up_by_a_hit() {
+        use crate::game::{Difficulty, Game};
+        use crate::input::Input;
+        use crate::signal::SignalEvent;
+
+        let mut game = Game::with_difficulty(Difficulty::NORMAL);
+        game.verbose = false;
+        game.signal.apply(SignalEvent::Hit(HitSource::Laser));
+        let lost = game.signal.lost();
+        //grey is never the player's hue
+        let grey = Float4(0.5, 0.5, 0.5, 1.0);
+        let hazard =
+            game.world
+                .spawn_fragment(game.position, Float2(0.0, 0.0), SIZE, grey, 100.0, 1.0);
+        game.step(&Input::default());
+        assert!(game.world.is_alive(hazard));
+        assert_eq!(game.signal.lost(), lost);
+        while game.signal.invulnerable() {
+            game.step(&Input::default());
+        }
+        game.step(&Input::default());
+        assert!(!game.world.is_alive(hazard));
+        assert!(game.signal.lost() > lost);
+    }
+
+    #[test]
+    fn mirrors_reflect_only_what_moves_into_them() {
+        let (walls, rects) = wall(WallKind::Mirror);
+        let mut world = World::new();
+        let incoming = fragment(&mut world, Float2(-12.0, 0.0), Float2(100.0, 0.0), RED);
+        let leaving = fragment(&mut world, Float2(12.0, 30.0), Float2(100.0, 0.0), RED);
+        wall_system(&mut world, &walls, &rects);
+        assert_eq!(
+            world.velocities.get(incoming).unwrap().0,
+            Float2(-100.0, 0.0)
+        );
+        assert_eq!(world.velocities.get(leaving).unwrap().0, Float2(100.0, 0.0));
+        //still overlapping on the next frame, the bounce isn't undone
+        wall_system(&mut world, &walls, &rects);
+        assert_eq!(
+            world.velocities.get(incoming).unwrap().0,
+            Float2(-100.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn mirrored_lasers_turn_once() {
+        let (walls, rects) = wall(WallKind::Mirror);
+        let mut world = World::new();
+        let laser = Laser::new(Float2(-12.0, 0.0), Float2(1.0, 0.0), 100.0);
+        let entity = world.spawn_laser(laser, RED, SIZE);
+        wall_system(&mut world, &walls, &rects);
+        let heading = world.lasers.get(entity).unwrap().velocity();
+        assert!(heading.0 < 0.0 && heading.1.abs() < 1e-5, "{heading:?}");
+        wall_system(&mut world, &walls, &rects);
+        assert_eq!(world.lasers.get(entity).unwrap().velocity(), heading);
+    }
+
+    #[test]
+    fn colored_walls_stop_other_colors_and_let_their_own_through() {
+        let (walls, rects) = wall(WallKind::Colored(RED));
+        let mut world = World::new();
+        let red = fragment(&mut world, Float2(-12.0, 0.0), Float2(100.0, 0.0), RED);
+        let blue = fragment(&mut world, Float2(-12.0, 40.0), Float2(100.0, 0.0), BLUE);
+        let clear = fragment(&mut world, Float2(-100.0, 0.0), Float2(100.0, 0.0), BLUE);
+        wall_system(&mut world, &walls, &rects);
+        assert!(world.is_alive(red));
+        assert_eq!(world.velocities.get(red).unwrap().0, Float2(100.0, 0.0));
+        assert!(!world.is_alive(blue));
+        assert!(world.is_alive(clear));
+    }
+}
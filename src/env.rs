@@ -0,0 +1,397 @@
+use crate::boss::palette_index;
+use crate::game::{Difficulty, Game};
+use crate::input::{self, Input};
+use crate::maths::{Float2, Float4, float2_subtract};
+use crate::{COLOR_STEPS, palette_t, rng, stepped_hue};
+
+pub use crate::entities::HazardKind;
+
+//hazards further than this from the player are left out of observations
+const NEARBY: f32 = 700.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct HazardObservation {
+    pub kind: HazardKind,
+    pub position: Float2,
+    //units per second
+    pub velocity: Float2,
+    pub size: Float2,
+    pub rotation: f32,
+    pub color: Float4,
+    pub hue_step: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClusterObservation {
+    pub position: Float2,
+    pub target: Float2,
+    pub blast_radius: f32,
+    //0 on launch, 1 on detonation
+    pub progress: f32,
+    pub hue_step: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GoalObservation {
+    pub position: Float2,
+    pub hue_step: u32,
+    pub points: u32,
+}
+
+//what a player could see, in world units with the arena centered on the origin
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub frames: u64,
+    pub position: Float2,
+    pub hue_step: u32,
+    pub signal_lost: f32,
+    pub score: u32,
+    pub carrying: bool,
+    pub delivery: Float2,
+    pub goals: Vec<GoalObservation>,
+    //nearest first
+    pub hazards: Vec<HazardObservation>,
+    pub clusters: Vec<ClusterObservation>,
+}
+
+impl Observation {
+    fn new(game: &Game) -> Self {
+        let world = &game.world;
+        let steps = game.palette_steps();
+        let mut hazards: Vec<HazardObservation> = world
+            .entities()
+            .into_iter()
+            .filter_map(|entity| {
+                let transform = world.transforms.get(entity)?;
+                let color = world.colors.get(entity)?.color;
+                Some(HazardObservation {
+                    kind: *world.hazards.get(entity)?,
+                    position: transform.position,
+                    velocity: world
+                        .velocities
+                        .get(entity)
+                        .map_or(Float2(0.0, 0.0), |velocity| velocity.0),
+                    size: transform.size,
+                    rotation: transform.rotation,
+                    color,
+                    hue_step: palette_index(color, steps),
+                })
+            })
+            .filter(|hazard| distance(hazard.position, game.position) < NEARBY)
+            .collect();
+        hazards.sort_by(|a, b| {
+            distance(a.position, game.position).total_cmp(&distance(b.position, game.position))
+        });
+        Observation {
+            frames: game.frames,
+            position: game.position,
+            hue_step: hue_step(game.hue, steps),
+            signal_lost: game.signal.lost(),
+            score: game.score,
+            carrying: game.carrying.is_some(),
+            delivery: game.arena.delivery().center,
+            goals: game
+                .goals
+                .iter()
+                .map(|goal| GoalObservation {
+                    position: goal.position,
+                    hue_step: hue_step(goal.t, steps),
+                    points: goal.points,
+                })
+                .collect(),
+            hazards,
+            clusters: game
+                .clusters
+                .iter()
+                .map(|bomb| ClusterObservation {
+                    position: bomb.position(),
+                    target: bomb.end_pos,
+                    blast_radius: bomb.blast_radius,
+                    progress: bomb.progress(),
+                    hue_step: palette_index(bomb.color, steps),
+                })
+                .collect(),
+        }
+    }
+
+    //fixed length features for learners that want a flat vector, missing hazards are zeros
+    pub fn features(&self, hazard_slots: usize) -> Vec<f32> {
+        let mut features = vec![
+            self.position.0,
+            self.position.1,
+            self.hue_step as f32,
+            self.signal_lost,
+            self.carrying as u32 as f32,
+            self.delivery.0 - self.position.0,
+            self.delivery.1 - self.position.1,
+        ];
+        let nearest_goal = self.goals.iter().min_by(|a, b| {
+            distance(a.position, self.position).total_cmp(&distance(b.position, self.position))
+        });
+        features.extend(match nearest_goal {
+            Some(goal) => [
+                goal.position.0 - self.position.0,
+                goal.position.1 - self.position.1,
+                goal.hue_step as f32,
+            ],
+            None => [0.0; 3],
+        });
+        for slot in 0..hazard_slots {
+            features.extend(match self.hazards.get(slot) {
+                Some(hazard) => [
+                    1.0,
+                    hazard.position.0 - self.position.0,
+                    hazard.position.1 - self.position.1,
+                    hazard.velocity.0,
+                    hazard.velocity.1,
+                    hazard.hue_step as f32,
+                ],
+                None => [0.0; 6],
+            });
+        }
+        features
+    }
+}
+
+fn distance(a: Float2, b: Float2) -> f32 {
+    float2_subtract(a, b).magnitude()
+}
+
+//on the palette the game is playing, the daily can narrow it
+fn hue_step(t: f64, palette_steps: u32) -> u32 {
+    (stepped_hue(t, palette_steps) / (360 / palette_steps) as f64) as u32
+}
+
+//one frame of control, movement axes are -1, 0 or 1 with y up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Action {
+    pub x: i8,
+    pub y: i8,
+    pub hue_step: u32,
+    pub place_wall: bool,
+    pub place_mirror: bool,
+}
+
+impl Action {
+    //nine movement directions for every step of the full palette, walls left out,
+    //steps past a narrowed palette wrap round
+    pub const COUNT: usize = 9 * COLOR_STEPS as usize;
+
+    //discrete encoding for learners with a fixed action space
+    pub fn from_index(index: usize) -> Self {
+        let index = index % Action::COUNT;
+        let movement = index % 9;
+        Action {
+            x: (movement % 3) as i8 - 1,
+            y: (movement / 3) as i8 - 1,
+            hue_step: (index / 9) as u32,
+            ..Default::default()
+        }
+    }
+
+    fn apply(&self, input: &mut Input, palette_steps: u32) {
+        for (axis, negative, positive) in [
+            (self.x, input::Action::Left, input::Action::Right),
+            (self.y, input::Action::Down, input::Action::Up),
+        ] {
+            input.release(negative);
+            input.release(positive);
+            match axis.signum() {
+                -1 => input.press(negative),
+                1 => input.press(positive),
+                _ => (),
+            }
+        }
+        for (held, action) in [
+            (self.place_wall, input::Action::PlaceWall),
+            (self.place_mirror, input::Action::PlaceMirror),
+        ] {
+            if held {
+                input.press(action);
+            } else {
+                input.release(action);
+            }
+        }
+        input.set_hue(palette_t(self.hue_step % palette_steps, palette_steps));
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RewardWeights {
+    //per point of the run total, bonuses included
+    pub score: f32,
+    //per unit of signal lost, recovering signal pays it back
+    pub signal: f32,
+    //once, on the frame the signal is lost
+    pub death: f32,
+}
+
+impl Default for RewardWeights {
+    fn default() -> Self {
+        RewardWeights {
+            score: 1.0,
+            signal: 10.0,
+            death: 10.0,
+        }
+    }
+}
+
+//the game behind a reset and step interface, no window and no frame pacing
+pub struct Env {
+    pub difficulty: Difficulty,
+    pub weights: RewardWeights,
+    //ends episodes that outlive it, None to play until the signal is lost
+    pub frame_limit: Option<u64>,
+    game: Game,
+    input: Input,
+    total: u32,
+    lost: f32,
+}
+
+impl Env {
+    pub fn new(difficulty: Difficulty) -> Self {
+        let mut game = Game::with_difficulty(difficulty);
+        game.verbose = false;
+        Env {
+            difficulty,
+            weights: RewardWeights::default(),
+            frame_limit: None,
+            game,
+            input: Input::new(),
+            total: 0,
+            lost: 0.0,
+        }
+    }
+
+    //the same seed and actions always replay the same episode
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        rng::seed(seed);
+        self.game = Game::with_difficulty(self.difficulty);
+        self.game.verbose = false;
+        self.input = Input::new();
+        self.total = 0;
+        self.lost = 0.0;
+        Observation::new(&self.game)
+    }
+
+    pub fn step(&mut self, action: Action) -> (Observation, f32, bool) {
+        action.apply(&mut self.input, self.game.palette_steps());
+        self.game.step(&self.input);
+        self.input.end_frame();
+
+        let total = self.game.scoring.total();
+        let lost = self.game.signal.lost();
+        let mut reward = (total as f32 - self.total as f32) * self.weights.score
+            - (lost - self.lost) * self.weights.signal;
+        self.total = total;
+        self.lost = lost;
+        if self.game.over() {
+            reward -= self.weights.death;
+        }
+        let done = self.game.over()
+            || self
+                .frame_limit
+                .is_some_and(|limit| self.game.frames >= limit);
+        (Observation::new(&self.game), reward, done)
+    }
+
+    //for rendering or inspecting an episode
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discrete_actions_cover_every_move_and_hue() {
+        let actions: Vec<Action> = (0..Action::COUNT).map(Action::from_index).collect();
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for hue_step in 0..COLOR_STEPS {
+                    let action = Action {
+                        x,
+                        y,
+                        hue_step,
+                        ..Default::default()
+                    };
+                    assert_eq!(actions.iter().filter(|&&a| a == action).count(), 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn actions_pick_their_palette_step() {
+        let mut input = Input::new();
+        for hue_step in 0..COLOR_STEPS {
+            Action {
+                hue_step,
+                ..Default::default()
+            }
+            .apply(&mut input, COLOR_STEPS);
+            assert_eq!(super::hue_step(input.hue, COLOR_STEPS), hue_step);
+        }
+    }
+
+    #[test]
+    fn a_narrowed_palette_is_used_for_actions_and_observations() {
+        use crate::daily::{Daily, Modifier};
+
+        let mut daily = Daily::for_date(chrono::NaiveDate::from_ymd_opt(2026, 10, 19).unwrap());
+        daily.modifiers = vec![Modifier::FiveColors];
+        let steps = daily.palette_steps().unwrap();
+        let mut game = Game::daily(daily);
+        let mut input = Input::new();
+        for hue_step in 0..steps + 2 {
+            Action {
+                hue_step,
+                ..Default::default()
+            }
+            .apply(&mut input, game.palette_steps());
+            assert_eq!(super::hue_step(input.hue, steps), hue_step % steps);
+            game.hue = input.hue;
+            assert_eq!(Observation::new(&game).hue_step, hue_step % steps);
+        }
+    }
+
+    #[test]
+    fn envs_and_daily_games_on_one_thread_keep_their_own_palettes() {
+        use crate::daily::{Daily, Modifier};
+
+        let mut daily = Daily::for_date(chrono::NaiveDate::from_ymd_opt(2026, 10, 19).unwrap());
+        daily.modifiers = vec![Modifier::FiveColors];
+        let mut game = Game::daily(daily);
+        game.hue = 0.5;
+        let color = game.color();
+        //the reset drops the env's first game while the daily one is still going
+        let mut env = Env::new(Difficulty::NORMAL);
+        env.reset(3);
+        let last = COLOR_STEPS - 1;
+        let (observation, _, _) = env.step(Action {
+            hue_step: last,
+            ..Default::default()
+        });
+        assert_eq!(observation.hue_step, last);
+        drop(env);
+        assert_eq!(game.palette_steps(), 5);
+        assert_eq!(game.color(), color);
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_episode() {
+        let play = || {
+            let mut env = Env::new(Difficulty::HARD);
+            env.reset(7);
+            let mut rewards = Vec::new();
+            for frame in 0..300 {
+                let (observation, reward, _) = env.step(Action::from_index(frame % Action::COUNT));
+                rewards.push((observation.position, observation.hazards.len(), reward));
+            }
+            rewards
+        };
+        assert_eq!(play(), play());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::maths::{Float2, float2_subtract, scale2};
+
+//all durations in seconds, distances in world units
+#[derive(Debug, Clone, Copy)]
+pub struct FeedbackTuning {
+    pub blink_interval: f32,
+    //camera trauma added per hit
+    pub shake_trauma: f32,
+    pub hit_stop: f32,
+    pub knockback_speed: f32,
+    pub knockback_duration: f32,
+    pub burst_count: u32,
+    pub burst_speed: f32,
+}
+
+impl Default for FeedbackTuning {
+    fn default() -> Self {
+        FeedbackTuning {
+            blink_interval: 0.08,
+            shake_trauma: 0.6,
+            hit_stop: 0.06,
+            knockback_speed: 900.0,
+            knockback_duration: 0.2,
+            burst_count: 16,
+            burst_speed: 12.0,
+        }
+    }
+}
+
+//player reactions to a hit, the signal itself only tracks the numbers and the camera shakes
+#[derive(Debug, Clone)]
+pub struct HitFeedback {
+    pub tuning: FeedbackTuning,
+    time: f32,
+    hit_stop: f32,
+    knockback: Float2,
+    knockback_left: f32,
+}
+
+impl HitFeedback {
+    pub fn new(tuning: FeedbackTuning) -> Self {
+        HitFeedback {
+            tuning,
+            time: 0.0,
+            hit_stop: 0.0,
+            knockback: Float2(0.0, 0.0),
+            knockback_left: 0.0,
+        }
+    }
+
+    //pushes the player directly away from whatever hit it
+    pub fn hit(&mut self, player: Float2, hazard: Float2) {
+        self.hit_stop = self.tuning.hit_stop;
+        let away = float2_subtract(player, hazard);
+        let direction = if away.magnitude() < 1.0 {
+            Float2(0.0, 1.0)
+        } else {
+            away.normalized()
+        };
+        self.knockback = scale2(direction, self.tuning.knockback_speed);
+        self.knockback_left = self.tuning.knockback_duration;
+    }
+
+    //real frame time, returns the time the simulation should step by, 0 during hit-stop
+    pub fn update(&mut self, delta_t: f32) -> f32 {
+        self.time += delta_t;
+        if self.hit_stop > 0.0 {
+            self.hit_stop = (self.hit_stop - delta_t).max(0.0);
+            return 0.0;
+        }
+        self.knockback_left = (self.knockback_left - delta_t).max(0.0);
+        delta_t
+    }
+
+    pub fn frozen(&self) -> bool {
+        self.hit_stop > 0.0
+    }
+
+    //displacement for this step, easing out over the knockback duration
+    pub fn knockback(&self, delta_t: f32) -> Float2 {
+        if self.tuning.knockback_duration <= 0.0 {
+            return Float2(0.0, 0.0);
+        }
+        let remaining = self.knockback_left / self.tuning.knockback_duration;
+        scale2(self.knockback, remaining * delta_t)
+    }
+
+    pub fn blink_visible(&self, invulnerable: bool) -> bool {
+        !invulnerable || ((self.time / self.tuning.blink_interval) as u32).is_multiple_of(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_stop_freezes_then_resumes() {
+        let mut feedback = HitFeedback::new(FeedbackTuning::default());
+        let frame = feedback.tuning.hit_stop / 3.0;
+        assert_eq!(feedback.update(frame), frame);
+        feedback.hit(Float2(0.0, 0.0), Float2(10.0, 0.0));
+        assert!(feedback.frozen());
+        //a third of the stop per frame, one extra frame of margin for the f32 countdown
+        let mut frozen_frames = 0;
+        while feedback.frozen() {
+            assert_eq!(feedback.update(frame), 0.0);
+            frozen_frames += 1;
+        }
+        assert!((3..=4).contains(&frozen_frames), "{frozen_frames}");
+        assert!(!feedback.frozen());
+        assert_eq!(feedback.update(frame), frame);
+    }
+
+    #[test]
+    fn knockback_pushes_away_from_the_hazard() {
+        let mut feedback = HitFeedback::new(FeedbackTuning::default());
+        assert_eq!(feedback.knockback(0.1), Float2(0.0, 0.0));
+        feedback.hit(Float2(0.0, 0.0), Float2(10.0, 0.0));
+        let push = feedback.knockback(0.1);
+        assert!(push.0 < 0.0 && push.1.abs() < 1e-6, "{push:?}");
+        //eases out and stops once the duration has passed after the hit-stop
+        while feedback.frozen() {
+            feedback.update(0.01);
+        }
+        feedback.update(0.05);
+        assert!(feedback.knockback(0.1).0 > push.0);
+        feedback.update(feedback.tuning.knockback_duration);
+        assert_eq!(feedback.knockback(0.1), Float2(0.0, 0.0));
+    }
+
+    #[test]
+    fn knockback_on_top_of_the_hazard_pushes_up() {
+        let mut feedback = HitFeedback::new(FeedbackTuning::default());
+        feedback.hit(Float2(5.0, 5.0), Float2(5.0, 5.0));
+        let push = feedback.knockback(0.1);
+        assert!(push.0 == 0.0 && push.1 > 0.0, "{push:?}");
+    }
+}
@@ -0,0 +1,1259 @@
+use std::f32::consts::PI;
+use std::io;
+use std::path::Path;
+
+use hsv::hsv_to_rgb;
+
+use crate::arena::Arena;
+use crate::boss::{Boss, BossAction, GravityWell};
+use crate::camera::Camera2D;
+use crate::clusterbomb::{Blast, Clusterbomb, FlightSpec, FragmentPattern};
+use crate::daily::{Daily, Modifier};
+use crate::debug::{self, Command, DebugTools, HELP, SpawnKind};
+use crate::entities::{
+    Contact, HazardKind, World, bounds_system, collision_system, gravity_system, lifetime_system,
+    movement_system, wall_system,
+};
+use crate::feedback::{FeedbackTuning, HitFeedback};
+use crate::goals::{Goal, GoalPlacement, GoalPlacer};
+use crate::hud;
+use crate::input::{Action, Input};
+use crate::lasers::{Laser, LaserPattern, LaserSpawner, Wave, lane_center};
+use crate::maths::{Float2, Float4, float2_add, scale2};
+use crate::profiler::{Phase, Profiler};
+use crate::rng::{self, random};
+use crate::scoring::{Scoring, ScoringTuning};
+use crate::signal::{HitSource, Signal, SignalEvent};
+use crate::stage::{Lane, SpeedOf, Stage, StageEvent, StageRun, Target};
+use crate::tuning::{Tuning, TuningFile};
+use crate::tutorial::Tutorial;
+use crate::walls::{Wall, WallKind};
+use crate::{
+    COLOR_STEPS, Particle, build_rect, circle_rect_intersect, color_convert, palette_t,
+    rect_intersect, stepped_hue, vertex_t,
+};
+
+//half extents of the virtual view, the same for every frontend
+pub const VIEW_WIDTH: f32 = 1024.0;
+pub const VIEW_HEIGHT: f32 = 768.0;
+//the simulation always steps at this rate
+pub const FPS: f32 = 60.0;
+
+//player params, the speed is in the tuning file
+pub const PLAYER_WIDTH: f32 = 50.0;
+pub const PLAYER_HEIGHT: f32 = 50.0;
+
+const GOAL_WIDTH: f32 = 100.0;
+const GOAL_HEIGHT: f32 = 100.0;
+//a second and third goal join as the score climbs, the furthest from delivery is worth most
+const MULTI_GOAL_SCORE: u32 = 6;
+const MAX_GOALS: u32 = 3;
+const RUN_FILE: &str = "lost-signal-run.txt";
+//the end of run panel, sized like the tutorial prompt
+const GAME_OVER_PIXEL: f32 = 5.0;
+const GAME_OVER_TEXT: Float4 = Float4(1.0, 1.0, 1.0, 1.0);
+const GAME_OVER_PANEL: Float4 = Float4(0.0, 0.0, 0.0, 0.75);
+
+//laser params
+const NUM_PATH_SPAWNS: usize = 10;
+const LASER_DESPAWN_MARGIN: f32 = 150.0;
+const LASER_TRAIL_SPAWN_FRAMES: u64 = 4;
+const PROJECTILE_WIDTH: f32 = 100.0;
+const PROJECTILE_HEIGHT: f32 = PROJECTILE_WIDTH / 10.0;
+
+//jumprope params
+const JUMPROPE_SPAWN_THRESHOLD: f64 = 200.0;
+const JUMPROPE_LIMIT: usize = 4;
+const JUMPROPE_X: f32 = 0.0;
+//wider than the world by this much so a rope can't be walked round
+const JUMPROPE_WIDTH_SCALE: f32 = 2.5;
+const JUMPROPE_HEIGHT: f32 = PROJECTILE_HEIGHT * 2.0;
+
+//clusterbomb params
+pub const CLUSTER_FRAG_COUNT: u32 = 8;
+const CLUSTER_WIDTH: f32 = 35.0;
+const CLUSTER_FRAG_LIFETIME: f32 = 1.0 / 3.0;
+const CLUSTER_FRAG_FRICTION: f32 = 0.75;
+const CLUSTER_BLAST_RADIUS: f32 = 110.0;
+const CLUSTER_MIN_FLIGHT_T: f32 = 2.0;
+const CLUSTER_MAX_FLIGHT_T: f32 = 3.5;
+
+//walls and mirrors the player can place, limited per run
+const WALL_LIMIT: usize = 3;
+const WALL_WIDTH: f32 = 16.0;
+const WALL_HEIGHT: f32 = 180.0;
+
+//boss encounter, takes over spawning until enough goals are delivered
+const BOSS_SCORE: u32 = 12;
+//where the boss sits as a fraction of the world half extents
+const BOSS_ANCHOR: Float2 = Float2(-0.6, 0.7);
+const BOSS_WIDTH: f32 = 160.0;
+const BOSS_COLOR: Float4 = Float4(0.9, 0.1, 0.2, 1.0);
+const GRAVITY_WELL_COLOR: Float4 = Float4(0.6, 0.0, 0.1, 0.8);
+const REDSHIFT_STEPS: u32 = 3;
+
+const HIT_COLOR: Float4 = Float4(1.0, 0.0, 0.0, 1.0);
+
+//starting hazard speeds and counts and how quickly deliveries ramp them up
+#[derive(Debug, Clone, Copy)]
+pub struct Difficulty {
+    pub name: &'static str,
+    pub laser_speed: f32,
+    pub jumprope_speed: f32,
+    //lasers per volley at the start, one more every second delivery
+    pub spawns: usize,
+    //hazard speeds are multiplied by this on every delivery
+    pub speedup: f32,
+    //first and second clusterbombs join at these scores
+    pub cluster_start_score: u32,
+    pub cluster_increase_score: u32,
+    //world half extents, the camera follows the player once these are larger than the view
+    pub world: Float2,
+}
+
+impl Difficulty {
+    pub const EASY: Difficulty = Difficulty {
+        name: "easy",
+        laser_speed: 350.0,
+        jumprope_speed: 120.0,
+        spawns: 1,
+        speedup: 1.03,
+        cluster_start_score: 6,
+        cluster_increase_score: 12,
+        world: Float2(VIEW_WIDTH, VIEW_HEIGHT),
+    };
+    //the tuned game
+    pub const NORMAL: Difficulty = Difficulty {
+        name: "normal",
+        laser_speed: 450.0,
+        jumprope_speed: 150.0,
+        spawns: 2,
+        speedup: 1.05,
+        cluster_start_score: 4,
+        cluster_increase_score: 8,
+        world: Float2(VIEW_WIDTH, VIEW_HEIGHT),
+    };
+    pub const HARD: Difficulty = Difficulty {
+        name: "hard",
+        laser_speed: 550.0,
+        jumprope_speed: 180.0,
+        spawns: 3,
+        speedup: 1.07,
+        cluster_start_score: 2,
+        cluster_increase_score: 6,
+        //more room than fits on screen, hazards come in from out of view
+        world: Float2(VIEW_WIDTH * 1.5, VIEW_HEIGHT * 1.25),
+    };
+    pub const ALL: [Difficulty; 3] = [Difficulty::EASY, Difficulty::NORMAL, Difficulty::HARD];
+
+    pub fn named(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name == name)
+    }
+}
+
+//one frame of vertices in world space, shared by every renderer
+#[derive(Debug, Default)]
+pub struct Scene {
+    //cluster landing zones, blasts and gravity wells, z carries the fill amount
+    pub telegraphs: Vec<vertex_t>,
+    pub lasers: Vec<vertex_t>,
+    pub jumpropes: Vec<vertex_t>,
+    pub clusters: Vec<vertex_t>,
+    pub fragments: Vec<vertex_t>,
+    pub particles: Vec<vertex_t>,
+    pub walls: Vec<vertex_t>,
+    pub boss: Vec<vertex_t>,
+    pub player: Vec<vertex_t>,
+    //z carries how far the picked hue is from the goal's, greying it out
+    pub goals: Vec<vertex_t>,
+    //debug overlay and console, drawn last in flat color
+    pub overlay: Vec<vertex_t>,
+    pub view_projection: [[f32; 4]; 4],
+    //player relative to the camera in viewport units, frontends turn it into pixels
+    pub player_view: Float2,
+    pub zoom: f32,
+    pub signal_radius: f32,
+    pub signal_lost: f32,
+}
+
+impl Scene {
+    //everything drawn with the box shader, back to front
+    pub fn boxes(&self) -> [&[vertex_t]; 8] {
+        [
+            &self.lasers,
+            &self.jumpropes,
+            &self.clusters,
+            &self.fragments,
+            &self.particles,
+            &self.walls,
+            &self.boss,
+            &self.player,
+        ]
+    }
+
+    //tuned as pixels of the original 1x window, where a pixel was two world units
+    pub fn radius_pixels(&self, scale: f32) -> f32 {
+        self.signal_radius * self.zoom * scale * 2.0
+    }
+}
+
+pub struct Game {
+    pub frames: u64,
+    //raw delivered points that drive difficulty, scoring adds the bonuses on top
+    pub score: u32,
+    pub position: Float2,
+    pub hue: f64,
+    pub arena: Arena,
+    pub goals: Vec<Goal>,
+    pub carrying: Option<Goal>,
+    pub deliveries: u32,
+    pub scoring: Scoring,
+    //every hazard lives in the entity store, bombs and blasts keep their own lists
+    pub world: World,
+    pub walls: Vec<Wall>,
+    pub clusters: Vec<Clusterbomb>,
+    pub blasts: Vec<Blast>,
+    pub boss: Option<Boss>,
+    pub gravity_wells: Vec<GravityWell>,
+    pub signal: Signal,
+    pub feedback: HitFeedback,
+    pub camera: Camera2D,
+    pub difficulty: Difficulty,
+    pub current_spawns: usize,
+    pub laser_speed: f32,
+    pub jumprope_speed: f32,
+    pub scene: Scene,
+    //prints each delivery, off for headless runs
+    pub verbose: bool,
+    pub debug: DebugTools,
+    pub profiler: Profiler,
+    pub tuning: Tuning,
+    //watched while the game runs, None for headless runs
+    pub tuning_file: Option<TuningFile>,
+    //why the last edit to the tuning file was rejected, shown until a good edit lands
+    pub tuning_errors: Vec<String>,
+    //a scripted stage replaces the procedural spawners and the boss
+    pub stage: Option<StageRun>,
+    //guided steps that swap the stage as each one is completed
+    pub tutorial: Option<Tutorial>,
+    pub daily: Option<Daily>,
+    //the game over panel was closed with enter, the frontend exits on it
+    pub dismissed: bool,
+    //hue steps this game plays on, the daily can narrow the palette
+    palette_steps: u32,
+    goal_placer: GoalPlacer,
+    boss_goal_placer: GoalPlacer,
+    laser_spawners: Vec<LaserSpawner>,
+    laser_ghosts: Vec<Particle>,
+    particles: Vec<Particle>,
+    jumprope_accum: f64,
+    boss_fought: bool,
+}
+
+//a palette step or a random palette hue
+fn palette_color(hue: Option<u32>, palette_steps: u32) -> Float4 {
+    let t = hue.map_or_else(random, |hue| palette_t(hue, palette_steps));
+    color_convert(hsv_to_rgb(stepped_hue(t, palette_steps), 1.0, 1.0))
+}
+
+impl Game {
+    pub fn with_difficulty(difficulty: Difficulty) -> Self {
+        Game::with_palette(difficulty, COLOR_STEPS)
+    }
+
+    //the palette goes in first so the first goals are placed on it
+    fn with_palette(difficulty: Difficulty, palette_steps: u32) -> Self {
+        let palette_steps = palette_steps.clamp(1, COLOR_STEPS);
+        //bounds, fixed walls, goal spawns and delivery zones
+        let arena = Arena::standard(difficulty.world);
+        let goal_placer = GoalPlacer::new(
+            GoalPlacement::RandomSafe { clearance: 250.0 },
+            Float2(GOAL_WIDTH, GOAL_HEIGHT),
+        );
+        let goals = goal_placer.place(&arena, &[], 1, palette_steps);
+        let camera = Camera2D::new(Float2(VIEW_WIDTH, VIEW_HEIGHT)).with_bounds(arena.bounds);
+        let tuning = Tuning::for_difficulty(difficulty);
+        let mut game = Game {
+            frames: 0,
+            score: 0,
+            position: Float2(0.0, 0.0),
+            hue: 0.0,
+            arena,
+            goals,
+            carrying: None,
+            deliveries: 0,
+            scoring: Scoring::new(ScoringTuning::default()),
+            world: World::new(),
+            walls: Vec::new(),
+            clusters: Vec::new(),
+            blasts: Vec::new(),
+            boss: None,
+            gravity_wells: Vec::new(),
+            signal: Signal::new(300.0, tuning.signal),
+            feedback: HitFeedback::new(FeedbackTuning::default()),
+            camera,
+            difficulty,
+            current_spawns: difficulty.spawns,
+            laser_speed: tuning.laser_speed,
+            jumprope_speed: tuning.jumprope_speed,
+            scene: Scene::default(),
+            verbose: true,
+            debug: DebugTools::default(),
+            profiler: Profiler::new(),
+            tuning,
+            tuning_file: None,
+            tuning_errors: Vec::new(),
+            stage: None,
+            tutorial: None,
+            daily: None,
+            dismissed: false,
+            palette_steps,
+            goal_placer,
+            //the boss fight keeps goals on the fixed spawn points so its script stays readable
+            boss_goal_placer: GoalPlacer::new(
+                GoalPlacement::SpawnPoints,
+                Float2(GOAL_WIDTH, GOAL_HEIGHT),
+            ),
+            laser_spawners: vec![
+                LaserSpawner::new(LaserPattern::Lanes, 0.3, 0),
+                LaserSpawner::new(
+                    LaserPattern::SineLanes(Wave {
+                        amplitude: 60.0,
+                        wavelength: 500.0,
+                    }),
+                    2.5,
+                    3,
+                ),
+                LaserSpawner::new(LaserPattern::DiagonalSweep { angle: PI / 8.0 }, 4.0, 6),
+                LaserSpawner::new(
+                    LaserPattern::ConvergingFan {
+                        count: 5,
+                        spread: 600.0,
+                    },
+                    6.0,
+                    10,
+                )
+                .with_ramp(0.6),
+            ],
+            laser_ghosts: Vec::new(),
+            particles: Vec::new(),
+            jumprope_accum: 0.0,
+            boss_fought: false,
+        };
+        //spawn initial jumprope
+        game.spawn_jumprope();
+        game
+    }
+
+    //the date's seed and palette go in before anything random is rolled, always on normal
+    pub fn daily(daily: Daily) -> Self {
+        rng::seed(daily.seed);
+        let mut game = Game::with_palette(
+            Difficulty::NORMAL,
+            daily.palette_steps().unwrap_or(COLOR_STEPS),
+        );
+        daily.apply(&mut game);
+        game.daily = Some(daily);
+        game
+    }
+
+    //creates the file from the current values if needed, then re-applies it whenever it is saved
+    pub fn watch_tuning(&mut self, path: &Path) -> io::Result<()> {
+        self.tuning_file = Some(TuningFile::open(path, &self.tuning)?);
+        Ok(())
+    }
+
+    //hazards already in flight keep their speed, the next ones and the delivery ramp use the new one
+    pub fn apply_tuning(&mut self, tuning: Tuning) {
+        self.laser_speed *= tuning.laser_speed / self.tuning.laser_speed;
+        self.jumprope_speed *= tuning.jumprope_speed / self.tuning.jumprope_speed;
+        self.signal.weights = tuning.signal;
+        self.tuning = tuning;
+    }
+
+    //a bad edit keeps the running values and leaves the errors on screen
+    fn reload_tuning(&mut self) {
+        let base = Tuning::for_difficulty(self.difficulty);
+        let Some(result) = self.tuning_file.as_mut().and_then(|file| file.poll(base)) else {
+            return;
+        };
+        match result {
+            Ok(tuning) => {
+                if tuning != self.tuning {
+                    self.apply_tuning(tuning);
+                    self.debug.console.print("tuning applied".to_string());
+                }
+                self.tuning_errors.clear();
+            }
+            Err(errors) => {
+                for error in errors.iter() {
+                    self.debug.console.print(error.clone());
+                }
+                self.tuning_errors = errors;
+            }
+        }
+    }
+
+    //starts the script from an empty field, hazard speeds begin at the difficulty's
+    pub fn play_stage(&mut self, stage: Stage) {
+        self.world = World::new();
+        self.clusters.clear();
+        self.blasts.clear();
+        self.stage = Some(StageRun::new(stage));
+    }
+
+    pub fn play_tutorial(&mut self) {
+        self.tutorial = Some(Tutorial::start(self));
+    }
+
+    //every event has fired and the last hazard has left, tutorial steps wait on the player instead
+    pub fn stage_cleared(&self) -> bool {
+        self.tutorial.is_none()
+            && self.stage.as_ref().is_some_and(StageRun::finished)
+            && self.clusters.is_empty()
+            && self.world.entities().is_empty()
+    }
+
+    pub fn over(&self) -> bool {
+        (!self.debug.god && self.tutorial.is_none() && self.signal.is_lost())
+            || self.stage_cleared()
+            || self.tutorial.as_ref().is_some_and(Tutorial::finished)
+    }
+
+    //spawn lanes across the world height, the stage's while one is playing
+    pub fn lanes(&self) -> usize {
+        match self.stage.as_ref() {
+            Some(run) => run.stage.lanes,
+            None => self.current_spawns,
+        }
+    }
+
+    fn boss_position(&self) -> Float2 {
+        Float2(
+            self.arena.bounds.0 * BOSS_ANCHOR.0,
+            self.arena.bounds.1 * BOSS_ANCHOR.1,
+        )
+    }
+
+    //clusterbombs kept in the air at the current score
+    pub fn cluster_count(&self) -> u32 {
+        if !self.clusters_allowed() {
+            return 0;
+        }
+        (self.score >= self.difficulty.cluster_start_score) as u32
+            + (self.score >= self.difficulty.cluster_increase_score) as u32
+    }
+
+    fn clusters_allowed(&self) -> bool {
+        !self
+            .daily
+            .as_ref()
+            .is_some_and(|daily| daily.has(Modifier::NoClusters))
+    }
+
+    pub fn color(&self) -> Float4 {
+        color_convert(hsv_to_rgb(
+            stepped_hue(self.hue, self.palette_steps),
+            1.0,
+            1.0,
+        ))
+    }
+
+    pub fn palette_steps(&self) -> u32 {
+        self.palette_steps
+    }
+
+    fn spawn_jumprope(&mut self) {
+        self.spawn_jumprope_colored(palette_color(None, self.palette_steps));
+    }
+
+    fn spawn_jumprope_colored(&mut self, color: Float4) {
+        self.world.spawn_jumprope(
+            Float2(JUMPROPE_X, self.arena.bounds.1),
+            Float2(self.arena.bounds.0 * JUMPROPE_WIDTH_SCALE, JUMPROPE_HEIGHT),
+            self.jumprope_speed,
+            color,
+        );
+    }
+
+    fn spawn_lasers(&mut self, lasers: Vec<(crate::lasers::Laser, Float4)>) {
+        for (laser, laser_color) in lasers {
+            self.world.spawn_laser(
+                laser,
+                laser_color,
+                Float2(PROJECTILE_WIDTH, PROJECTILE_HEIGHT),
+            );
+        }
+    }
+
+    //penalty, combo reset, shake and a red burst at the player, skipped while invulnerable
+    //false when god mode or the i-frames ignored it
+    fn hit(&mut self, source: HitSource, point: Float2) -> bool {
+        if self.debug.god || self.signal.apply(SignalEvent::Hit(source)) <= 0.0 {
+            return false;
+        }
+        self.feedback.hit(self.position, point);
+        self.scoring.hit();
+        self.camera.add_trauma(self.feedback.tuning.shake_trauma);
+        for _ in 0..self.feedback.tuning.burst_count {
+            self.particles.push(Particle::spawn(
+                self.position,
+                self.feedback.tuning.burst_speed,
+                0.0,
+                Float2(0.0, 0.0),
+                HIT_COLOR,
+            ));
+        }
+        true
+    }
+
+    //one fixed step of simulation, leaving this frame's vertices in scene
+    pub fn step(&mut self, input: &Input) {
+        //the run is over, the last frame stays up under the score breakdown until enter
+        if self.over() {
+            self.dismissed |= input.typed().contains('\n');
+            self.scene.overlay.clear();
+            let panel = self.game_over_panel();
+            self.overlay_in_view(panel);
+            return;
+        }
+        self.profiler.next_frame();
+        let span = self.profiler.start();
+        self.frames += 1;
+        self.reload_tuning();
+        self.hue = input.hue;
+        //hit-stop freezes the simulation, rendering and particles carry on
+        let delta_t = self.feedback.update(1.0 / FPS);
+        self.signal.update(delta_t);
+        self.scoring.update(delta_t);
+        let color = self.color();
+        let mut scene = Scene::default();
+
+        if input.pressed(Action::ToggleOverlay) {
+            self.debug.overlay = !self.debug.overlay;
+        }
+        if input.pressed(Action::ToggleProfiler) {
+            self.debug.profiler = !self.debug.profiler;
+        }
+        if input.pressed(Action::ToggleConsole) {
+            self.debug.console.open = !self.debug.console.open;
+        }
+        //while the console is open keys type into it instead of playing
+        let playing = !self.debug.console.open;
+        if !playing && let Some(line) = self.debug.console.feed(input.typed()) {
+            self.execute(&line);
+        }
+
+        //a wall in the current hue or a mirror
+        for (action, kind) in [
+            (Action::PlaceWall, WallKind::Colored(color)),
+            (Action::PlaceMirror, WallKind::Mirror),
+        ] {
+            if playing && input.pressed(action) && self.walls.len() < WALL_LIMIT {
+                let rotation = if kind == WallKind::Mirror {
+                    PI / 4.0
+                } else {
+                    0.0
+                };
+                self.walls.push(Wall::new(
+                    self.position,
+                    WALL_WIDTH,
+                    WALL_HEIGHT,
+                    rotation,
+                    kind,
+                ));
+            }
+        }
+
+        let movement = if playing {
+            input.movement()
+        } else {
+            Float2(0.0, 0.0)
+        };
+        let step = float2_add(
+            self.feedback.knockback(delta_t),
+            scale2(movement, self.tuning.player_speed * delta_t),
+        );
+        if playing && input.held(Action::Drain) {
+            self.signal.apply(SignalEvent::Drain(delta_t));
+        }
+        self.position = self.arena.resolve(
+            self.position,
+            float2_add(self.position, step),
+            Float2(PLAYER_WIDTH / 2.0, PLAYER_HEIGHT / 2.0),
+        );
+        let Float2(x, y) = self.position;
+
+        let player_rect = build_rect(x, y, PLAYER_WIDTH, PLAYER_HEIGHT, 0.0, color);
+        scene.player.extend_from_slice(&player_rect);
+        self.profiler.record(Phase::Input, span);
+        let span = self.profiler.start();
+
+        //fixed arena walls act on hazards the same as placed ones
+        let all_walls: Vec<Wall> = self
+            .arena
+            .walls
+            .iter()
+            .chain(self.walls.iter())
+            .copied()
+            .collect();
+        let wall_rects: Vec<Vec<vertex_t>> = all_walls.iter().map(|wall| wall.rect()).collect();
+        for rect in wall_rects.iter() {
+            scene.walls.extend_from_slice(rect);
+        }
+
+        if self.boss.is_none()
+            && self.stage.is_none()
+            && !self.boss_fought
+            && self.score >= BOSS_SCORE
+        {
+            self.boss = Some(Boss::redshift_boss(self.boss_position()));
+            self.boss_fought = true;
+        }
+        //the boss script replaces the procedural spawners while it is alive
+        let boss_actions = match self.boss.as_mut() {
+            Some(boss) => boss.update(delta_t),
+            None => Vec::new(),
+        };
+        for action in boss_actions {
+            match action {
+                BossAction::Lasers(pattern) => {
+                    let volley = LaserSpawner::new(pattern, 0.0, 0).volley(
+                        self.current_spawns.max(4),
+                        self.arena.bounds,
+                        self.laser_speed,
+                        self.position,
+                        self.palette_steps,
+                    );
+                    self.spawn_lasers(volley);
+                }
+                BossAction::Jumprope => self.spawn_jumprope(),
+                BossAction::Clusters { count } => {
+                    let count = if self.clusters_allowed() { count } else { 0 };
+                    for _ in 0..count {
+                        let target = Float2(
+                            x + (random::<f32>() - 0.5) * self.arena.bounds.0 / 2.0,
+                            y + (random::<f32>() - 0.5) * self.arena.bounds.1 / 2.0,
+                        );
+                        self.clusters.extend(Clusterbomb::lob(
+                            self.boss_position(),
+                            target,
+                            CLUSTER_MIN_FLIGHT_T,
+                            CLUSTER_MAX_FLIGHT_T,
+                            CLUSTER_BLAST_RADIUS,
+                            self.palette_steps,
+                        ));
+                    }
+                }
+                BossAction::GravityWell {
+                    strength,
+                    radius,
+                    duration,
+                } => {
+                    self.gravity_wells.push(GravityWell::new(
+                        self.position,
+                        strength,
+                        radius,
+                        duration,
+                    ));
+                }
+            }
+        }
+        for well in self.gravity_wells.iter_mut() {
+            let mut well_rect = build_rect(
+                well.position.0,
+                well.position.1,
+                well.radius * 2.0,
+                well.radius * 2.0,
+                0.0,
+                GRAVITY_WELL_COLOR,
+            );
+            for vert in well_rect.iter_mut() {
+                vert.position.2 = well.remaining();
+            }
+            scene.telegraphs.append(&mut well_rect);
+            well.lifetime -= delta_t;
+        }
+        self.gravity_wells.retain(|well| well.lifetime > 0.0);
+        if let Some(boss) = self.boss.as_ref() {
+            scene.boss.append(&mut build_rect(
+                boss.position.0,
+                boss.position.1,
+                BOSS_WIDTH,
+                BOSS_WIDTH / 2.0,
+                self.frames as f32 / FPS,
+                BOSS_COLOR,
+            ));
+            let health_width = BOSS_WIDTH * boss.health as f32 / boss.max_health as f32;
+            scene.boss.append(&mut build_rect(
+                boss.position.0 - (BOSS_WIDTH - health_width) / 2.0,
+                boss.position.1 + BOSS_WIDTH * 0.6,
+                health_width,
+                12.0,
+                0.0,
+                BOSS_COLOR,
+            ));
+        }
+
+        if let Some(mut tutorial) = self.tutorial.take() {
+            tutorial.update(self, delta_t);
+            self.tutorial = Some(tutorial);
+        }
+        let stage_events = match self.stage.as_mut() {
+            Some(run) => run.update(delta_t),
+            None => Vec::new(),
+        };
+        for event in stage_events {
+            self.stage_event(event);
+        }
+        let procedural = self.boss.is_none() && self.stage.is_none();
+
+        //check jumprope spawn
+        self.jumprope_accum += random::<f64>();
+        if self.jumprope_accum >= JUMPROPE_SPAWN_THRESHOLD
+            && self.world.count(HazardKind::Jumprope) < JUMPROPE_LIMIT
+            && procedural
+        {
+            self.spawn_jumprope();
+            self.jumprope_accum = 0.0;
+        }
+
+        if procedural {
+            let mut lasers = Vec::new();
+            for spawner in self
+                .laser_spawners
+                .iter_mut()
+                .filter(|spawner| self.score >= spawner.unlock_score)
+            {
+                lasers.extend(spawner.update(
+                    delta_t,
+                    self.current_spawns,
+                    self.arena.bounds,
+                    self.laser_speed,
+                    self.position,
+                    self.palette_steps,
+                ));
+            }
+            self.spawn_lasers(lasers);
+        }
+
+        if self.clusters.is_empty() && procedural {
+            for _ in 0..self.cluster_count() {
+                let start_pos = Float2(
+                    (random::<f32>() * 2.0 - 1.0) * self.arena.bounds.0,
+                    (random::<f32>() * 2.0 - 1.0) * self.arena.bounds.1,
+                );
+                let target = Float2(
+                    x + random::<f32>() * self.arena.bounds.0 / 4.0,
+                    y + random::<f32>() * self.arena.bounds.1 / 4.0,
+                );
+                //impossible lobs are dropped, the spawner tries again next frame
+                self.clusters.extend(Clusterbomb::lob(
+                    start_pos,
+                    target,
+                    CLUSTER_MIN_FLIGHT_T,
+                    CLUSTER_MAX_FLIGHT_T,
+                    CLUSTER_BLAST_RADIUS,
+                    self.palette_steps,
+                ));
+            }
+        }
+
+        self.profiler.record(Phase::Spawn, span);
+        let span = self.profiler.start();
+
+        let mut clusters = std::mem::take(&mut self.clusters);
+        for bomb in clusters.iter_mut() {
+            let current_pos = bomb.update(delta_t);
+            scene.clusters.append(&mut build_rect(
+                current_pos.0,
+                current_pos.1,
+                CLUSTER_WIDTH,
+                CLUSTER_WIDTH,
+                0.0,
+                bomb.color,
+            ));
+            //landing circle, z carries the fill amount to the telegraph shader
+            let mut telegraph = build_rect(
+                bomb.end_pos.0,
+                bomb.end_pos.1,
+                bomb.blast_radius * 2.0,
+                bomb.blast_radius * 2.0,
+                0.0,
+                bomb.color,
+            );
+            for vert in telegraph.iter_mut() {
+                vert.position.2 = bomb.progress();
+            }
+            scene.telegraphs.append(&mut telegraph);
+            if bomb.detonated() {
+                if circle_rect_intersect(bomb.end_pos, bomb.blast_radius, &player_rect)
+                    && player_rect[0].color != bomb.color
+                {
+                    self.hit(HitSource::Blast, bomb.end_pos);
+                }
+                self.blasts.push(Blast::new(bomb));
+                for velocity in bomb
+                    .pattern
+                    .velocities(CLUSTER_FRAG_COUNT, self.tuning.cluster_frag_speed)
+                {
+                    //pattern speeds are per frame
+                    self.world.spawn_fragment(
+                        bomb.end_pos,
+                        scale2(velocity, FPS),
+                        Float2(CLUSTER_WIDTH, CLUSTER_WIDTH),
+                        bomb.color,
+                        CLUSTER_FRAG_LIFETIME,
+                        CLUSTER_FRAG_FRICTION,
+                    );
+                }
+            }
+        }
+        clusters.retain(|bomb| !bomb.detonated());
+        self.clusters = clusters;
+
+        for blast in self.blasts.iter_mut() {
+            let mut flash = build_rect(
+                blast.position.0,
+                blast.position.1,
+                blast.radius * 2.0,
+                blast.radius * 2.0,
+                0.0,
+                Float4(blast.color.0, blast.color.1, blast.color.2, blast.lifetime),
+            );
+            for vert in flash.iter_mut() {
+                vert.position.2 = 1.0;
+            }
+            scene.telegraphs.append(&mut flash);
+            blast.lifetime -= 4.0 * delta_t;
+        }
+        self.blasts.retain(|blast| blast.lifetime > 0.0);
+
+        movement_system(&mut self.world, delta_t);
+        gravity_system(
+            &mut self.world,
+            &self.gravity_wells,
+            delta_t,
+            REDSHIFT_STEPS,
+            self.palette_steps,
+        );
+        wall_system(&mut self.world, &all_walls, &wall_rects);
+        //spent and escaped hazards go before they can touch the player
+        lifetime_system(&mut self.world, delta_t);
+        bounds_system(&mut self.world, self.arena.bounds, LASER_DESPAWN_MARGIN);
+        self.profiler.record(Phase::Movement, span);
+        let span = self.profiler.start();
+        for contact in collision_system(&mut self.world, self.position, &player_rect) {
+            match contact {
+                //an ignored hit passes through instead of using the hazard up
+                Contact::Hit {
+                    entity,
+                    source,
+                    point,
+                } => {
+                    if self.hit(source, point) {
+                        self.world.despawn(entity);
+                    }
+                }
+                Contact::Graze(source) => {
+                    self.signal.apply(SignalEvent::Graze(source));
+                    self.scoring.graze(delta_t);
+                }
+            }
+        }
+        self.profiler.record(Phase::Collision, span);
+        let span = self.profiler.start();
+
+        for entity in self.world.entities() {
+            let (Some(mut rect), Some(&kind)) =
+                (self.world.rect(entity), self.world.hazards.get(entity))
+            else {
+                continue;
+            };
+            let transform = self.world.transforms.get(entity).unwrap();
+            let hazard_color = rect[0].color;
+            match kind {
+                HazardKind::Laser => {
+                    if self.frames.is_multiple_of(LASER_TRAIL_SPAWN_FRAMES) {
+                        let mut ghost = Particle::spawn(
+                            transform.position,
+                            0.0,
+                            0.0,
+                            Float2(0.0, 0.0),
+                            hazard_color,
+                        );
+                        ghost.rotation = transform.rotation;
+                        self.laser_ghosts.insert(0, ghost)
+                    }
+                    scene.lasers.append(&mut rect);
+                }
+                HazardKind::Jumprope => {
+                    //particles at both ends of the band
+                    for (end_x, bias) in [(self.arena.bounds.0, -5.0), (-self.arena.bounds.0, 5.0)]
+                    {
+                        for _ in 0..2 {
+                            self.particles.push(Particle::spawn(
+                                Float2(JUMPROPE_X + end_x, transform.position.1),
+                                10.0,
+                                3.0,
+                                Float2(bias, 0.0),
+                                hazard_color,
+                            ));
+                        }
+                    }
+                    scene.jumpropes.append(&mut rect);
+                }
+                HazardKind::Fragment => scene.fragments.append(&mut rect),
+            }
+        }
+
+        self.profiler.record(Phase::Vertices, span);
+        let span = self.profiler.start();
+
+        self.particles.retain(|particle| particle.lifetime > 0.0);
+
+        for unit in self.particles.iter_mut() {
+            unit.update();
+            scene.particles.append(&mut build_rect(
+                unit.position.0,
+                unit.position.1,
+                self.tuning.particle_width,
+                self.tuning.particle_width,
+                0.0,
+                Float4(unit.color.0, unit.color.1, unit.color.2, unit.lifetime),
+            ));
+        }
+
+        for ghost in self.laser_ghosts.iter_mut() {
+            scene.particles.append(&mut build_rect(
+                ghost.position.0,
+                ghost.position.1,
+                PROJECTILE_WIDTH,
+                PROJECTILE_HEIGHT,
+                ghost.rotation,
+                Float4(ghost.color.0, ghost.color.1, ghost.color.2, ghost.lifetime),
+            ));
+            ghost.lifetime -= 3.0 / FPS;
+        }
+        self.laser_ghosts.retain(|ghost| ghost.lifetime > 0.0);
+        self.profiler.record(Phase::Particles, span);
+        let span = self.profiler.start();
+
+        if let Some(goal) = self.carrying
+            && self.arena.delivery().contains(self.position)
+        {
+            self.deliver(goal);
+        }
+        let delivery_color = match self.carrying {
+            Some(goal) => {
+                let carried = goal.color(self.palette_steps);
+                Float4(carried.0, carried.1, carried.2, 0.3)
+            }
+            None => Float4(1.0, 1.0, 1.0, 0.08),
+        };
+        scene
+            .walls
+            .append(&mut self.arena.delivery().rect(delivery_color));
+        for goal in self.goals.iter() {
+            let held = self.carrying.is_some_and(|carried| {
+                carried.hue(self.palette_steps) == goal.hue(self.palette_steps)
+            });
+            //carried goals stay put, greyed out until delivered
+            let saturation = if held { 0.0 } else { 1.0 };
+            let mut goal_rect = build_rect(
+                goal.position.0,
+                goal.position.1,
+                GOAL_WIDTH,
+                GOAL_HEIGHT,
+                0.0,
+                color_convert(hsv_to_rgb(goal.hue(self.palette_steps), saturation, 1.0)),
+            );
+            if self.carrying.is_none()
+                && rect_intersect(&player_rect, &goal_rect)
+                && stepped_hue(self.hue, self.palette_steps) == goal.hue(self.palette_steps)
+            {
+                self.carrying = Some(*goal);
+            }
+            for vert in goal_rect.iter_mut() {
+                vert.position.2 = (self.hue - goal.t).abs() as f32 * 10.0;
+            }
+            scene.goals.extend(goal_rect);
+        }
+        //player flashes red through the hit-stop and blinks while invulnerable
+        for vert in scene.player.iter_mut() {
+            if self.feedback.frozen() {
+                vert.color = HIT_COLOR;
+            }
+            if !self.feedback.blink_visible(self.signal.invulnerable()) {
+                vert.color.3 = 0.0;
+            }
+        }
+        //boss phases zoom in, the camera keeps the player in view within the world
+        self.camera
+            .zoom_to(self.boss.as_ref().map_or(1.0, |boss| boss.zoom()));
+        self.camera.update(self.position, 1.0 / FPS);
+        scene.view_projection = self.camera.view_projection();
+        scene.player_view = self.camera.world_to_view(self.position);
+        scene.zoom = self.camera.zoom;
+        scene.signal_radius = self.signal.radius();
+        scene.signal_lost = self.signal.lost();
+        self.scene = scene;
+        if let Some(tutorial) = self.tutorial.as_ref() {
+            let prompt = tutorial.prompt(self.camera.viewport);
+            self.overlay_in_view(prompt);
+        }
+        let overlay = debug::overlay(self);
+        self.scene.overlay.extend(overlay);
+        self.profiler.record(Phase::Vertices, span);
+    }
+
+    //panels laid out in view units like the debug text, moved under the camera into the overlay
+    fn overlay_in_view(&mut self, verts: Vec<vertex_t>) {
+        for mut vert in verts {
+            let world = self
+                .camera
+                .view_to_world(Float2(vert.position.0, vert.position.1));
+            vert.position.0 = world.0;
+            vert.position.1 = world.1;
+            self.scene.overlay.push(vert);
+        }
+    }
+
+    //how the run ended, the first line of the game over panel and the report
+    fn outcome(&self) -> String {
+        if let Some(tutorial) = self.tutorial.as_ref() {
+            let outcome = if tutorial.finished() {
+                "complete"
+            } else {
+                "left unfinished"
+            };
+            format!("tutorial {outcome}")
+        } else if let Some(run) = self.stage.as_ref() {
+            let outcome = if self.stage_cleared() {
+                "cleared"
+            } else {
+                "failed"
+            };
+            format!("stage {} {outcome}", run.stage.name)
+        } else {
+            "signal lost".to_string()
+        }
+    }
+
+    //the outcome and score breakdown centered in the view
+    fn game_over_panel(&self) -> Vec<vertex_t> {
+        let mut lines = vec![self.outcome(), String::new()];
+        if let Some(daily) = self.daily.as_ref() {
+            lines.insert(1, format!("daily {}", daily.modifier_names()));
+        }
+        lines.extend(self.scoring.breakdown.to_string().lines().map(String::from));
+        lines.extend([String::new(), "press enter to quit".to_string()]);
+        let width = lines
+            .iter()
+            .map(|line| hud::text_width(line, GAME_OVER_PIXEL))
+            .fold(0.0, f32::max);
+        let height = lines.len() as f32 * hud::LINE_HEIGHT * GAME_OVER_PIXEL;
+        let mut out = Vec::new();
+        hud::text_block(
+            &mut out,
+            &lines,
+            Float2(-width / 2.0, height / 2.0),
+            GAME_OVER_PIXEL,
+            GAME_OVER_TEXT,
+            GAME_OVER_PANEL,
+        );
+        out
+    }
+
+    //runs a console line, echoing it and the outcome into the console history
+    pub fn execute(&mut self, line: &str) {
+        if line.trim().is_empty() {
+            return;
+        }
+        let reply = match Command::parse(line) {
+            Ok(command) => self.command(command),
+            Err(error) => error,
+        };
+        self.debug.console.print(format!("> {line}"));
+        self.debug.console.print(reply);
+        if let Some(daily) = self.daily.as_mut()
+            && daily.scored
+        {
+            daily.scored = false;
+            self.debug
+                .console
+                .print("console used, this daily run won't be scored".to_string());
+        }
+    }
+
+    //one scripted spawn or speed change, lanes count up from the bottom of the world
+    fn stage_event(&mut self, event: StageEvent) {
+        match event {
+            StageEvent::Laser { lane, color } => {
+                let lanes = self.lanes();
+                let bounds = self.arena.bounds;
+                let lane = match lane {
+                    Lane::Index(lane) => lane,
+                    Lane::Player => {
+                        let lane_height = 2.0 * bounds.1 / lanes as f32;
+                        (((self.position.1 + bounds.1) / lane_height) as usize).min(lanes - 1)
+                    }
+                };
+                let laser = Laser::new(
+                    Float2(bounds.0, lane_center(lane, lanes, bounds.1)),
+                    Float2(-1.0, 0.0),
+                    self.laser_speed,
+                );
+                self.spawn_lasers(vec![(laser, palette_color(color, self.palette_steps))]);
+            }
+            StageEvent::Jumprope { color } => {
+                self.spawn_jumprope_colored(palette_color(color, self.palette_steps))
+            }
+            StageEvent::Cluster {
+                target,
+                from,
+                color,
+            } => {
+                let target = match target {
+                    Target::Point(point) => point,
+                    Target::Player => self.position,
+                };
+                let start = from.unwrap_or(Float2(target.0, self.arena.bounds.1));
+                //a script asking for an impossible lob loses that bomb
+                if let Ok(bomb) = Clusterbomb::from_positions(
+                    start,
+                    target,
+                    FlightSpec::Time(CLUSTER_MIN_FLIGHT_T),
+                    palette_color(color, self.palette_steps),
+                ) {
+                    self.clusters
+                        .push(bomb.with_blast(CLUSTER_BLAST_RADIUS, FragmentPattern::random()));
+                }
+            }
+            StageEvent::Speed(SpeedOf::Laser, speed) => self.laser_speed = speed,
+            StageEvent::Speed(SpeedOf::Jumprope, speed) => self.jumprope_speed = speed,
+        }
+    }
+
+    pub fn command(&mut self, command: Command) -> String {
+        match command {
+            Command::Score(score) => {
+                self.score = score;
+                format!("score {score}")
+            }
+            Command::Spawn(SpawnKind::Laser, hue) => {
+                //in the player's row so it is guaranteed to arrive
+                let laser = Laser::new(
+                    Float2(self.arena.bounds.0, self.position.1),
+                    Float2(-1.0, 0.0),
+                    self.laser_speed,
+                );
+                self.spawn_lasers(vec![(laser, palette_color(hue, self.palette_steps))]);
+                "laser spawned".to_string()
+            }
+            Command::Spawn(SpawnKind::Jumprope, hue) => {
+                self.spawn_jumprope_colored(palette_color(hue, self.palette_steps));
+                "jumprope spawned".to_string()
+            }
+            Command::Spawn(SpawnKind::Cluster, hue) => {
+                //dropped from straight above the player onto where they stand
+                let start = Float2(self.position.0, self.arena.bounds.1);
+                match Clusterbomb::from_positions(
+                    start,
+                    self.position,
+                    FlightSpec::Time(CLUSTER_MIN_FLIGHT_T),
+                    palette_color(hue, self.palette_steps),
+                ) {
+                    Ok(bomb) => {
+                        self.clusters
+                            .push(bomb.with_blast(CLUSTER_BLAST_RADIUS, FragmentPattern::random()));
+                        "cluster spawned".to_string()
+                    }
+                    Err(error) => format!("couldn't lob a cluster: {error:?}"),
+                }
+            }
+            Command::Signal(lost) => {
+                self.signal.set_lost(lost);
+                format!("signal lost {:.3}", self.signal.lost())
+            }
+            Command::God(on) => {
+                self.debug.god = on.unwrap_or(!self.debug.god);
+                format!("god {}", if self.debug.god { "on" } else { "off" })
+            }
+            Command::Seed(seed) => {
+                rng::seed(seed);
+                format!("seed {seed}")
+            }
+            Command::Overlay(on) => {
+                self.debug.overlay = on.unwrap_or(!self.debug.overlay);
+                format!("overlay {}", if self.debug.overlay { "on" } else { "off" })
+            }
+            Command::Profiler(on) => {
+                self.debug.profiler = on.unwrap_or(!self.debug.profiler);
+                format!(
+                    "profiler {}",
+                    if self.debug.profiler { "on" } else { "off" }
+                )
+            }
+            Command::Help => HELP.to_string(),
+        }
+    }
+
+    fn deliver(&mut self, goal: Goal) {
+        self.carrying = None;
+        self.signal.apply(SignalEvent::Deliver);
+        //stage scripts set their own speeds
+        if self.stage.is_none() {
+            self.laser_speed *= self.difficulty.speedup;
+            self.jumprope_speed *= self.difficulty.speedup;
+        }
+        if self.deliveries.is_multiple_of(2) {
+            self.current_spawns = (self.current_spawns + 1).min(NUM_PATH_SPAWNS);
+        }
+        self.deliveries += 1;
+        self.score += goal.points;
+        if let Some(boss) = self.boss.as_mut() {
+            boss.hit();
+            if boss.defeated() {
+                self.boss = None;
+                self.gravity_wells.clear();
+            }
+        }
+        let multiplier = self.scoring.multiplier();
+        let earned = self.scoring.deliver(goal.points);
+        if self.verbose {
+            println!("+{earned:.0} (x{multiplier:.1}) {}", self.scoring.total());
+        }
+        //next round, delivery moves and goals are placed clear of what is on screen
+        self.arena.move_delivery();
+        let mut hazards = self.world.positions(HazardKind::Laser);
+        hazards.extend(self.world.positions(HazardKind::Fragment));
+        let goal_count = (1 + self.score / MULTI_GOAL_SCORE).min(MAX_GOALS) as usize;
+        let placer = if self.boss.is_some() {
+            &self.boss_goal_placer
+        } else {
+            &self.goal_placer
+        };
+        self.goals = placer.place(&self.arena, &hazards, goal_count, self.palette_steps);
+    }
+
+    //end of run summary, printed with what took the signal and saved as a separate run file,
+    //there are no replays to attach it to, the sim's seeded run records carry their own breakdown
+    pub fn report(&self) {
+        if let Some(daily) = self.daily.as_ref() {
+            daily.finish(self);
+        }
+        if self.tutorial.is_some() || self.stage.is_some() {
+            println!("{}", self.outcome());
+        }
+        println!("{}", self.scoring.breakdown);
+        if let Err(error) = self.scoring.breakdown.save(Path::new(RUN_FILE)) {
+            println!("couldn't save run to {RUN_FILE}: {error}");
+        }
+        for entry in self.signal.log() {
+            println!("{:>7.2}s {:?} {:+.3}", entry.time, entry.event, entry.delta);
+        }
+    }
+}
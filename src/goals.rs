@@ -0,0 +1,253 @@
+use hsv::hsv_to_rgb;
+
+use crate::arena::Arena;
+use crate::maths::{Float2, Float4, float2_subtract, scale2};
+use crate::rng::random;
+use crate::{color_convert, stepped_hue};
+
+//hazards closer than this to a goal add to what it is worth, the closer the more
+const RISK_RANGE: f32 = 400.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Goal {
+    pub position: Float2,
+    pub t: f64,
+    pub points: u32,
+}
+
+impl Goal {
+    pub fn hue(&self, palette_steps: u32) -> f64 {
+        stepped_hue(self.t, palette_steps)
+    }
+    pub fn color(&self, palette_steps: u32) -> Float4 {
+        color_convert(hsv_to_rgb(self.hue(palette_steps), 1.0, 1.0))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GoalPlacement {
+    //the arena's spawn points, safest first
+    SpawnPoints,
+    //anywhere in the arena at least clearance away from hazards, spawn points when nowhere is clear
+    RandomSafe { clearance: f32 },
+}
+
+#[derive(Debug, Clone)]
+pub struct GoalPlacer {
+    pub placement: GoalPlacement,
+    pub size: Float2,
+    //candidates sampled per goal in random placement
+    pub tries: u32,
+}
+
+impl GoalPlacer {
+    pub fn new(placement: GoalPlacement, size: Float2) -> Self {
+        GoalPlacer {
+            placement,
+            size,
+            tries: 24,
+        }
+    }
+
+    //distinct colors, goals further from the delivery zone and closer to hazards are worth more
+    pub fn place(
+        &self,
+        arena: &Arena,
+        hazards: &[Float2],
+        count: usize,
+        palette_steps: u32,
+    ) -> Vec<Goal> {
+        let mut positions: Vec<Float2> = Vec::new();
+        //one goal per palette color at most
+        for _ in 0..count.min(palette_steps as usize) {
+            let position = match self.placement {
+                GoalPlacement::SpawnPoints => None,
+                GoalPlacement::RandomSafe { clearance } => {
+                    self.random_safe(arena, hazards, &positions, clearance)
+                }
+            }
+            .or_else(|| self.safest_spawn(arena, hazards, &positions));
+            positions.extend(position);
+        }
+
+        let mut hues: Vec<f64> = Vec::new();
+        let mut goals: Vec<Goal> = positions
+            .into_iter()
+            .map(|position| {
+                let mut t = random::<f64>();
+                while hues.contains(&stepped_hue(t, palette_steps)) {
+                    t = random();
+                }
+                hues.push(stepped_hue(t, palette_steps));
+                Goal {
+                    position,
+                    t,
+                    points: 1,
+                }
+            })
+            .collect();
+        let delivery = arena.delivery().center;
+        let worth = |goal: &Goal| {
+            let distance = float2_subtract(goal.position, delivery).magnitude();
+            let risk = (RISK_RANGE - self.clearance(goal.position, hazards, &[])).max(0.0);
+            distance + risk
+        };
+        goals.sort_by(|a, b| worth(a).total_cmp(&worth(b)));
+        for (rank, goal) in goals.iter_mut().enumerate() {
+            goal.points = rank as u32 + 1;
+        }
+        goals
+    }
+
+    //closest hazard or already placed goal, the bigger the safer
+    fn clearance(&self, point: Float2, hazards: &[Float2], taken: &[Float2]) -> f32 {
+        let hazard = hazards
+            .iter()
+            .map(|&hazard| float2_subtract(point, hazard).magnitude())
+            .fold(f32::MAX, f32::min);
+        let goal = taken
+            .iter()
+            .map(|&other| float2_subtract(point, other).magnitude() / 2.0)
+            .fold(f32::MAX, f32::min);
+        hazard.min(goal)
+    }
+
+    fn usable(&self, arena: &Arena, point: Float2, taken: &[Float2]) -> bool {
+        let half = scale2(self.size, 0.5);
+        let overlaps_goal = taken.iter().any(|other| {
+            (point.0 - other.0).abs() < self.size.0 && (point.1 - other.1).abs() < self.size.1
+        });
+        !arena.delivery().contains(point) && !arena.blocked(point, half) && !overlaps_goal
+    }
+
+    fn random_safe(
+        &self,
+        arena: &Arena,
+        hazards: &[Float2],
+        taken: &[Float2],
+        clearance: f32,
+    ) -> Option<Float2> {
+        let reach = float2_subtract(arena.bounds, self.size);
+        (0..self.tries)
+            .map(|_| {
+                Float2(
+                    (random::<f32>() * 2.0 - 1.0) * reach.0,
+                    (random::<f32>() * 2.0 - 1.0) * reach.1,
+                )
+            })
+            .filter(|&point| self.usable(arena, point, taken))
+            .map(|point| (point, self.clearance(point, hazards, taken)))
+            .filter(|&(_, distance)| distance >= clearance)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(point, _)| point)
+    }
+
+    fn safest_spawn(&self, arena: &Arena, hazards: &[Float2], taken: &[Float2]) -> Option<Float2> {
+        arena
+            .goal_spawns
+            .iter()
+            .copied()
+            .filter(|&point| self.usable(arena, point, taken))
+            .max_by(|&a, &b| {
+                self.clearance(a, hazards, taken)
+                    .total_cmp(&self.clearance(b, hazards, taken))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::COLOR_STEPS;
+    use crate::arena::Zone;
+    use crate::game::Difficulty;
+    use crate::rng;
+
+    const SIZE: Float2 = Float2(100.0, 100.0);
+    const BOUNDS: Float2 = Float2(512.0, 384.0);
+
+    fn distance(a: Float2, b: Float2) -> f32 {
+        float2_subtract(a, b).magnitude()
+    }
+
+    //one delivery zone along the bottom edge and the given spawns, no walls
+    fn arena(goal_spawns: Vec<Float2>) -> Arena {
+        let delivery = Zone::new(Float2(0.0, -BOUNDS.1 + 40.0), Float2(BOUNDS.0 * 2.0, 80.0));
+        Arena::new(BOUNDS, vec![delivery], goal_spawns)
+    }
+
+    #[test]
+    fn random_goals_keep_clear_of_hazards_walls_and_the_delivery_zone() {
+        let arena = Arena::standard(Difficulty::NORMAL.world);
+        let hazards = [
+            Float2(-300.0, 200.0),
+            Float2(250.0, -100.0),
+            Float2(0.0, 400.0),
+        ];
+        let placer = GoalPlacer::new(GoalPlacement::RandomSafe { clearance: 200.0 }, SIZE);
+        for seed in 0..20 {
+            rng::seed(seed);
+            let goals = placer.place(&arena, &hazards, 3, COLOR_STEPS);
+            assert_eq!(goals.len(), 3);
+            for goal in goals.iter() {
+                for &hazard in hazards.iter() {
+                    assert!(
+                        distance(goal.position, hazard) >= 200.0,
+                        "seed {seed} {goal:?}"
+                    );
+                }
+                assert!(!arena.blocked(goal.position, scale2(SIZE, 0.5)));
+                assert!(!arena.delivery().contains(goal.position));
+                assert!(goal.position.0.abs() <= arena.bounds.0 - SIZE.0);
+                assert!(goal.position.1.abs() <= arena.bounds.1 - SIZE.1);
+            }
+        }
+    }
+
+    #[test]
+    fn spawn_points_are_taken_safest_first() {
+        let spawns = vec![Float2(-300.0, 200.0), Float2(300.0, 200.0)];
+        let placer = GoalPlacer::new(GoalPlacement::SpawnPoints, SIZE);
+        rng::seed(1);
+        let goals = placer.place(&arena(spawns), &[Float2(-250.0, 150.0)], 1, COLOR_STEPS);
+        assert_eq!(goals.len(), 1);
+        assert_eq!(goals[0].position, Float2(300.0, 200.0));
+        //nowhere clear enough falls back on the spawn points
+        let nowhere = GoalPlacer::new(GoalPlacement::RandomSafe { clearance: 1e6 }, SIZE);
+        let hazards = [Float2(0.0, 300.0)];
+        let goals = nowhere.place(&arena(vec![Float2(0.0, 0.0)]), &hazards, 1, COLOR_STEPS);
+        assert_eq!(goals[0].position, Float2(0.0, 0.0));
+    }
+
+    #[test]
+    fn goals_have_distinct_hues_and_never_outnumber_the_palette() {
+        let arena = Arena::standard(Difficulty::NORMAL.world);
+        let placer = GoalPlacer::new(GoalPlacement::RandomSafe { clearance: 0.0 }, SIZE);
+        for (seed, steps) in [(2, COLOR_STEPS), (3, 5), (4, 3)] {
+            rng::seed(seed);
+            let goals = placer.place(&arena, &[], 10, steps);
+            assert_eq!(goals.len(), steps as usize);
+            let mut hues: Vec<f64> = goals.iter().map(|goal| goal.hue(steps)).collect();
+            hues.sort_by(f64::total_cmp);
+            hues.dedup();
+            assert_eq!(hues.len(), goals.len());
+        }
+    }
+
+    #[test]
+    fn points_rank_goals_by_distance_and_risk() {
+        let near = Float2(-300.0, -200.0);
+        let far = Float2(300.0, 150.0);
+        let placer = GoalPlacer::new(GoalPlacement::SpawnPoints, SIZE);
+        let points = |hazards: &[Float2]| {
+            rng::seed(5);
+            let goals = placer.place(&arena(vec![near, far]), hazards, 2, COLOR_STEPS);
+            let at = |position| goals.iter().find(|goal| goal.position == position).unwrap();
+            (at(near).points, at(far).points)
+        };
+        //further from delivery is worth more
+        assert_eq!(points(&[]), (1, 2));
+        //a hazard right by the nearer goal makes it the one worth more
+        assert_eq!(points(&[Float2(-270.0, -200.0)]), (2, 1));
+    }
+}
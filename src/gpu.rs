@@ -0,0 +1,284 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use winit::event_loop::OwnedDisplayHandle;
+use winit::window::Window;
+
+use crate::game::Scene;
+use crate::portable::Renderer;
+use crate::profiler::{Phase, Profiler};
+use crate::scaling::ScaledView;
+use crate::software::Shading;
+use crate::vertex_t;
+
+//the Uniforms struct in shaders.wgsl, shared with the metal frontend
+#[repr(C)]
+pub struct Uniforms {
+    view_projection: [[f32; 4]; 4],
+    player: [f32; 2],
+    radius: f32,
+    signal_lost: f32,
+}
+
+impl Uniforms {
+    pub fn new(scene: &Scene, view: ScaledView) -> Self {
+        let shading = Shading::new(scene, view);
+        Uniforms {
+            view_projection: scene.view_projection,
+            player: [shading.player.0, shading.player.1],
+            radius: shading.radius,
+            signal_lost: shading.signal_lost,
+        }
+    }
+}
+
+//raw bytes of plain repr(C) data for buffer uploads
+fn bytes<T>(data: &[T]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, size_of_val(data)) }
+}
+
+pub struct GpuRenderer {
+    surface: wgpu::Surface<'static>,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    box_pipeline: wgpu::RenderPipeline,
+    telegraph_pipeline: wgpu::RenderPipeline,
+    goal_pipeline: wgpu::RenderPipeline,
+    overlay_pipeline: wgpu::RenderPipeline,
+    uniform_buf: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertex_buf: wgpu::Buffer,
+}
+
+impl GpuRenderer {
+    pub fn new(window: Arc<Window>, display: OwnedDisplayHandle) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(
+            wgpu::InstanceDescriptor::new_without_display_handle()
+                .with_display_handle(Box::new(display)),
+        );
+        let surface = instance
+            .create_surface(window.clone())
+            .map_err(|error| error.to_string())?;
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            compatible_surface: Some(&surface),
+            ..Default::default()
+        }))
+        .map_err(|error| error.to_string())?;
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default()))
+                .map_err(|error| error.to_string())?;
+
+        let size = window.inner_size();
+        let mut config = surface
+            .get_default_config(&adapter, size.width.max(1), size.height.max(1))
+            .ok_or("surface not supported by the adapter")?;
+        //blending happens on the stored values like the metal RGBA8Unorm target
+        let capabilities = surface.get_capabilities(&adapter);
+        if let Some(&format) = capabilities.formats.iter().find(|format| !format.is_srgb()) {
+            config.format = format;
+        }
+        surface.configure(&device, &config);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("shaders"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders.wgsl").into()),
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("uniforms"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pipeline layout"),
+            bind_group_layouts: &[Some(&bind_group_layout)],
+            immediate_size: 0,
+        });
+        let attributes = wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4];
+        let pipeline = |fragment: &str| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(fragment),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("box_vertex"),
+                    compilation_options: Default::default(),
+                    buffers: &[Some(wgpu::VertexBufferLayout {
+                        array_stride: size_of::<vertex_t>() as u64,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &attributes,
+                    })],
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(fragment),
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: config.format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview_mask: None,
+                cache: None,
+            })
+        };
+        let box_pipeline = pipeline("box_fragment");
+        let telegraph_pipeline = pipeline("telegraph_fragment");
+        let goal_pipeline = pipeline("scorezone_fragment");
+        let overlay_pipeline = pipeline("overlay_fragment");
+
+        let uniform_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("uniforms"),
+            size: size_of::<Uniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("uniforms"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buf.as_entire_binding(),
+            }],
+        });
+        let vertex_buf = Self::vertex_buffer(&device, 4 * 1024);
+
+        Ok(GpuRenderer {
+            surface,
+            device,
+            queue,
+            config,
+            box_pipeline,
+            telegraph_pipeline,
+            goal_pipeline,
+            overlay_pipeline,
+            uniform_buf,
+            bind_group,
+            vertex_buf,
+        })
+    }
+
+    fn vertex_buffer(device: &wgpu::Device, vertices: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("vertices"),
+            size: (size_of::<vertex_t>() * vertices) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+}
+
+impl Renderer for GpuRenderer {
+    fn resize(&mut self, width: u32, height: u32) {
+        self.config.width = width.max(1);
+        self.config.height = height.max(1);
+        self.surface.configure(&self.device, &self.config);
+    }
+
+    fn render(&mut self, scene: &Scene, view: ScaledView, profiler: &mut Profiler) {
+        let frame = match self.surface.get_current_texture() {
+            wgpu::CurrentSurfaceTexture::Success(frame)
+            | wgpu::CurrentSurfaceTexture::Suboptimal(frame) => frame,
+            wgpu::CurrentSurfaceTexture::Outdated | wgpu::CurrentSurfaceTexture::Lost => {
+                self.surface.configure(&self.device, &self.config);
+                return;
+            }
+            _ => return,
+        };
+        let target = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let span = profiler.start();
+        let uniforms = Uniforms::new(scene, view);
+        self.queue
+            .write_buffer(&self.uniform_buf, 0, bytes(std::slice::from_ref(&uniforms)));
+
+        //every quad in one buffer, drawn back to front with the pipeline each range needs
+        let mut vertices: Vec<vertex_t> = Vec::new();
+        let mut ranges: Vec<(&wgpu::RenderPipeline, Range<u32>)> = Vec::new();
+        let layers = [(&self.telegraph_pipeline, &scene.telegraphs[..])]
+            .into_iter()
+            .chain(scene.boxes().map(|verts| (&self.box_pipeline, verts)))
+            .chain([
+                (&self.goal_pipeline, &scene.goals[..]),
+                (&self.overlay_pipeline, &scene.overlay[..]),
+            ]);
+        for (pipeline, verts) in layers {
+            let start = vertices.len() as u32;
+            vertices.extend_from_slice(verts);
+            ranges.push((pipeline, start..vertices.len() as u32));
+        }
+        if size_of_val(&vertices[..]) as u64 > self.vertex_buf.size() {
+            self.vertex_buf = Self::vertex_buffer(&self.device, vertices.len().next_power_of_two());
+        }
+        self.queue
+            .write_buffer(&self.vertex_buf, 0, bytes(&vertices));
+        profiler.record(Phase::Upload, span);
+        let span = profiler.start();
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("scene"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                multiview_mask: None,
+            });
+            //a resize between the tick and this redraw can leave the viewport past the target
+            let (width, height) = (self.config.width as f32, self.config.height as f32);
+            let (x, y) = (
+                view.viewport.x.min(width - 1.0),
+                view.viewport.y.min(height - 1.0),
+            );
+            pass.set_viewport(
+                x,
+                y,
+                view.viewport.width.min(width - x),
+                view.viewport.height.min(height - y),
+                0.0,
+                1.0,
+            );
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.set_vertex_buffer(0, self.vertex_buf.slice(..));
+            for (pipeline, range) in ranges {
+                pass.set_pipeline(pipeline);
+                for start in range.step_by(4) {
+                    pass.draw(start..start + 4, 0..1);
+                }
+            }
+        }
+        self.queue.submit([encoder.finish()]);
+        self.queue.present(frame);
+        profiler.record(Phase::Encode, span);
+    }
+}
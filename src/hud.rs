@@ -0,0 +1,182 @@
+use crate::maths::{Float2, Float4, float2_subtract};
+use crate::{build_rect, vertex_t};
+
+//5x7 glyphs, one row per byte with the leftmost pixel in bit 4, lowercase draws as uppercase
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+#[rustfmt::skip]
+const GLYPHS: [(char, [u8; GLYPH_HEIGHT]); 59] = [
+    (' ', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    (';', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+    ('=', [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000]),
+    ('_', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111]),
+    ('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('[', [0b01110, 0b01000, 0b01000, 0b01000, 0b01000, 0b01000, 0b01110]),
+    (']', [0b01110, 0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0b01110]),
+    ('<', [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010]),
+    ('>', [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+    ('\'', [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('%', [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011]),
+    ('#', [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010]),
+    ('*', [0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000]),
+    ('|', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+];
+
+//characters without a glyph draw as this
+const MISSING: char = '?';
+
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    let c = c.to_ascii_uppercase();
+    let find = |c: char| GLYPHS.iter().find(|(glyph, _)| *glyph == c);
+    find(c).or_else(|| find(MISSING)).unwrap().1
+}
+
+//advance per character and per line, in font pixels
+const ADVANCE: f32 = (GLYPH_WIDTH + 1) as f32;
+pub const LINE_HEIGHT: f32 = (GLYPH_HEIGHT + 3) as f32;
+
+pub fn text_width(text: &str, pixel: f32) -> f32 {
+    text.chars().count() as f32 * ADVANCE * pixel
+}
+
+//top left anchored, y up like the rest of the game, runs of lit pixels share one quad
+pub fn text(out: &mut Vec<vertex_t>, text: &str, top_left: Float2, pixel: f32, color: Float4) {
+    for (column, c) in text.chars().enumerate() {
+        let left = top_left.0 + column as f32 * ADVANCE * pixel;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            let y = top_left.1 - (row as f32 + 0.5) * pixel;
+            let mut x = 0;
+            while x < GLYPH_WIDTH {
+                let lit = |x: usize| bits & (1 << (GLYPH_WIDTH - 1 - x)) != 0;
+                if !lit(x) {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < GLYPH_WIDTH && lit(x) {
+                    x += 1;
+                }
+                let run = (x - start) as f32;
+                out.extend(build_rect(
+                    left + (start as f32 + run / 2.0) * pixel,
+                    y,
+                    run * pixel,
+                    pixel,
+                    0.0,
+                    color,
+                ));
+            }
+        }
+    }
+}
+
+//lines of text over a backing panel two font pixels larger on every side
+pub fn text_block(
+    out: &mut Vec<vertex_t>,
+    lines: &[String],
+    top_left: Float2,
+    pixel: f32,
+    color: Float4,
+    background: Float4,
+) {
+    let padding = pixel * 2.0;
+    let width = lines
+        .iter()
+        .map(|line| text_width(line, pixel))
+        .fold(0.0, f32::max);
+    let height = lines.len() as f32 * LINE_HEIGHT * pixel;
+    panel(
+        out,
+        top_left,
+        Float2(width + padding * 2.0, height + padding * 2.0),
+        background,
+    );
+    for (index, line) in lines.iter().enumerate() {
+        text(
+            out,
+            line,
+            Float2(
+                top_left.0 + padding,
+                top_left.1 - padding - index as f32 * LINE_HEIGHT * pixel,
+            ),
+            pixel,
+            color,
+        );
+    }
+}
+
+pub fn panel(out: &mut Vec<vertex_t>, top_left: Float2, size: Float2, color: Float4) {
+    out.extend(build_rect(
+        top_left.0 + size.0 / 2.0,
+        top_left.1 - size.1 / 2.0,
+        size.0,
+        size.1,
+        0.0,
+        color,
+    ));
+}
+
+pub fn line(out: &mut Vec<vertex_t>, from: Float2, to: Float2, thickness: f32, color: Float4) {
+    let delta = float2_subtract(to, from);
+    out.extend(build_rect(
+        (from.0 + to.0) / 2.0,
+        (from.1 + to.1) / 2.0,
+        delta.magnitude(),
+        thickness,
+        delta.1.atan2(delta.0),
+        color,
+    ));
+}
+
+//edges of a build_rect quad, corners come bottom left, bottom right, top left, top right
+pub fn outline(out: &mut Vec<vertex_t>, rect: &[vertex_t], thickness: f32, color: Float4) {
+    let corner = |index: usize| Float2(rect[index].position.0, rect[index].position.1);
+    for (from, to) in [(0, 1), (1, 3), (3, 2), (2, 0)] {
+        line(out, corner(from), corner(to), thickness, color);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::maths::Float2;
+
+//what the game responds to, each frontend maps its own key codes onto these
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    //debug key that bleeds signal while held
+    Drain,
+    PlaceWall,
+    PlaceMirror,
+    ToggleFullscreen,
+    CycleScalePolicy,
+    ToggleConsole,
+    ToggleOverlay,
+    ToggleProfiler,
+}
+
+//held actions plus the hue picked with the mouse, the same for a human, a bot or a test
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    held: Vec<Action>,
+    previous: Vec<Action>,
+    //0 to 1 across the view, stepped into a palette hue by the game
+    pub hue: f64,
+    //text typed this frame for the console, enter arrives as '\n' and backspace as '\u{8}'
+    typed: String,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Input::default()
+    }
+
+    pub fn press(&mut self, action: Action) {
+        if !self.held.contains(&action) {
+            self.held.push(action);
+        }
+    }
+    pub fn release(&mut self, action: Action) {
+        self.held.retain(|&held| held != action);
+    }
+    pub fn type_char(&mut self, c: char) {
+        self.typed.push(c);
+    }
+    pub fn set_hue(&mut self, hue: f64) {
+        self.hue = hue.clamp(0.0, 1.0);
+    }
+
+    pub fn held(&self, action: Action) -> bool {
+        self.held.contains(&action)
+    }
+    pub fn typed(&self) -> &str {
+        &self.typed
+    }
+    //down now but not at the end of the last frame
+    pub fn pressed(&self, action: Action) -> bool {
+        self.held(action) && !self.previous.contains(&action)
+    }
+
+    //unit steps per axis, y up
+    pub fn movement(&self) -> Float2 {
+        let axis = |negative, positive| self.held(positive) as i32 - self.held(negative) as i32;
+        Float2(
+            axis(Action::Left, Action::Right) as f32,
+            axis(Action::Down, Action::Up) as f32,
+        )
+    }
+
+    //called once per simulated frame so presses fire exactly once
+    pub fn end_frame(&mut self) {
+        self.previous = self.held.clone();
+        self.typed.clear();
+    }
+}
@@ -0,0 +1,362 @@
+use std::f32::consts::PI;
+
+use hsv::hsv_to_rgb;
+
+use crate::maths::{Float2, Float4, dot, float2_add, float2_subtract, scale2};
+use crate::rng::random;
+use crate::walls::reflect;
+use crate::{color_convert, stepped_hue};
+
+//lane geometry shared by every pattern, lanes split the full view height evenly
+pub fn lane_center(lane: usize, lanes: usize, view_height: f32) -> f32 {
+    let lane_height = (2.0 * view_height) / lanes as f32;
+    lane_height * lane as f32 + lane_height / 2.0 - view_height
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Wave {
+    pub amplitude: f32,
+    pub wavelength: f32,
+}
+
+//path component for laser entities, the movement system copies the evaluated position out
+#[derive(Debug, Clone)]
+pub struct Laser {
+    position: Float2,
+    origin: Float2,
+    direction: Float2,
+    speed: f32,
+    speed_ramp: f32,
+    wave: Option<Wave>,
+    travelled: f32,
+}
+
+impl Laser {
+    pub fn new(origin: Float2, direction: Float2, speed: f32) -> Self {
+        Laser {
+            position: origin,
+            origin,
+            direction: direction.normalized(),
+            speed,
+            speed_ramp: 0.0,
+            wave: None,
+            travelled: 0.0,
+        }
+    }
+    pub fn with_wave(mut self, wave: Wave) -> Self {
+        self.wave = Some(wave);
+        self
+    }
+    //fractional speed gained per second of flight
+    pub fn with_ramp(mut self, speed_ramp: f32) -> Self {
+        self.speed_ramp = speed_ramp;
+        self
+    }
+    pub fn position(&self) -> Float2 {
+        self.position
+    }
+    fn normal(&self) -> Float2 {
+        Float2(-self.direction.1, self.direction.0)
+    }
+    pub fn update(&mut self, delta_t: f32) -> Float2 {
+        self.speed *= 1.0 + self.speed_ramp * delta_t;
+        self.travelled += self.speed * delta_t;
+        let mut position = float2_add(self.origin, scale2(self.direction, self.travelled));
+        if let Some(wave) = self.wave {
+            let phase = 2.0 * PI * self.travelled / wave.wavelength;
+            position = float2_add(
+                position,
+                scale2(self.normal(), wave.amplitude * phase.sin()),
+            );
+        }
+        self.position = position;
+        position
+    }
+    pub fn velocity(&self) -> Float2 {
+        let mut heading = self.direction;
+        if let Some(wave) = self.wave {
+            //derivative of the wave offset along the path
+            let phase = 2.0 * PI * self.travelled / wave.wavelength;
+            let slope = wave.amplitude * 2.0 * PI / wave.wavelength * phase.cos();
+            heading = float2_add(heading, scale2(self.normal(), slope));
+        }
+        scale2(heading.normalized(), self.speed)
+    }
+    pub fn rotation(&self) -> f32 {
+        let velocity = self.velocity();
+        velocity.1.atan2(velocity.0)
+    }
+    //restarts the path from the current position, only when heading into the surface so a
+    //laser still overlapping a mirror on the next frame isn't flipped straight back
+    pub fn reflect(&mut self, normal: Float2) {
+        if dot(self.velocity(), normal) >= 0.0 {
+            return;
+        }
+        self.direction = reflect(self.direction, normal).normalized();
+        self.origin = self.position;
+        self.travelled = 0.0;
+    }
+    //bends the path along an acceleration, a bent laser drops its wave and flies straight
+    pub fn steer(&mut self, accel: Float2, delta_t: f32) {
+        if accel.0 == 0.0 && accel.1 == 0.0 {
+            return;
+        }
+        self.direction = float2_add(self.velocity(), scale2(accel, delta_t)).normalized();
+        self.origin = self.position;
+        self.travelled = 0.0;
+        self.wave = None;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LaserPattern {
+    //the original horizontal lanes, one laser per lane per volley
+    Lanes,
+    SineLanes(Wave),
+    //parallel lasers entering from the right at an angle, in radians from straight left
+    DiagonalSweep { angle: f32 },
+    //lasers spread along the right edge, all aimed at the same point
+    ConvergingFan { count: u32, spread: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LaserSpawner {
+    pub pattern: LaserPattern,
+    pub interval: f32,
+    pub speed_ramp: f32,
+    pub unlock_score: u32,
+    timer: f32,
+}
+
+impl LaserSpawner {
+    pub fn new(pattern: LaserPattern, interval: f32, unlock_score: u32) -> Self {
+        LaserSpawner {
+            pattern,
+            interval,
+            speed_ramp: 0.0,
+            unlock_score,
+            timer: 0.0,
+        }
+    }
+    pub fn with_ramp(mut self, speed_ramp: f32) -> Self {
+        self.speed_ramp = speed_ramp;
+        self
+    }
+    pub fn update(
+        &mut self,
+        delta_t: f32,
+        lanes: usize,
+        bounds: Float2,
+        speed: f32,
+        target: Float2,
+        palette_steps: u32,
+    ) -> Vec<(Laser, Float4)> {
+        self.timer += delta_t;
+        if self.timer < self.interval {
+            return Vec::new();
+        }
+        self.timer -= self.interval;
+        self.volley(lanes, bounds, speed, target, palette_steps)
+    }
+
+    pub fn volley(
+        &self,
+        lanes: usize,
+        bounds: Float2,
+        speed: f32,
+        target: Float2,
+        palette_steps: u32,
+    ) -> Vec<(Laser, Float4)> {
+        let lane_height = (2.0 * bounds.1) / lanes as f32;
+        //random spawn depth past the right edge staggers lasers that share a volley
+        let spawn_x = || bounds.0 + (random::<f32>() * 22.5).floor() * 10.0;
+        let left = Float2(-1.0, 0.0);
+
+        let lasers: Vec<(Laser, Float4)> = match self.pattern {
+            LaserPattern::Lanes => (0..lanes)
+                .map(|lane| {
+                    let y =
+                        lane_center(lane, lanes, bounds.1) + (random::<f32>() - 0.5) * lane_height;
+                    (
+                        Laser::new(Float2(spawn_x(), y), left, speed),
+                        random_laser_color(palette_steps),
+                    )
+                })
+                .collect(),
+            LaserPattern::SineLanes(wave) => (0..lanes)
+                .map(|lane| {
+                    let y = lane_center(lane, lanes, bounds.1);
+                    let laser = Laser::new(Float2(spawn_x(), y), left, speed).with_wave(wave);
+                    (laser, random_laser_color(palette_steps))
+                })
+                .collect(),
+            LaserPattern::DiagonalSweep { angle } => {
+                let direction = Float2(-angle.cos(), -angle.sin());
+                //shift origins up the slope so each laser crosses the middle of the screen in its lane
+                let rise = bounds.0 * angle.tan();
+                (0..lanes)
+                    .map(|lane| {
+                        let y = lane_center(lane, lanes, bounds.1) + rise;
+                        let laser = Laser::new(Float2(spawn_x(), y), direction, speed);
+                        (laser, random_laser_color(palette_steps))
+                    })
+                    .collect()
+            }
+            LaserPattern::ConvergingFan { count, spread } => {
+                let center = (random::<f32>() * 2.0 - 1.0) * (bounds.1 - spread / 2.0).max(0.0);
+                let fan_color = random_laser_color(palette_steps);
+                (0..count)
+                    .map(|i| {
+                        let y = center - spread / 2.0
+                            + spread * i as f32 / count.saturating_sub(1).max(1) as f32;
+                        let origin = Float2(bounds.0, y);
+                        let laser = Laser::new(origin, float2_subtract(target, origin), speed);
+                        (laser, fan_color)
+                    })
+                    .collect()
+            }
+        };
+        lasers
+            .into_iter()
+            .map(|(laser, color)| (laser.with_ramp(self.speed_ramp), color))
+            .collect()
+    }
+}
+
+fn random_laser_color(palette_steps: u32) -> Float4 {
+    color_convert(hsv_to_rgb(
+        stepped_hue(random::<f64>(), palette_steps),
+        1.0,
+        1.0,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{COLOR_STEPS, build_rect, obb_intersect};
+
+    const BOUNDS: Float2 = Float2(512.0, 384.0);
+    const TARGET: Float2 = Float2(-100.0, 50.0);
+
+    fn close(a: Float2, b: Float2) -> bool {
+        float2_subtract(a, b).magnitude() < 1e-3
+    }
+
+    fn volley(pattern: LaserPattern, lanes: usize) -> Vec<(Laser, Float4)> {
+        LaserSpawner::new(pattern, 1.0, 0).volley(lanes, BOUNDS, 100.0, TARGET, COLOR_STEPS)
+    }
+
+    #[test]
+    fn straight_lasers_fly_along_their_direction() {
+        let mut laser = Laser::new(Float2(0.0, 0.0), Float2(-2.0, 0.0), 100.0);
+        assert!(close(laser.update(0.5), Float2(-50.0, 0.0)));
+        assert!(close(laser.velocity(), Float2(-100.0, 0.0)));
+        assert_eq!(laser.rotation(), PI);
+    }
+
+    #[test]
+    fn ramped_lasers_speed_up_over_their_flight() {
+        let mut laser = Laser::new(Float2(0.0, 0.0), Float2(-1.0, 0.0), 100.0).with_ramp(0.5);
+        laser.update(1.0);
+        assert!((laser.velocity().magnitude() - 150.0).abs() < 1e-3);
+        assert!(close(laser.position(), Float2(-150.0, 0.0)));
+        laser.update(1.0);
+        assert!((laser.velocity().magnitude() - 225.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sine_lasers_weave_across_their_path() {
+        let wave = Wave {
+            amplitude: 50.0,
+            wavelength: 400.0,
+        };
+        let mut laser = Laser::new(Float2(0.0, 0.0), Float2(-1.0, 0.0), 100.0).with_wave(wave);
+        //climbing out of the start, then flat at the crest a quarter wavelength in
+        assert!(laser.velocity().1 < 0.0);
+        assert!((laser.velocity().magnitude() - 100.0).abs() < 1e-3);
+        assert!(close(laser.update(1.0), Float2(-100.0, -50.0)));
+        assert!(close(laser.velocity(), Float2(-100.0, 0.0)));
+        assert!(close(laser.update(1.0), Float2(-200.0, 0.0)));
+    }
+
+    #[test]
+    fn lane_patterns_put_a_laser_in_every_lane() {
+        let wave = Wave {
+            amplitude: 60.0,
+            wavelength: 500.0,
+        };
+        let lanes = 4;
+        let lane_height = 2.0 * BOUNDS.1 / lanes as f32;
+        for pattern in [LaserPattern::Lanes, LaserPattern::SineLanes(wave)] {
+            let lasers = volley(pattern, lanes);
+            assert_eq!(lasers.len(), lanes);
+            for (lane, (laser, _)) in lasers.iter().enumerate() {
+                let offset = laser.position().1 - lane_center(lane, lanes, BOUNDS.1);
+                assert!(offset.abs() <= lane_height / 2.0);
+                assert!(laser.position().0 >= BOUNDS.0);
+                assert!(laser.direction.0 == -1.0 && laser.direction.1 == 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn diagonal_sweeps_are_parallel_and_start_up_the_slope() {
+        let angle = PI / 8.0;
+        let lasers = volley(LaserPattern::DiagonalSweep { angle }, 3);
+        assert_eq!(lasers.len(), 3);
+        for (lane, (laser, _)) in lasers.iter().enumerate() {
+            assert!(close(laser.direction, Float2(-angle.cos(), -angle.sin())));
+            //raised by the slope across half the screen, so the middle of the screen is in its lane
+            let rise = BOUNDS.0 * angle.tan();
+            assert!((laser.position().1 - lane_center(lane, 3, BOUNDS.1) - rise).abs() < 1e-3);
+            assert!(laser.position().0 >= BOUNDS.0);
+        }
+    }
+
+    #[test]
+    fn converging_fans_share_a_color_and_aim_at_the_target() {
+        let spread = 300.0;
+        let lasers = volley(LaserPattern::ConvergingFan { count: 5, spread }, 4);
+        assert_eq!(lasers.len(), 5);
+        let (lowest, highest) = (lasers[0].0.position().1, lasers[4].0.position().1);
+        assert!((highest - lowest - spread).abs() < 1e-3);
+        for (laser, color) in lasers.iter() {
+            assert_eq!(*color, lasers[0].1);
+            let aim = float2_subtract(TARGET, laser.position()).normalized();
+            assert!(close(laser.direction, aim));
+        }
+        let single = volley(LaserPattern::ConvergingFan { count: 1, spread }, 4);
+        assert_eq!(single.len(), 1);
+        assert!(volley(LaserPattern::ConvergingFan { count: 0, spread }, 4).is_empty());
+    }
+
+    #[test]
+    fn spawners_fire_every_interval_with_their_ramp() {
+        let mut spawner = LaserSpawner::new(LaserPattern::Lanes, 1.0, 0).with_ramp(0.6);
+        let mut fire = |delta_t| spawner.update(delta_t, 2, BOUNDS, 100.0, TARGET, COLOR_STEPS);
+        assert!(fire(0.6).is_empty());
+        let lasers = fire(0.6);
+        assert_eq!(lasers.len(), 2);
+        assert!(lasers.iter().all(|(laser, _)| laser.speed_ramp == 0.6));
+        assert!(fire(0.6).is_empty());
+    }
+
+    #[test]
+    fn oriented_boxes_only_collide_where_they_overlap() {
+        let color = Float4(1.0, 1.0, 1.0, 1.0);
+        let square = |x, y| build_rect(x, y, 20.0, 20.0, 0.0, color);
+        let beam = build_rect(0.0, 0.0, 200.0, 10.0, PI / 4.0, color);
+        //both squares sit inside the beam's bounding box, only the one on its diagonal touches it
+        let hits: Vec<bool> = [square(60.0, 60.0), square(60.0, -60.0)]
+            .iter()
+            .map(|other| obb_intersect(&beam, other))
+            .collect();
+        assert_eq!(hits.iter().filter(|&&hit| hit).count(), 1, "{hits:?}");
+        assert!(obb_intersect(&beam, &square(0.0, 0.0)));
+        assert!(!obb_intersect(&beam, &square(200.0, 0.0)));
+        //edges that only touch don't count
+        assert!(!obb_intersect(&square(0.0, 0.0), &square(20.0, 0.0)));
+        assert!(obb_intersect(&square(0.0, 0.0), &square(19.0, 5.0)));
+    }
+}
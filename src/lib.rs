@@ -0,0 +1,252 @@
+use std::f32::consts::PI;
+
+use maths::{Float2, Float4, apply_rotation_float2, dot, float2_add, float2_subtract, scale2};
+use rng::random;
+
+#[cfg(target_os = "macos")]
+pub mod appkit;
+mod arena;
+mod boss;
+pub mod bot;
+mod camera;
+mod clusterbomb;
+pub mod daily;
+mod debug;
+mod entities;
+pub mod env;
+mod feedback;
+pub mod game;
+mod goals;
+mod gpu;
+mod hud;
+mod input;
+mod lasers;
+mod maths;
+pub mod portable;
+pub mod profiler;
+mod rng;
+mod scaling;
+mod scoring;
+mod signal;
+pub mod sim;
+mod software;
+pub mod stage;
+mod tuning;
+mod tutorial;
+#[cfg(target_os = "macos")]
+mod utils;
+mod walls;
+
+fn color_convert(int_color: (u8, u8, u8)) -> Float4 {
+    Float4(
+        int_color.0 as f32 / 255.0,
+        int_color.1 as f32 / 255.0,
+        int_color.2 as f32 / 255.0,
+        1.0,
+    )
+}
+
+//  jumprope vertical
+//      full screen colored band
+//      maybe shifting color slightly because telegraphed
+//      would be cool if particles at end to make it more substantial
+//  laser horizontal
+//      speed tuning is the most important thing
+//      single colored, fx unclear maybe ghosting trail
+//  clusterbomb thingy maybe
+//      lob out then explode, circle on ground is important
+//          to do parabolic path, calculate offset from axis aligned parabola equation then apply rotation
+//  chasers
+//      following player with simple predictable pattern
+//      maybe should choose direction + lunge
+//      touching one with same color clears it, maybe fade out
+//
+//  want to decide on whether same color is safe or different color is safe
+//  I think same color being safe makes sense, then also as you lose signal you have to just dodge
+//  unclear what that means for the jumpropes, maybe color band is pretty lenient
+//
+// check out oklab color gradient
+//
+//  stretch ideas
+//      jumpropes represent game music and are the waveform with strong aura/glow
+//      music augmentation based on the player life
+//      interesting environmental barriers like maybe mirrors or colored walls
+//          player should be able to create / interact with these walls somehow
+//          being static parts of the environment would be kind of dull
+//  boss ideas
+//      cool 3d visuals using z axis for stuff
+//      gravity bending projectiles and causing simultaneous redshift
+//
+//
+
+//the full palette, the daily challenge can play on fewer steps
+const COLOR_STEPS: u32 = 7;
+
+//palette_steps is the palette the game plays on, see Game::palette_steps
+fn stepped_hue(t: f64, palette_steps: u32) -> f64 {
+    let hue_step = 360 / palette_steps;
+    let hue = t * 360.0 - 20.0;
+    let int_hue = hue as u32 / hue_step;
+    (int_hue * hue_step) as f64
+}
+
+//input t in the middle of a palette step, picking that step's hue back out of stepped_hue
+//steps past a narrowed palette wrap round
+fn palette_t(index: u32, palette_steps: u32) -> f64 {
+    let hue_step = (360 / palette_steps) as f64;
+    ((index % palette_steps) as f64 * hue_step + 20.0 + hue_step / 2.0) / 360.0
+}
+
+fn rect_intersect(rect1: &[vertex_t], rect2: &[vertex_t]) -> bool {
+    rect1[0].position.0 < rect2[1].position.0
+        && rect2[0].position.0 < rect1[1].position.0
+        && rect1[0].position.1 < rect2[2].position.1
+        && rect2[0].position.1 < rect1[2].position.1
+}
+
+//separating axis test, needed once rects are built with a rotation
+fn obb_intersect(rect1: &[vertex_t], rect2: &[vertex_t]) -> bool {
+    let corners = |rect: &[vertex_t]| -> Vec<Float2> {
+        rect.iter()
+            .map(|vert| Float2(vert.position.0, vert.position.1))
+            .collect()
+    };
+    let (corners1, corners2) = (corners(rect1), corners(rect2));
+    //rect edges are perpendicular, so the two edges from the first corner cover every normal
+    let axes = [
+        float2_subtract(corners1[1], corners1[0]),
+        float2_subtract(corners1[2], corners1[0]),
+        float2_subtract(corners2[1], corners2[0]),
+        float2_subtract(corners2[2], corners2[0]),
+    ];
+    axes.iter().all(|&axis| {
+        let project = |points: &[Float2]| {
+            points
+                .iter()
+                .map(|&point| dot(point, axis))
+                .fold((f32::MAX, f32::MIN), |(lo, hi), d| (lo.min(d), hi.max(d)))
+        };
+        let (min1, max1) = project(&corners1);
+        let (min2, max2) = project(&corners2);
+        min1 < max2 && min2 < max1
+    })
+}
+
+fn circle_rect_intersect(center: Float2, radius: f32, rect: &[vertex_t]) -> bool {
+    let closest = Float2(
+        center.0.clamp(rect[0].position.0, rect[1].position.0),
+        center.1.clamp(rect[0].position.1, rect[2].position.1),
+    );
+    float2_subtract(center, closest).magnitude() < radius
+}
+
+struct Particle {
+    position: Float2,
+    velocity: Float2,
+    acceleration: Float2,
+    color: Float4,
+    lifetime: f32,
+    rotation: f32,
+}
+
+impl Particle {
+    fn spawn(
+        location: Float2,
+        max_velocity: f32,
+        max_accel: f32,
+        velocity_bias: Float2,
+        color: Float4,
+    ) -> Self {
+        let v_theta = random::<f32>() * 2.0 * PI;
+        let a_theta = random::<f32>() * 2.0 * PI;
+        Particle {
+            position: Float2(
+                location.0 * (1.0 + random::<f32>() * 0.01 - 0.005),
+                location.1 * (1.0 + random::<f32>() * 0.01 - 0.005),
+            ),
+            velocity: float2_add(
+                Float2(v_theta.cos() * max_velocity, v_theta.sin() * max_velocity),
+                velocity_bias,
+            ),
+            acceleration: Float2(a_theta.cos() * max_accel, a_theta.sin() * max_accel),
+            color,
+            lifetime: 1.0,
+            rotation: 0.0,
+        }
+    }
+    fn update(&mut self) {
+        self.lifetime -= random::<f32>() * 0.1;
+        // self.acceleration = scale2(self.acceleration, self.lifetime);
+        self.velocity = float2_add(self.acceleration, self.velocity);
+        self.velocity = scale2(self.velocity, self.lifetime);
+        self.position = float2_add(self.velocity, self.position);
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct vertex_t {
+    position: Float4,
+    color: Float4,
+}
+
+pub fn build_rect(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    rot: f32,
+    color: Float4,
+) -> Vec<vertex_t> {
+    let mut verts = Vec::new();
+
+    let origin = Float2(x - width / 2.0, y - height / 2.0);
+    //rotate about the center so rotated rects stay where they were placed
+    let center = Float2(x, y);
+    let v1_pos = origin;
+    let v1_rot_pos = float2_add(
+        apply_rotation_float2(float2_subtract(v1_pos, center), rot),
+        center,
+    );
+    let vert1 = vertex_t {
+        position: Float4(v1_rot_pos.0, v1_rot_pos.1, 0.0, 1.0),
+        color,
+    };
+
+    let v2_pos = Float2(x + width / 2.0, y - height / 2.0);
+    let v2_rot_pos = float2_add(
+        apply_rotation_float2(float2_subtract(v2_pos, center), rot),
+        center,
+    );
+    let vert2 = vertex_t {
+        position: Float4(v2_rot_pos.0, v2_rot_pos.1, 0.0, 1.0),
+        color,
+    };
+
+    let v3_pos = Float2(x - width / 2.0, y + height / 2.0);
+    let v3_rot_pos = float2_add(
+        apply_rotation_float2(float2_subtract(v3_pos, center), rot),
+        center,
+    );
+    let vert3 = vertex_t {
+        position: Float4(v3_rot_pos.0, v3_rot_pos.1, 0.0, 1.0),
+        color,
+    };
+
+    let v4_pos = Float2(x + width / 2.0, y + height / 2.0);
+    let v4_rot_pos = float2_add(
+        apply_rotation_float2(float2_subtract(v4_pos, center), rot),
+        center,
+    );
+    let vert4 = vertex_t {
+        position: Float4(v4_rot_pos.0, v4_rot_pos.1, 0.0, 1.0),
+        color,
+    };
+
+    verts.push(vert1);
+    verts.push(vert2);
+    verts.push(vert3);
+    verts.push(vert4);
+
+    verts
+}
@@ -48,7 +48,7 @@ impl Index<usize> for Float3 {
 }
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Float2(pub c_float, pub c_float);
 impl Float2 {
     pub fn magnitude(&self) -> f32 {
@@ -58,9 +58,6 @@ impl Float2 {
     pub fn normalized(&self) -> Float2 {
         Float2(self.0 / self.magnitude(), self.1 / self.magnitude())
     }
-    pub fn default() -> Float2 {
-        Float2(0.0, 0.0)
-    }
 }
 
 pub struct Float2x2 {
@@ -83,15 +80,18 @@ pub fn scale2 (v : Float2, f : f32) -> Float2 {
 }
 
 #[inline]
+#[allow(dead_code)]
 pub fn float3_add(v1 : Float3, v2 : Float3) -> Float3 {
     Float3(v1.0 + v2.0, v1.1 + v2.1, v1.2 + v2.2)
 }
 
 #[inline]
+#[allow(dead_code)]
 pub fn float3_subtract(v1 : Float3, v2 : Float3) -> Float3 {
     Float3(v1.0 - v2.0, v1.1 - v2.1, v1.2 - v2.2)
 }
 #[inline]
+#[allow(dead_code)]
 pub fn scale3 (v : Float3, f : f32) -> Float3 {
     Float3(v.0 * f, v.1 * f, v.2 * f)
 }
@@ -102,6 +102,7 @@ pub fn dot(v1 : Float2, v2 : Float2) -> f32 {
 }
 
 #[inline]
+#[allow(dead_code)]
 pub fn dot3(v1 : Float3, v2 : Float3) -> f32 {
     v1.0 * v2.0 + v1.1 * v2.1 + v1.2 * v2.2
 }
@@ -127,6 +128,7 @@ pub fn apply_rotation_float2(target : Float2, theta : f32) -> Float2 {
 }
 
 #[inline]
+#[allow(dead_code)]
 pub fn cross_product(vec1 : &Float3, vec2 : &Float3) -> Float3 {
     Float3(
         vec1.1 * vec2.2 - vec1.2 * vec2.1,
@@ -136,6 +138,7 @@ pub fn cross_product(vec1 : &Float3, vec2 : &Float3) -> Float3 {
 }
 
 
+#[allow(dead_code)]
 pub fn calculate_quaternion(camera_rotation_dir : &Float3) -> Float4 {
     let default_rotation = Float3(0.0, 0.0, 1.0);
     let camera_rotation = camera_rotation_dir.normalized();
@@ -156,22 +159,26 @@ pub fn calculate_quaternion(camera_rotation_dir : &Float3) -> Float4 {
 }
 
 
+#[allow(dead_code)]
 pub fn update_quat_angle(q : &Float4, theta : f32) -> Float4 {
     let new_ratio = theta.sin() / q.3.acos().sin();
     Float4(q.0 * new_ratio, q.1 * new_ratio, q.2 * new_ratio, theta.cos())
 }
 
 
+#[allow(dead_code)]
 fn quat_inv(quat : Float4) -> Float4 {
     Float4(-quat.0, -quat.1, -quat.2, quat.3)
 }
 
+#[allow(dead_code)]
 fn quat_dot (q1 : Float4, q2 : Float4) -> Float4 {
     let s : f32 = q1.3 * q2.3 - dot3(Float3(q1.0, q1.1, q1.2), Float3(q2.0, q2.1, q2.2));
     let v : Float3 = float3_add(cross_product(&Float3(q1.0, q1.1, q1.2), &Float3(q2.0, q2.1, q2.2)), float3_add(scale3(Float3(q2.0, q2.1, q2.2), q1.3), scale3(Float3(q1.0, q1.1, q1.2), q2.3)));
     Float4::from_float3(v, s)
 }
 
+#[allow(dead_code)]
 pub fn quat_mult (vec : Float3, quat : Float4) -> Float3 {
     let r = quat_dot(quat_dot(quat_inv(quat), Float4::from_float3(vec, 0.0)), quat);
     Float3(r.0, r.1, r.2)
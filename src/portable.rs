@@ -0,0 +1,218 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, OwnedDisplayHandle};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::{Fullscreen, Window, WindowId};
+
+use crate::bot::Bot;
+use crate::game::{FPS, Game, Scene, VIEW_HEIGHT, VIEW_WIDTH};
+use crate::gpu::GpuRenderer;
+use crate::input::{Action, Input};
+use crate::maths::Float2;
+use crate::profiler::Profiler;
+use crate::scaling::{ScalePolicy, ScaledView, fit};
+use crate::software::SoftwareRenderer;
+
+//draws a scene into the window, sizes are in physical pixels
+pub trait Renderer {
+    fn resize(&mut self, width: u32, height: u32);
+    //upload and encode time goes to the profiler
+    fn render(&mut self, scene: &Scene, view: ScaledView, profiler: &mut Profiler);
+}
+
+//physical keys so the layout matches the mac build whatever the keyboard language
+fn action(code: KeyCode) -> Option<Action> {
+    match code {
+        KeyCode::KeyW => Some(Action::Up),
+        KeyCode::KeyS => Some(Action::Down),
+        KeyCode::KeyA => Some(Action::Left),
+        KeyCode::KeyD => Some(Action::Right),
+        KeyCode::KeyE => Some(Action::Drain),
+        KeyCode::Space => Some(Action::PlaceWall),
+        KeyCode::KeyQ => Some(Action::PlaceMirror),
+        KeyCode::KeyF => Some(Action::ToggleFullscreen),
+        KeyCode::KeyP => Some(Action::CycleScalePolicy),
+        KeyCode::Backquote => Some(Action::ToggleConsole),
+        KeyCode::F3 => Some(Action::ToggleOverlay),
+        KeyCode::F4 => Some(Action::ToggleProfiler),
+        _ => None,
+    }
+}
+
+struct App {
+    game: Game,
+    input: Input,
+    bot: Option<Bot>,
+    software: bool,
+    display: OwnedDisplayHandle,
+    window: Option<Arc<Window>>,
+    renderer: Option<Box<dyn Renderer>>,
+    scale_policy: ScalePolicy,
+    scaled: ScaledView,
+    next_frame: Instant,
+}
+
+impl App {
+    fn create_renderer(&self, window: Arc<Window>) -> Box<dyn Renderer> {
+        if !self.software {
+            match GpuRenderer::new(window.clone(), self.display.clone()) {
+                Ok(gpu) => return Box::new(gpu),
+                Err(error) => println!("gpu renderer unavailable ({error}), using software"),
+            }
+        }
+        Box::new(SoftwareRenderer::new(window, self.display.clone()))
+    }
+
+    fn tick(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(window) = self.window.as_ref() else {
+            return;
+        };
+        //frontend keys type into the console while it is open
+        let typing = self.game.debug.console.open;
+        if !typing && self.input.pressed(Action::ToggleFullscreen) {
+            let fullscreen = match window.fullscreen() {
+                Some(_) => None,
+                None => Some(Fullscreen::Borderless(None)),
+            };
+            window.set_fullscreen(fullscreen);
+        }
+        if !typing && self.input.pressed(Action::CycleScalePolicy) {
+            self.scale_policy = match self.scale_policy {
+                ScalePolicy::Letterbox => ScalePolicy::Expand,
+                ScalePolicy::Expand => ScalePolicy::Letterbox,
+            };
+        }
+        //resizes and scale factor changes only change how much of the world is shown
+        let size = window.inner_size();
+        self.scaled = fit(
+            Float2(VIEW_WIDTH, VIEW_HEIGHT),
+            Float2(size.width as f32, size.height as f32),
+            self.scale_policy,
+        );
+        self.game.camera.viewport = self.scaled.visible;
+        if let Some(bot) = self.bot.as_mut() {
+            bot.act(&self.game, &mut self.input);
+        }
+        self.game.step(&self.input);
+        self.input.end_frame();
+        window.request_redraw();
+        if self.game.dismissed {
+            event_loop.exit();
+        }
+    }
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        let attributes = Window::default_attributes()
+            .with_title("Colorstep")
+            .with_inner_size(LogicalSize::new(VIEW_WIDTH, VIEW_HEIGHT))
+            .with_min_inner_size(LogicalSize::new(VIEW_WIDTH / 2.0, VIEW_HEIGHT / 2.0));
+        let window = Arc::new(
+            event_loop
+                .create_window(attributes)
+                .expect("couldn't create window"),
+        );
+        let mut renderer = self.create_renderer(window.clone());
+        let size = window.inner_size();
+        renderer.resize(size.width, size.height);
+        self.renderer = Some(renderer);
+        self.window = Some(window);
+        self.next_frame = Instant::now();
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(code),
+                        state,
+                        text,
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(action) = action(code) {
+                    match state {
+                        ElementState::Pressed => self.input.press(action),
+                        ElementState::Released => self.input.release(action),
+                    }
+                }
+                if state == ElementState::Pressed {
+                    match code {
+                        KeyCode::Enter | KeyCode::NumpadEnter => self.input.type_char('\n'),
+                        KeyCode::Backspace => self.input.type_char('\u{8}'),
+                        _ => {
+                            for c in text.iter().flat_map(|text| text.chars()) {
+                                self.input.type_char(c);
+                            }
+                        }
+                    }
+                }
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                //hue slider spans the game view, not the letterbox bars
+                let pixel = Float2(position.x as f32, position.y as f32);
+                if self.scaled.contains(pixel) {
+                    let point = self.scaled.to_view(pixel);
+                    self.input
+                        .set_hue(((point.0 / self.scaled.visible.0 + 1.0) / 2.0) as f64);
+                }
+            }
+            WindowEvent::Resized(size) => {
+                if let Some(renderer) = self.renderer.as_mut() {
+                    renderer.resize(size.width, size.height);
+                }
+            }
+            WindowEvent::RedrawRequested => {
+                if let Some(renderer) = self.renderer.as_mut() {
+                    renderer.render(&self.game.scene, self.scaled, &mut self.game.profiler);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+        if now >= self.next_frame {
+            //falling behind skips frames instead of running the simulation fast to catch up
+            self.next_frame = (self.next_frame + Duration::from_secs_f32(1.0 / FPS)).max(now);
+            self.tick(event_loop);
+        }
+        event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_frame));
+    }
+}
+
+pub fn run(game: Game, software: bool, bot: Option<Bot>) {
+    let event_loop = EventLoop::new().expect("couldn't create event loop");
+    let mut app = App {
+        game,
+        input: Input::new(),
+        bot,
+        software,
+        display: event_loop.owned_display_handle(),
+        window: None,
+        renderer: None,
+        scale_policy: ScalePolicy::Letterbox,
+        scaled: fit(
+            Float2(VIEW_WIDTH, VIEW_HEIGHT),
+            Float2(VIEW_WIDTH, VIEW_HEIGHT),
+            ScalePolicy::Letterbox,
+        ),
+        next_frame: Instant::now(),
+    };
+    event_loop
+        .run_app(&mut app)
+        .expect("event loop exited with an error");
+    app.game.report();
+}
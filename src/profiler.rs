@@ -0,0 +1,248 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+
+//frames kept for the rolling stats, four seconds at 60fps
+const WINDOW: usize = 240;
+//histogram bin upper edges in microseconds, the last bin takes everything slower
+pub const BIN_EDGES: [f32; 7] = [25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0];
+pub const BINS: usize = BIN_EDGES.len() + 1;
+
+//the parts of a frame that get timed, the first six in Game::step and the last two in the renderers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    //console, placement and player movement
+    Input,
+    //boss script, jumpropes, laser volleys and cluster lobs
+    Spawn,
+    //bombs, blasts and the movement, gravity, wall, lifetime and bounds systems
+    Movement,
+    Collision,
+    Particles,
+    //hazard, goal and overlay quads plus the camera
+    Vertices,
+    //vertex and uniform writes, or presenting the software canvas
+    Upload,
+    //command encoding and submission, or rasterizing on the cpu
+    Encode,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 8] = [
+        Phase::Input,
+        Phase::Spawn,
+        Phase::Movement,
+        Phase::Collision,
+        Phase::Particles,
+        Phase::Vertices,
+        Phase::Upload,
+        Phase::Encode,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Input => "input",
+            Phase::Spawn => "spawn",
+            Phase::Movement => "movement",
+            Phase::Collision => "collision",
+            Phase::Particles => "particles",
+            Phase::Vertices => "vertices",
+            Phase::Upload => "upload",
+            Phase::Encode => "encode",
+        }
+    }
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+//rolling numbers for one phase, in microseconds per frame
+#[derive(Debug, Clone, Copy)]
+pub struct PhaseStats {
+    pub mean: f32,
+    pub p95: f32,
+    pub max: f32,
+    pub histogram: [u32; BINS],
+}
+
+//a finished span, microseconds since the profiler was made
+#[derive(Debug, Clone, Copy)]
+struct TraceEvent {
+    phase: Phase,
+    frame: u64,
+    start: f64,
+    duration: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    epoch: Instant,
+    frame: u64,
+    //this frame's total per phase, a phase can be timed more than once a frame
+    current: [f32; Phase::ALL.len()],
+    timed: bool,
+    history: [VecDeque<f32>; Phase::ALL.len()],
+    //every span, only kept while tracing
+    trace: Option<Vec<TraceEvent>>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Profiler::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler {
+            epoch: Instant::now(),
+            frame: 0,
+            current: [0.0; Phase::ALL.len()],
+            timed: false,
+            history: Default::default(),
+            trace: None,
+        }
+    }
+
+    //keeps every span for a chrome trace, meant for headless runs since it grows every frame
+    pub fn record_trace(&mut self, on: bool) {
+        self.trace = on.then(Vec::new);
+    }
+
+    //the start of a span, handed back to record once the phase is done
+    pub fn start(&self) -> Instant {
+        Instant::now()
+    }
+
+    pub fn record(&mut self, phase: Phase, start: Instant) {
+        let end = Instant::now();
+        let duration = end.duration_since(start).as_secs_f64() * 1e6;
+        self.current[phase.index()] += duration as f32;
+        self.timed = true;
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(TraceEvent {
+                phase,
+                frame: self.frame,
+                start: start.duration_since(self.epoch).as_secs_f64() * 1e6,
+                duration,
+            });
+        }
+    }
+
+    //closes the frame, rendering after a step counts towards the frame it drew
+    pub fn next_frame(&mut self) {
+        if !self.timed {
+            return;
+        }
+        for (history, &time) in self.history.iter_mut().zip(self.current.iter()) {
+            if history.len() == WINDOW {
+                history.pop_front();
+            }
+            history.push_back(time);
+        }
+        self.current = [0.0; Phase::ALL.len()];
+        self.timed = false;
+        self.frame += 1;
+    }
+
+    pub fn stats(&self, phase: Phase) -> Option<PhaseStats> {
+        let history = &self.history[phase.index()];
+        if history.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f32> = history.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let mut histogram = [0; BINS];
+        for &time in sorted.iter() {
+            histogram[BIN_EDGES.partition_point(|&edge| edge <= time)] += 1;
+        }
+        let rank = ((sorted.len() as f32 * 0.95).ceil() as usize).clamp(1, sorted.len());
+        Some(PhaseStats {
+            mean: sorted.iter().sum::<f32>() / sorted.len() as f32,
+            p95: sorted[rank - 1],
+            max: sorted[sorted.len() - 1],
+            histogram,
+        })
+    }
+
+    //chrome://tracing and perfetto format, one complete event per span
+    pub fn trace_json(&self) -> String {
+        let mut json = String::from("{\"traceEvents\": [\n");
+        for (index, event) in self.trace.iter().flatten().enumerate() {
+            let separator = if index == 0 { "" } else { ",\n" };
+            let _ = write!(
+                json,
+                "{separator}  {{\"name\": \"{}\", \"ph\": \"X\", \"ts\": {:.3}, \"dur\": {:.3}, \"pid\": 1, \"tid\": 1, \"args\": {{\"frame\": {}}}}}",
+                event.phase.name(),
+                event.start,
+                event.duration,
+                event.frame
+            );
+        }
+        json.push_str("\n], \"displayTimeUnit\": \"ms\"}\n");
+        json
+    }
+
+    pub fn save_trace(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.trace_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_roll_into_stats_and_bins() {
+        let mut profiler = Profiler::new();
+        assert!(profiler.stats(Phase::Collision).is_none());
+        for _ in 0..WINDOW + 10 {
+            let start = profiler.start();
+            profiler.record(Phase::Collision, start);
+            profiler.next_frame();
+        }
+        let stats = profiler.stats(Phase::Collision).unwrap();
+        assert_eq!(stats.histogram.iter().sum::<u32>(), WINDOW as u32);
+        assert!(stats.mean <= stats.max && stats.p95 <= stats.max);
+        //phases never timed still roll as zero cost frames
+        assert_eq!(profiler.stats(Phase::Encode).unwrap().max, 0.0);
+    }
+
+    #[test]
+    fn traces_are_only_kept_when_asked() {
+        let mut profiler = Profiler::new();
+        let start = profiler.start();
+        profiler.record(Phase::Spawn, start);
+        assert!(!profiler.trace_json().contains("spawn"));
+
+        profiler.record_trace(true);
+        let start = profiler.start();
+        profiler.record(Phase::Spawn, start);
+        profiler.next_frame();
+        let start = profiler.start();
+        profiler.record(Phase::Upload, start);
+        let json = profiler.trace_json();
+        assert!(json.contains("\"name\": \"spawn\", \"ph\": \"X\""));
+        assert!(json.contains("\"name\": \"upload\""));
+        assert!(json.contains("\"frame\": 1"));
+    }
+
+    #[test]
+    fn a_game_step_times_each_simulation_phase_once() {
+        use crate::game::{Difficulty, Game};
+        use crate::input::Input;
+
+        let mut game = Game::with_difficulty(Difficulty::NORMAL);
+        game.verbose = false;
+        game.profiler.record_trace(true);
+        game.step(&Input::default());
+        let json = game.profiler.trace_json();
+        for phase in &Phase::ALL[..5] {
+            let name = format!("\"name\": \"{}\"", phase.name());
+            assert_eq!(json.matches(&name).count(), 1, "{name}");
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::maths::Float2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalePolicy {
+    //keep the virtual area exactly, bars fill whatever is left over
+    Letterbox,
+    //fill the window, showing extra world on the longer axis
+    Expand,
+}
+
+//rect in drawable pixels, origin top left like the render target
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaledView {
+    //where the game draws within the drawable
+    pub viewport: PixelRect,
+    //half extents in virtual units that fit the viewport, at least the virtual size
+    pub visible: Float2,
+    //drawable pixels per virtual unit, the same on both axes
+    pub scale: f32,
+}
+
+//virtual is a half extent in game units, drawable the render target size in pixels
+pub fn fit(virtual_half: Float2, drawable: Float2, policy: ScalePolicy) -> ScaledView {
+    //a minimised window can report 0x0, keep the numbers finite
+    let width = drawable.0.max(1.0);
+    let height = drawable.1.max(1.0);
+    let scale = (width / (2.0 * virtual_half.0)).min(height / (2.0 * virtual_half.1));
+    match policy {
+        ScalePolicy::Letterbox => {
+            let viewport_width = 2.0 * virtual_half.0 * scale;
+            let viewport_height = 2.0 * virtual_half.1 * scale;
+            ScaledView {
+                viewport: PixelRect {
+                    x: (width - viewport_width) / 2.0,
+                    y: (height - viewport_height) / 2.0,
+                    width: viewport_width,
+                    height: viewport_height,
+                },
+                visible: virtual_half,
+                scale,
+            }
+        }
+        ScalePolicy::Expand => ScaledView {
+            viewport: PixelRect {
+                x: 0.0,
+                y: 0.0,
+                width,
+                height,
+            },
+            visible: Float2(width / (2.0 * scale), height / (2.0 * scale)),
+            scale,
+        },
+    }
+}
+
+impl ScaledView {
+    //view space point, y up and centered, to a drawable pixel
+    pub fn to_pixels(self, point: Float2) -> Float2 {
+        Float2(
+            self.viewport.x + self.viewport.width / 2.0 + point.0 * self.scale,
+            self.viewport.y + self.viewport.height / 2.0 - point.1 * self.scale,
+        )
+    }
+    pub fn to_view(self, pixel: Float2) -> Float2 {
+        Float2(
+            (pixel.0 - self.viewport.x - self.viewport.width / 2.0) / self.scale,
+            (self.viewport.y + self.viewport.height / 2.0 - pixel.1) / self.scale,
+        )
+    }
+    pub fn contains(self, pixel: Float2) -> bool {
+        pixel.0 >= self.viewport.x
+            && pixel.0 <= self.viewport.x + self.viewport.width
+            && pixel.1 >= self.viewport.y
+            && pixel.1 <= self.viewport.y + self.viewport.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIRTUAL: Float2 = Float2(1024.0, 768.0);
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn matching_aspect_fills_the_drawable() {
+        for policy in [ScalePolicy::Letterbox, ScalePolicy::Expand] {
+            let view = fit(VIRTUAL, Float2(1024.0, 768.0), policy);
+            assert_eq!(
+                view.viewport,
+                PixelRect {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 1024.0,
+                    height: 768.0
+                }
+            );
+            assert!(close(view.scale, 0.5));
+            assert!(close(view.visible.0, 1024.0) && close(view.visible.1, 768.0));
+        }
+    }
+
+    #[test]
+    fn letterbox_pillarboxes_wide_windows() {
+        let view = fit(VIRTUAL, Float2(1920.0, 768.0), ScalePolicy::Letterbox);
+        assert!(close(view.scale, 0.5));
+        assert!(close(view.viewport.width, 1024.0));
+        assert!(close(view.viewport.x, 448.0));
+        assert!(close(view.viewport.y, 0.0));
+        assert!(close(view.visible.0, 1024.0) && close(view.visible.1, 768.0));
+    }
+
+    #[test]
+    fn letterbox_bars_tall_windows() {
+        let view = fit(VIRTUAL, Float2(1024.0, 1200.0), ScalePolicy::Letterbox);
+        assert!(close(view.viewport.height, 768.0));
+        assert!(close(view.viewport.y, 216.0));
+        assert!(close(view.viewport.x, 0.0));
+    }
+
+    #[test]
+    fn expand_shows_more_world_on_the_long_axis() {
+        let view = fit(VIRTUAL, Float2(1920.0, 768.0), ScalePolicy::Expand);
+        assert!(close(view.scale, 0.5));
+        assert!(close(view.visible.0, 1920.0));
+        assert!(close(view.visible.1, 768.0));
+        assert!(close(view.viewport.width, 1920.0));
+    }
+
+    #[test]
+    fn hidpi_keeps_gameplay_coordinates_stable() {
+        let standard = fit(VIRTUAL, Float2(1024.0, 768.0), ScalePolicy::Letterbox);
+        let retina = fit(VIRTUAL, Float2(2048.0, 1536.0), ScalePolicy::Letterbox);
+        assert_eq!(standard.visible, retina.visible);
+        assert!(close(retina.scale, standard.scale * 2.0));
+        //the same pixel fraction maps to the same view point
+        let point = Float2(300.0, -200.0);
+        let pixel = retina.to_pixels(point);
+        assert!(close(pixel.0 / 2.0, standard.to_pixels(point).0));
+        let back = retina.to_view(pixel);
+        assert!(close(back.0, point.0) && close(back.1, point.1));
+    }
+
+    #[test]
+    fn pixel_mapping_is_y_down_and_round_trips() {
+        let view = fit(VIRTUAL, Float2(1600.0, 900.0), ScalePolicy::Letterbox);
+        let top_left = view.to_pixels(Float2(-VIRTUAL.0, VIRTUAL.1));
+        assert!(close(top_left.0, view.viewport.x) && close(top_left.1, view.viewport.y));
+        assert!(view.contains(top_left));
+        assert!(!view.contains(Float2(0.0, 0.0)));
+        let back = view.to_view(Float2(800.0, 450.0));
+        assert!(close(back.0, 0.0) && close(back.1, 0.0));
+    }
+
+    #[test]
+    fn empty_drawable_stays_finite() {
+        for policy in [ScalePolicy::Letterbox, ScalePolicy::Expand] {
+            let view = fit(VIRTUAL, Float2(0.0, 0.0), policy);
+            assert!(view.scale.is_finite() && view.scale > 0.0);
+            assert!(view.visible.0.is_finite() && view.visible.1.is_finite());
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScoringTuning {
+    //multiplier gained per delivery in a row without being hit
+    pub combo_step: f32,
+    pub max_multiplier: f32,
+    pub graze_points_per_second: f32,
+    //deliveries faster than par earn up to the full time bonus
+    pub par_time: f32,
+    pub time_bonus: f32,
+}
+
+impl Default for ScoringTuning {
+    fn default() -> Self {
+        ScoringTuning {
+            combo_step: 0.5,
+            max_multiplier: 4.0,
+            graze_points_per_second: 2.0,
+            par_time: 10.0,
+            time_bonus: 3.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScoreBreakdown {
+    pub deliveries: u32,
+    pub hits: u32,
+    pub best_combo: u32,
+    pub delivery_points: f32,
+    pub combo_points: f32,
+    pub graze_points: f32,
+    pub time_points: f32,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> u32 {
+        (self.delivery_points + self.combo_points + self.graze_points + self.time_points).floor()
+            as u32
+    }
+
+    //named values shared by the run file and the sim's per run records
+    pub fn fields(&self) -> [(&'static str, String); 8] {
+        [
+            ("deliveries", self.deliveries.to_string()),
+            ("hits", self.hits.to_string()),
+            ("best_combo", self.best_combo.to_string()),
+            ("delivery_points", self.delivery_points.to_string()),
+            ("combo_points", self.combo_points.to_string()),
+            ("graze_points", self.graze_points.to_string()),
+            ("time_points", self.time_points.to_string()),
+            ("total", self.total().to_string()),
+        ]
+    }
+
+    //plain key value lines so a run file can be read back without a parser dependency,
+    //the interactive game's own file, sim reports keep one breakdown per seeded run
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let lines: Vec<String> = self
+            .fields()
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        fs::write(path, lines.join("\n") + "\n")
+    }
+}
+
+impl fmt::Display for ScoreBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "deliveries   {:>6}", self.deliveries)?;
+        writeln!(f, "hits taken   {:>6}", self.hits)?;
+        writeln!(f, "best combo   {:>6}", self.best_combo)?;
+        writeln!(f, "delivery     {:>6.0}", self.delivery_points.floor())?;
+        writeln!(f, "combo        {:>6.0}", self.combo_points.floor())?;
+        writeln!(f, "grazes       {:>6.0}", self.graze_points.floor())?;
+        writeln!(f, "time         {:>6.0}", self.time_points.floor())?;
+        write!(f, "total        {:>6}", self.total())
+    }
+}
+
+pub struct Scoring {
+    pub tuning: ScoringTuning,
+    pub breakdown: ScoreBreakdown,
+    combo: u32,
+    round_time: f32,
+}
+
+impl Scoring {
+    pub fn new(tuning: ScoringTuning) -> Self {
+        Scoring {
+            tuning,
+            breakdown: ScoreBreakdown::default(),
+            combo: 0,
+            round_time: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, delta_t: f32) {
+        self.round_time += delta_t;
+    }
+
+    pub fn multiplier(&self) -> f32 {
+        (1.0 + self.combo as f32 * self.tuning.combo_step).min(self.tuning.max_multiplier)
+    }
+
+    //returns the points the delivery was worth with every bonus applied, starts the next round
+    pub fn deliver(&mut self, points: u32) -> f32 {
+        let base = points as f32;
+        let combo = base * (self.multiplier() - 1.0);
+        let speed = ((self.tuning.par_time - self.round_time) / self.tuning.par_time).max(0.0);
+        let time = speed * self.tuning.time_bonus;
+        self.breakdown.deliveries += 1;
+        self.breakdown.delivery_points += base;
+        self.breakdown.combo_points += combo;
+        self.breakdown.time_points += time;
+        self.combo += 1;
+        self.breakdown.best_combo = self.breakdown.best_combo.max(self.combo);
+        self.round_time = 0.0;
+        base + combo + time
+    }
+
+    pub fn graze(&mut self, delta_t: f32) {
+        self.breakdown.graze_points += self.tuning.graze_points_per_second * delta_t;
+    }
+
+    pub fn hit(&mut self) {
+        self.breakdown.hits += 1;
+        self.combo = 0;
+    }
+
+    pub fn total(&self) -> u32 {
+        self.breakdown.total()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_combo_multiplier_grows_per_delivery_up_to_its_cap() {
+        let mut scoring = Scoring::new(ScoringTuning::default());
+        assert_eq!(scoring.multiplier(), 1.0);
+        let mut multipliers = Vec::new();
+        for _ in 0..8 {
+            scoring.update(ScoringTuning::default().par_time);
+            scoring.deliver(1);
+            multipliers.push(scoring.multiplier());
+        }
+        assert_eq!(multipliers, [1.5, 2.0, 2.5, 3.0, 3.5, 4.0, 4.0, 4.0]);
+        assert_eq!(scoring.breakdown.best_combo, 8);
+    }
+
+    #[test]
+    fn a_hit_resets_the_combo_but_keeps_the_best() {
+        let mut scoring = Scoring::new(ScoringTuning::default());
+        for _ in 0..3 {
+            scoring.update(10.0);
+            scoring.deliver(2);
+        }
+        scoring.hit();
+        assert_eq!(scoring.multiplier(), 1.0);
+        assert_eq!(scoring.breakdown.hits, 1);
+        assert_eq!(scoring.breakdown.best_combo, 3);
+        //no combo points on the first delivery after the hit
+        scoring.update(10.0);
+        assert_eq!(scoring.deliver(2), 2.0);
+        assert_eq!(scoring.breakdown.combo_points, 1.0 + 2.0);
+    }
+
+    #[test]
+    fn grazes_pay_per_second_of_contact() {
+        let mut scoring = Scoring::new(ScoringTuning::default());
+        for _ in 0..6 {
+            scoring.graze(0.5);
+        }
+        assert_eq!(scoring.breakdown.graze_points, 6.0);
+        assert_eq!(scoring.total(), 6);
+    }
+
+    #[test]
+    fn faster_deliveries_earn_more_of_the_time_bonus() {
+        let tuning = ScoringTuning {
+            combo_step: 0.0,
+            ..ScoringTuning::default()
+        };
+        let mut scoring = Scoring::new(tuning);
+        assert_eq!(scoring.deliver(1), 1.0 + tuning.time_bonus);
+        scoring.update(tuning.par_time / 2.0);
+        assert_eq!(scoring.deliver(1), 1.0 + tuning.time_bonus / 2.0);
+        //nothing past par, and never less than nothing
+        scoring.update(tuning.par_time * 2.0);
+        assert_eq!(scoring.deliver(1), 1.0);
+        assert_eq!(scoring.breakdown.time_points, tuning.time_bonus * 1.5);
+        assert_eq!(scoring.breakdown.deliveries, 3);
+    }
+}
@@ -0,0 +1,135 @@
+// the only shader source, build.rs validates it and generates the metal version for the appkit frontend
+// every fragment function also has a cpu port of the same name in software.rs
+
+struct Uniforms {
+    view_projection: mat4x4<f32>,
+    // player position in drawable pixels, matching the fragment position
+    player: vec2<f32>,
+    radius: f32,
+    signal_lost: f32,
+};
+
+@group(0) @binding(0) var<uniform> unis: Uniforms;
+
+struct VertexIn {
+    @location(0) pos: vec4<f32>,
+    @location(1) col: vec4<f32>,
+};
+
+struct ColorInOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec4<f32>,
+};
+
+@vertex
+fn box_vertex(in: VertexIn, @builtin(vertex_index) vid: u32) -> ColorInOut {
+    var out: ColorInOut;
+    // world to clip through the camera, z carries per shader data so it stays out of the transform
+    out.position = unis.view_projection * vec4<f32>(in.pos.xy, 0.0, 1.0);
+    out.color = in.col;
+    out.uv = vec4<f32>(f32(vid % 2u), f32((vid % 4u) / 2u), in.pos.z, 1.0);
+    return out;
+}
+
+fn grayscale(color: vec4<f32>, alpha: f32) -> vec4<f32> {
+    return vec4<f32>(vec3<f32>(color.r * 0.299 + 0.587 * color.g + color.b * 0.114), alpha);
+}
+
+fn signal_fade(in: ColorInOut) -> vec4<f32> {
+    let t = saturate((distance(unis.player, in.position.xy) / unis.radius) + unis.signal_lost);
+    return mix(in.color, grayscale(in.color, in.color.a), t);
+}
+
+@fragment
+fn box_fragment(in: ColorInOut) -> @location(0) vec4<f32> {
+    return signal_fade(in);
+}
+
+// debug lines and text, flat color whatever the signal
+@fragment
+fn overlay_fragment(in: ColorInOut) -> @location(0) vec4<f32> {
+    return in.color;
+}
+
+// uv.z is the hue distance, unused while goals draw with scorezone_fragment
+@fragment
+fn goal_fragment(in: ColorInOut) -> @location(0) vec4<f32> {
+    let clamped_t = saturate(in.uv.z);
+    return mix(in.color, grayscale(in.color, 1.0), clamped_t);
+}
+
+@fragment
+fn target_fragment(in: ColorInOut) -> @location(0) vec4<f32> {
+    let color_out = signal_fade(in);
+
+    let coords = abs(vec2<f32>(in.uv.x - 0.5, in.uv.y - 0.5));
+    let r = 0.06;
+    let w = 0.4;
+
+    let d = length(coords - min(coords.x + coords.y, w) * 0.5) - r;
+    if (d > 0.0) {
+        discard;
+    }
+
+    return color_out;
+}
+
+// uv.z is the fill amount, 0 on launch and 1 on detonation
+@fragment
+fn telegraph_fragment(in: ColorInOut) -> @location(0) vec4<f32> {
+    var color_out = signal_fade(in);
+
+    let d = length(vec2<f32>(in.uv.x - 0.5, in.uv.y - 0.5) * 2.0);
+    if (d > 1.0) {
+        discard;
+    }
+
+    let fill = saturate(in.uv.z);
+    let edge = step(0.92, d);
+    let filled = step(d, fill);
+    color_out.a *= max(edge, max(filled * 0.5, 0.12));
+    return color_out;
+}
+
+// uv.z is how far the picked hue is from the goal's
+@fragment
+fn scorezone_fragment(in: ColorInOut) -> @location(0) vec4<f32> {
+    let coords0 = vec2<f32>(in.uv.x - 0.5, in.uv.y - 0.5);
+
+    var coords = vec2<f32>(in.uv.x - 0.5, in.uv.y - 0.5) * 2.0;
+    let he = 1.2;
+    let ra = 0.2;
+
+    coords = abs(coords);
+    coords = vec2<f32>(abs(coords.x - coords.y), 1.0 - coords.x - coords.y) / sqrt(2.0);
+
+    let p = (he - coords.y - 0.25 / he) / (6.0 * he);
+    let q = coords.x / (he * he * 16.0);
+    let h = q * q - p * p * p;
+
+    var x: f32;
+    if (h > 0.0) {
+        let r = sqrt(h);
+        x = pow(q + r, 1.0 / 3.0) - pow(abs(q - r), 1.0 / 3.0) * sign(r - q);
+    } else {
+        let r = sqrt(p);
+        x = 2.0 * r * cos(acos(q / (p * r)) / 3.0);
+    }
+    x = min(x, sqrt(2.0) / 2.0);
+
+    let z = vec2<f32>(x, he * (1.0 - 2.0 * x * x)) - coords;
+    let inner_d = (length(z) * sign(z.y)) - ra;
+
+    let coords1 = coords0 * 0.9;
+    let b = vec2<f32>(0.06125, 0.06125);
+    let a = abs(coords1) - b;
+    let d = length(max(a, vec2<f32>(0.0))) + min(max(a.x, a.y), 0.0);
+
+    if (sign(inner_d) == 1.0 && sign(d) == 1.0) {
+        discard;
+    }
+
+    let clamped_t = saturate(in.uv.z);
+    return mix(in.color, grayscale(in.color, 0.0), clamped_t);
+}
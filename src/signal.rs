@@ -0,0 +1,341 @@
+use std::collections::VecDeque;
+
+//signal lost at or past this ends the run
+pub const DEATH_THRESHOLD: f32 = 1.15;
+//accumulated hits land a hair under their exact sum in f32
+const THRESHOLD_EPSILON: f32 = 1e-4;
+const LOG_LIMIT: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitSource {
+    Laser,
+    Jumprope,
+    Fragment,
+    Blast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignalEvent {
+    //mismatched color contact, ignored during invulnerability
+    Hit(HitSource),
+    //same color contact
+    Graze(HitSource),
+    Deliver,
+    //held drain key, widens the view radius at the cost of signal
+    Drain(f32),
+    //passive loss over time
+    Decay(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalWeights {
+    pub laser_hit: f32,
+    pub jumprope_hit: f32,
+    pub fragment_hit: f32,
+    pub blast_hit: f32,
+    pub graze: f32,
+    pub deliver: f32,
+    pub drain_per_second: f32,
+    pub decay_per_second: f32,
+    pub jumprope_graze_radius: f32,
+    pub drain_radius_per_second: f32,
+    //seconds after a hit where further hits are ignored
+    pub invulnerability: f32,
+}
+
+impl Default for SignalWeights {
+    fn default() -> Self {
+        SignalWeights {
+            laser_hit: 0.10,
+            jumprope_hit: 0.15,
+            fragment_hit: 0.20,
+            blast_hit: 0.20,
+            graze: 0.005,
+            deliver: 0.25,
+            drain_per_second: 0.1,
+            decay_per_second: 0.0,
+            jumprope_graze_radius: 1.0,
+            drain_radius_per_second: 10.0,
+            invulnerability: 0.75,
+        }
+    }
+}
+
+impl SignalWeights {
+    pub fn hit(&self, source: HitSource) -> f32 {
+        match source {
+            HitSource::Laser => self.laser_hit,
+            HitSource::Jumprope => self.jumprope_hit,
+            HitSource::Fragment => self.fragment_hit,
+            HitSource::Blast => self.blast_hit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalLogEntry {
+    pub time: f32,
+    pub event: SignalEvent,
+    //change in signal lost after clamping
+    pub delta: f32,
+}
+
+//signal lost over a whole run by where it came from, the log only keeps the latest events
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SignalTotals {
+    pub laser: f32,
+    pub jumprope: f32,
+    pub fragment: f32,
+    pub blast: f32,
+    pub graze: f32,
+    pub drain: f32,
+    pub decay: f32,
+    //given back by deliveries
+    pub recovered: f32,
+}
+
+impl SignalTotals {
+    fn add(&mut self, event: SignalEvent, delta: f32) {
+        let total = match event {
+            SignalEvent::Hit(HitSource::Laser) => &mut self.laser,
+            SignalEvent::Hit(HitSource::Jumprope) => &mut self.jumprope,
+            SignalEvent::Hit(HitSource::Fragment) => &mut self.fragment,
+            SignalEvent::Hit(HitSource::Blast) => &mut self.blast,
+            SignalEvent::Graze(_) => &mut self.graze,
+            SignalEvent::Drain(_) => &mut self.drain,
+            SignalEvent::Decay(_) => &mut self.decay,
+            SignalEvent::Deliver => {
+                self.recovered -= delta;
+                return;
+            }
+        };
+        *total += delta;
+    }
+
+    pub fn sum(&self, other: &SignalTotals) -> SignalTotals {
+        SignalTotals {
+            laser: self.laser + other.laser,
+            jumprope: self.jumprope + other.jumprope,
+            fragment: self.fragment + other.fragment,
+            blast: self.blast + other.blast,
+            graze: self.graze + other.graze,
+            drain: self.drain + other.drain,
+            decay: self.decay + other.decay,
+            recovered: self.recovered + other.recovered,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Signal {
+    pub weights: SignalWeights,
+    lost: f32,
+    radius: f32,
+    invulnerable: f32,
+    time: f32,
+    log: VecDeque<SignalLogEntry>,
+    totals: SignalTotals,
+}
+
+impl Signal {
+    pub fn new(radius: f32, weights: SignalWeights) -> Self {
+        Signal {
+            weights,
+            lost: 0.0,
+            radius,
+            invulnerable: 0.0,
+            time: 0.0,
+            log: VecDeque::new(),
+            totals: SignalTotals::default(),
+        }
+    }
+
+    //advances the clock and the invulnerability timer, then applies passive decay
+    pub fn update(&mut self, delta_t: f32) {
+        self.time += delta_t;
+        self.invulnerable = (self.invulnerable - delta_t).max(0.0);
+        self.apply(SignalEvent::Decay(delta_t));
+    }
+
+    //returns the change in signal lost, 0 when the event was ignored or clamped away
+    pub fn apply(&mut self, event: SignalEvent) -> f32 {
+        let weights = self.weights;
+        let change = match event {
+            SignalEvent::Hit(_) if self.invulnerable > 0.0 => return 0.0,
+            SignalEvent::Hit(source) => {
+                self.invulnerable = weights.invulnerability;
+                weights.hit(source)
+            }
+            SignalEvent::Graze(source) => {
+                if source == HitSource::Jumprope {
+                    self.radius += weights.jumprope_graze_radius;
+                }
+                weights.graze
+            }
+            SignalEvent::Deliver => -weights.deliver,
+            SignalEvent::Drain(delta_t) => {
+                self.radius += weights.drain_radius_per_second * delta_t;
+                weights.drain_per_second * delta_t
+            }
+            SignalEvent::Decay(delta_t) => weights.decay_per_second * delta_t,
+        };
+        let previous = self.lost;
+        self.lost = (self.lost + change).clamp(0.0, DEATH_THRESHOLD);
+        let delta = self.lost - previous;
+        self.totals.add(event, delta);
+        //continuous events would flood the log every frame
+        let discrete = !matches!(event, SignalEvent::Drain(_) | SignalEvent::Decay(_));
+        if discrete {
+            if self.log.len() == LOG_LIMIT {
+                self.log.pop_front();
+            }
+            self.log.push_back(SignalLogEntry {
+                time: self.time,
+                event,
+                delta,
+            });
+        }
+        delta
+    }
+
+    pub fn lost(&self) -> f32 {
+        self.lost
+    }
+    //console override, bypasses the log and the totals
+    pub fn set_lost(&mut self, lost: f32) {
+        self.lost = lost.clamp(0.0, DEATH_THRESHOLD);
+    }
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+    //seconds of simulation, the clock log entries are stamped with
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+    pub fn invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
+    }
+    pub fn is_lost(&self) -> bool {
+        self.lost >= DEATH_THRESHOLD - THRESHOLD_EPSILON
+    }
+    pub fn log(&self) -> impl Iterator<Item = &SignalLogEntry> {
+        self.log.iter()
+    }
+    pub fn totals(&self) -> SignalTotals {
+        self.totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vulnerable() -> Signal {
+        Signal::new(
+            300.0,
+            SignalWeights {
+                invulnerability: 0.0,
+                ..SignalWeights::default()
+            },
+        )
+    }
+
+    #[test]
+    fn survives_just_under_threshold() {
+        let mut signal = vulnerable();
+        //1.10 lost after eleven laser hits
+        for _ in 0..11 {
+            signal.apply(SignalEvent::Hit(HitSource::Laser));
+        }
+        assert!(!signal.is_lost(), "lost {}", signal.lost());
+        for _ in 0..9 {
+            signal.apply(SignalEvent::Graze(HitSource::Laser));
+        }
+        assert!(!signal.is_lost(), "lost {}", signal.lost());
+    }
+
+    #[test]
+    fn dies_exactly_at_threshold() {
+        let mut signal = vulnerable();
+        //five fragments and a rope sum to exactly 1.15, which f32 lands just under
+        for _ in 0..5 {
+            signal.apply(SignalEvent::Hit(HitSource::Fragment));
+        }
+        assert!(!signal.is_lost());
+        signal.apply(SignalEvent::Hit(HitSource::Jumprope));
+        assert!(signal.is_lost(), "lost {}", signal.lost());
+    }
+
+    #[test]
+    fn lost_is_clamped() {
+        let mut signal = vulnerable();
+        assert_eq!(signal.apply(SignalEvent::Deliver), 0.0);
+        assert_eq!(signal.lost(), 0.0);
+        for _ in 0..20 {
+            signal.apply(SignalEvent::Hit(HitSource::Blast));
+        }
+        assert_eq!(signal.lost(), DEATH_THRESHOLD);
+        assert!(signal.is_lost());
+        signal.apply(SignalEvent::Deliver);
+        assert!(!signal.is_lost());
+    }
+
+    #[test]
+    fn invulnerability_ignores_hits_until_it_expires() {
+        let mut signal = Signal::new(300.0, SignalWeights::default());
+        signal.apply(SignalEvent::Hit(HitSource::Laser));
+        assert!(signal.invulnerable());
+        assert_eq!(signal.apply(SignalEvent::Hit(HitSource::Fragment)), 0.0);
+        //grazes still count
+        assert!(signal.apply(SignalEvent::Graze(HitSource::Laser)) > 0.0);
+        signal.update(signal.weights.invulnerability);
+        assert!(!signal.invulnerable());
+        assert!(signal.apply(SignalEvent::Hit(HitSource::Fragment)) > 0.0);
+    }
+
+    #[test]
+    fn rope_grazes_and_drain_widen_radius() {
+        let mut signal = vulnerable();
+        signal.apply(SignalEvent::Graze(HitSource::Jumprope));
+        signal.apply(SignalEvent::Graze(HitSource::Laser));
+        assert_eq!(signal.radius(), 301.0);
+        signal.apply(SignalEvent::Drain(0.5));
+        assert_eq!(signal.radius(), 306.0);
+        assert!((signal.lost() - 0.06).abs() < 1e-6);
+    }
+
+    #[test]
+    fn log_records_discrete_events() {
+        let mut signal = vulnerable();
+        signal.update(1.0);
+        signal.apply(SignalEvent::Hit(HitSource::Laser));
+        signal.apply(SignalEvent::Drain(1.0 / 60.0));
+        signal.update(0.5);
+        signal.apply(SignalEvent::Deliver);
+        let log: Vec<_> = signal.log().collect();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].event, SignalEvent::Hit(HitSource::Laser));
+        assert_eq!(log[0].time, 1.0);
+        assert_eq!(log[1].event, SignalEvent::Deliver);
+        assert_eq!(log[1].time, 1.5);
+        assert!(log[1].delta < 0.0);
+    }
+
+    #[test]
+    fn totals_outlive_the_log() {
+        let mut signal = vulnerable();
+        for _ in 0..LOG_LIMIT {
+            signal.apply(SignalEvent::Graze(HitSource::Laser));
+            signal.apply(SignalEvent::Deliver);
+        }
+        signal.apply(SignalEvent::Hit(HitSource::Jumprope));
+        signal.apply(SignalEvent::Hit(HitSource::Laser));
+        assert_eq!(signal.log().count(), LOG_LIMIT);
+        let totals = signal.totals();
+        let grazed = LOG_LIMIT as f32 * signal.weights.graze;
+        assert!((totals.graze - grazed).abs() < 1e-4);
+        assert!((totals.recovered - grazed).abs() < 1e-4);
+        assert!((totals.jumprope - 0.15).abs() < 1e-6);
+        assert!((totals.laser - 0.10).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,574 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::bot::{Bot, Policy};
+use crate::game::{Difficulty, FPS, Game};
+use crate::input::Input;
+use crate::profiler::Profiler;
+use crate::rng;
+use crate::scoring::ScoreBreakdown;
+use crate::signal::SignalTotals;
+
+const HISTOGRAM_BINS: u32 = 10;
+
+//one headless run, with the state it ended in so a report can say what ended it
+//the seed replays it, the breakdown is the one the game over panel showed
+#[derive(Debug, Clone, Copy)]
+pub struct RunResult {
+    pub seed: u64,
+    pub frames: u64,
+    //raw delivered points, what spawns and clusters scale with
+    pub score: u32,
+    pub breakdown: ScoreBreakdown,
+    pub spawns: usize,
+    pub clusters: u32,
+    pub losses: SignalTotals,
+}
+
+impl RunResult {
+    //with the combo, graze and time bonuses
+    pub fn total(&self) -> u32 {
+        self.breakdown.total()
+    }
+}
+
+fn play_out(game: &mut Game, policy: Policy, frame_limit: u64) {
+    let mut input = Input::new();
+    let mut bot = Bot::new(policy);
+    while !game.over() && game.frames < frame_limit {
+        bot.act(game, &mut input);
+        game.step(&input);
+        input.end_frame();
+    }
+}
+
+fn headless(seed: u64, difficulty: Difficulty) -> Game {
+    rng::seed(seed);
+    let mut game = Game::with_difficulty(difficulty);
+    game.verbose = false;
+    game
+}
+
+//the same seed, policy and difficulty always play out the same run
+pub fn play(seed: u64, policy: Policy, difficulty: Difficulty, frame_limit: u64) -> RunResult {
+    let mut game = headless(seed, difficulty);
+    play_out(&mut game, policy, frame_limit);
+    RunResult {
+        seed,
+        frames: game.frames,
+        score: game.score,
+        breakdown: game.scoring.breakdown,
+        spawns: game.current_spawns,
+        clusters: game.cluster_count(),
+        losses: game.signal.totals(),
+    }
+}
+
+//replays one run with every simulation span kept, for saving as a chrome trace
+pub fn trace(seed: u64, policy: Policy, difficulty: Difficulty, frame_limit: u64) -> Profiler {
+    let mut game = headless(seed, difficulty);
+    game.profiler.record_trace(true);
+    play_out(&mut game, policy, frame_limit);
+    std::mem::take(&mut game.profiler)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SimConfig {
+    pub games: u64,
+    //runs use seeds first_seed..first_seed + games
+    pub first_seed: u64,
+    //runs still going here are stopped and count as survived to it
+    pub frame_limit: u64,
+    pub policy: Policy,
+    pub difficulty: Difficulty,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig {
+            games: 100,
+            first_seed: 0,
+            //ten minutes of play
+            frame_limit: 60 * 60 * 10,
+            policy: Policy::Scripted,
+            difficulty: Difficulty::NORMAL,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    pub min: f32,
+    pub median: f32,
+    pub p90: f32,
+    pub max: f32,
+    pub mean: f32,
+}
+
+impl Stats {
+    pub fn of(values: &[f32]) -> Self {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        Stats {
+            min: sorted[0],
+            median: nearest_rank(&sorted, 0.5),
+            p90: nearest_rank(&sorted, 0.9),
+            max: sorted[sorted.len() - 1],
+            mean: sorted.iter().sum::<f32>() / sorted.len() as f32,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "min {:.1} median {:.1} p90 {:.1} max {:.1} mean {:.1}",
+            self.min, self.median, self.p90, self.max, self.mean
+        )
+    }
+}
+
+fn nearest_rank<T: Copy>(sorted: &[T], fraction: f32) -> T {
+    let rank = (sorted.len() as f32 * fraction).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+//runs that lost their signal at the same spawn and cluster level
+#[derive(Debug, Clone, Copy)]
+pub struct Ending {
+    pub spawns: usize,
+    pub clusters: u32,
+    pub runs: usize,
+    pub median_score: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub config: SimConfig,
+    pub runs: Vec<RunResult>,
+}
+
+impl Report {
+    pub fn run(config: SimConfig) -> Self {
+        let runs = (config.first_seed..config.first_seed + config.games)
+            .map(|seed| play(seed, config.policy, config.difficulty, config.frame_limit))
+            .collect();
+        Report { config, runs }
+    }
+
+    fn ended(&self) -> impl Iterator<Item = &RunResult> {
+        self.runs
+            .iter()
+            .filter(|run| run.frames < self.config.frame_limit)
+    }
+
+    //equal width bins over the run totals, as (lowest score in the bin, runs)
+    pub fn histogram(&self) -> (u32, Vec<(u32, usize)>) {
+        let highest = self.runs.iter().map(|run| run.total()).max().unwrap_or(0);
+        let width = (highest + 1).div_ceil(HISTOGRAM_BINS).max(1);
+        let bins = (0..HISTOGRAM_BINS)
+            .map(|bin| {
+                let runs = self
+                    .runs
+                    .iter()
+                    .filter(|run| run.total() / width == bin)
+                    .count();
+                (bin * width, runs)
+            })
+            .collect();
+        (width, bins)
+    }
+
+    pub fn median_survival(&self) -> u64 {
+        let mut frames: Vec<u64> = self.runs.iter().map(|run| run.frames).collect();
+        frames.sort();
+        nearest_rank(&frames, 0.5)
+    }
+
+    //averaged over every run
+    pub fn losses(&self) -> SignalTotals {
+        let total = self
+            .runs
+            .iter()
+            .fold(SignalTotals::default(), |total, run| total.sum(&run.losses));
+        let runs = self.runs.len() as f32;
+        SignalTotals {
+            laser: total.laser / runs,
+            jumprope: total.jumprope / runs,
+            fragment: total.fragment / runs,
+            blast: total.blast / runs,
+            graze: total.graze / runs,
+            drain: total.drain / runs,
+            decay: total.decay / runs,
+            recovered: total.recovered / runs,
+        }
+    }
+
+    //runs stopped at the frame limit didn't end, so they are left out
+    pub fn endings(&self) -> Vec<Ending> {
+        let mut levels: Vec<(usize, u32)> =
+            self.ended().map(|run| (run.spawns, run.clusters)).collect();
+        levels.sort();
+        levels.dedup();
+        levels
+            .into_iter()
+            .map(|(spawns, clusters)| {
+                let mut scores: Vec<u32> = self
+                    .ended()
+                    .filter(|run| run.spawns == spawns && run.clusters == clusters)
+                    .map(|run| run.score)
+                    .collect();
+                scores.sort();
+                Ending {
+                    spawns,
+                    clusters,
+                    runs: scores.len(),
+                    median_score: nearest_rank(&scores, 0.5),
+                }
+            })
+            .collect()
+    }
+
+    fn loss_rows(&self) -> [(&'static str, f32); 8] {
+        let losses = self.losses();
+        [
+            ("laser", losses.laser),
+            ("jumprope", losses.jumprope),
+            ("fragment", losses.fragment),
+            ("blast", losses.blast),
+            ("graze", losses.graze),
+            ("drain", losses.drain),
+            ("decay", losses.decay),
+            ("recovered", losses.recovered),
+        ]
+    }
+
+    //hand written so the report doesn't pull in a serializer
+    pub fn to_json(&self) -> String {
+        let config = &self.config;
+        let difficulty = &config.difficulty;
+        let (width, bins) = self.histogram();
+        let histogram: Vec<String> = bins
+            .iter()
+            .map(|(from, runs)| {
+                format!(
+                    "{{\"from\": {from}, \"to\": {}, \"runs\": {runs}}}",
+                    from + width - 1
+                )
+            })
+            .collect();
+        let losses: Vec<String> = self
+            .loss_rows()
+            .iter()
+            .map(|(hazard, lost)| format!("\"{hazard}\": {lost:.4}"))
+            .collect();
+        let endings: Vec<String> = self
+            .endings()
+            .iter()
+            .map(|ending| {
+                format!(
+                    "{{\"spawns\": {}, \"clusters\": {}, \"runs\": {}, \"median_score\": {}}}",
+                    ending.spawns, ending.clusters, ending.runs, ending.median_score
+                )
+            })
+            .collect();
+        let runs: Vec<String> = self
+            .runs
+            .iter()
+            .map(|run| {
+                let breakdown: Vec<String> = run
+                    .breakdown
+                    .fields()
+                    .iter()
+                    .map(|(name, value)| format!("\"{name}\": {value}"))
+                    .collect();
+                format!(
+                    "{{\"seed\": {}, \"frames\": {}, \"score\": {}, \"total\": {}, \"spawns\": {}, \"clusters\": {}, \"breakdown\": {{{}}}}}",
+                    run.seed,
+                    run.frames,
+                    run.score,
+                    run.total(),
+                    run.spawns,
+                    run.clusters,
+                    breakdown.join(", ")
+                )
+            })
+            .collect();
+        let lines = [
+            "{".to_string(),
+            format!("  \"policy\": \"{}\",", config.policy.name()),
+            format!(
+                "  \"difficulty\": {{\"name\": \"{}\", \"laser_speed\": {}, \"jumprope_speed\": {}, \"spawns\": {}, \"speedup\": {}, \"cluster_start_score\": {}, \"cluster_increase_score\": {}}},",
+                difficulty.name,
+                difficulty.laser_speed,
+                difficulty.jumprope_speed,
+                difficulty.spawns,
+                difficulty.speedup,
+                difficulty.cluster_start_score,
+                difficulty.cluster_increase_score
+            ),
+            format!("  \"games\": {},", config.games),
+            format!("  \"first_seed\": {},", config.first_seed),
+            format!("  \"frame_limit\": {},", config.frame_limit),
+            format!(
+                "  \"reached_limit\": {},",
+                self.runs.len() - self.ended().count()
+            ),
+            format!("  \"median_survival_frames\": {},", self.median_survival()),
+            format!("  \"score_histogram\": [{}],", histogram.join(", ")),
+            format!("  \"signal_lost_per_run\": {{{}}},", losses.join(", ")),
+            format!("  \"endings\": [{}],", endings.join(", ")),
+            format!("  \"runs\": [\n    {}\n  ]", runs.join(",\n    ")),
+            "}".to_string(),
+        ];
+        lines.join("\n") + "\n"
+    }
+
+    //one table per section, separated by blank lines
+    pub fn to_csv(&self) -> String {
+        let config = &self.config;
+        let mut lines = vec![
+            "policy,difficulty,games,first_seed,frame_limit,reached_limit,median_survival_frames"
+                .to_string(),
+            format!(
+                "{},{},{},{},{},{},{}",
+                config.policy.name(),
+                config.difficulty.name,
+                config.games,
+                config.first_seed,
+                config.frame_limit,
+                self.runs.len() - self.ended().count(),
+                self.median_survival()
+            ),
+            String::new(),
+            "score_from,score_to,runs".to_string(),
+        ];
+        let (width, bins) = self.histogram();
+        for (from, runs) in bins {
+            lines.push(format!("{from},{},{runs}", from + width - 1));
+        }
+        lines.push(String::new());
+        lines.push("hazard,signal_lost_per_run".to_string());
+        for (hazard, lost) in self.loss_rows() {
+            lines.push(format!("{hazard},{lost:.4}"));
+        }
+        lines.push(String::new());
+        lines.push("end_spawns,end_clusters,runs,median_score".to_string());
+        for ending in self.endings() {
+            lines.push(format!(
+                "{},{},{},{}",
+                ending.spawns, ending.clusters, ending.runs, ending.median_score
+            ));
+        }
+        lines.push(String::new());
+        //the breakdown's own total is already in the total column
+        let breakdown = |run: &RunResult| -> Vec<String> {
+            let fields = run.breakdown.fields();
+            let (_, fields) = fields.split_last().unwrap();
+            fields.iter().map(|(_, value)| value.clone()).collect()
+        };
+        let names: Vec<&str> = ScoreBreakdown::default().fields()[..7]
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+        lines.push(format!(
+            "seed,frames,score,total,spawns,clusters,laser,jumprope,fragment,blast,graze,{}",
+            names.join(",")
+        ));
+        for run in self.runs.iter() {
+            let losses = run.losses;
+            lines.push(format!(
+                "{},{},{},{},{},{},{:.4},{:.4},{:.4},{:.4},{:.4},{}",
+                run.seed,
+                run.frames,
+                run.score,
+                run.total(),
+                run.spawns,
+                run.clusters,
+                losses.laser,
+                losses.jumprope,
+                losses.fragment,
+                losses.blast,
+                losses.graze,
+                breakdown(run).join(",")
+            ));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    //csv for a .csv path, json for anything else
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => self.to_csv(),
+            _ => self.to_json(),
+        };
+        fs::write(path, contents)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let config = &self.config;
+        let survival: Vec<f32> = self
+            .runs
+            .iter()
+            .map(|run| run.frames as f32 / FPS)
+            .collect();
+        let scores: Vec<f32> = self.runs.iter().map(|run| run.total() as f32).collect();
+        writeln!(
+            f,
+            "{} on {}, {} games from seed {} ({} reached the frame limit)",
+            config.policy.name(),
+            config.difficulty.name,
+            config.games,
+            config.first_seed,
+            self.runs.len() - self.ended().count()
+        )?;
+        writeln!(f, "  survival s  {}", Stats::of(&survival))?;
+        writeln!(f, "  median survival {} frames", self.median_survival())?;
+        writeln!(f, "  score       {}", Stats::of(&scores))?;
+        let losses: Vec<String> = self
+            .loss_rows()
+            .iter()
+            .filter(|(_, lost)| *lost != 0.0)
+            .map(|(hazard, lost)| format!("{hazard} {lost:.3}"))
+            .collect();
+        writeln!(f, "  signal lost per run: {}", losses.join(", "))?;
+        for ending in self.endings() {
+            writeln!(
+                f,
+                "  {} runs ended at {} spawns and {} clusters, median score {}",
+                ending.runs, ending.spawns, ending.clusters, ending.median_score
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(seed: u64, frames: u64, score: u32, total: u32, level: (usize, u32)) -> RunResult {
+        RunResult {
+            seed,
+            frames,
+            score,
+            breakdown: ScoreBreakdown {
+                delivery_points: total as f32,
+                ..Default::default()
+            },
+            spawns: level.0,
+            clusters: level.1,
+            losses: SignalTotals::default(),
+        }
+    }
+
+    //three runs that lost their signal and one stopped at the frame limit
+    fn report() -> Report {
+        let mut runs = vec![
+            run(0, 100, 2, 5, (2, 0)),
+            run(1, 300, 4, 25, (3, 1)),
+            run(2, 1000, 10, 99, (5, 2)),
+            run(3, 200, 3, 12, (2, 0)),
+        ];
+        runs[0].losses.laser = 0.5;
+        runs[1].losses.laser = 1.0;
+        runs[1].losses.graze = 0.2;
+        runs[2].losses.recovered = 1.5;
+        runs[3].losses.fragment = 0.3;
+        Report {
+            config: SimConfig {
+                games: 4,
+                frame_limit: 1000,
+                ..SimConfig::default()
+            },
+            runs,
+        }
+    }
+
+    #[test]
+    fn medians_are_nearest_rank() {
+        let report = report();
+        assert_eq!(report.median_survival(), 200);
+        let stats = Stats::of(&[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(
+            (stats.min, stats.median, stats.p90, stats.max, stats.mean),
+            (1.0, 2.0, 4.0, 4.0, 2.5)
+        );
+    }
+
+    #[test]
+    fn the_histogram_splits_totals_into_equal_bins() {
+        let (width, bins) = report().histogram();
+        assert_eq!(width, 10);
+        assert_eq!(bins.len(), HISTOGRAM_BINS as usize);
+        let counts: Vec<usize> = bins.iter().map(|&(_, runs)| runs).collect();
+        assert_eq!(counts, [1, 1, 1, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(bins[9].0, 90);
+    }
+
+    #[test]
+    fn losses_are_averaged_over_every_run() {
+        let losses = report().losses();
+        assert_eq!(
+            losses,
+            SignalTotals {
+                laser: 0.375,
+                graze: 0.05,
+                fragment: 0.075,
+                recovered: 0.375,
+                ..SignalTotals::default()
+            }
+        );
+    }
+
+    #[test]
+    fn endings_leave_out_runs_at_the_frame_limit() {
+        let endings: Vec<(usize, u32, usize, u32)> = report()
+            .endings()
+            .iter()
+            .map(|ending| {
+                (
+                    ending.spawns,
+                    ending.clusters,
+                    ending.runs,
+                    ending.median_score,
+                )
+            })
+            .collect();
+        assert_eq!(endings, [(2, 0, 2, 2), (3, 1, 1, 4)]);
+    }
+
+    #[test]
+    fn the_same_config_reports_the_same() {
+        let config = SimConfig {
+            games: 2,
+            first_seed: 5,
+            frame_limit: 600,
+            ..SimConfig::default()
+        };
+        assert_eq!(Report::run(config).to_json(), Report::run(config).to_json());
+    }
+
+    #[test]
+    fn each_run_record_carries_its_score_breakdown() {
+        let report = report();
+        let json = report.to_json();
+        assert!(json.contains("\"breakdown\": {\"deliveries\": 0"));
+        assert!(json.contains("\"delivery_points\": 99, \"combo_points\": 0"));
+        let csv = report.to_csv();
+        let header = csv.lines().find(|line| line.starts_with("seed,")).unwrap();
+        assert!(header.ends_with(",graze,deliveries,hits,best_combo,delivery_points,combo_points,graze_points,time_points"));
+        let columns = header.split(',').count();
+        let row = csv
+            .lines()
+            .find(|line| line.starts_with("2,1000,"))
+            .unwrap();
+        assert_eq!(row.split(',').count(), columns);
+        assert!(row.ends_with(",0,0,0,99,0,0,0"));
+    }
+}
@@ -0,0 +1,588 @@
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use softbuffer::{Context, Surface};
+use winit::event_loop::OwnedDisplayHandle;
+use winit::window::Window;
+
+use crate::game::Scene;
+use crate::maths::{Float2, Float4, float2_add, float2_subtract};
+use crate::portable::Renderer;
+use crate::profiler::{Phase, Profiler};
+use crate::scaling::ScaledView;
+use crate::vertex_t;
+
+//per frame values every fragment function reads, the uniforms of the gpu paths
+#[derive(Debug, Clone, Copy)]
+pub struct Shading {
+    //drawable pixels, like the fragment position
+    pub player: Float2,
+    pub radius: f32,
+    pub signal_lost: f32,
+}
+
+impl Shading {
+    pub fn new(scene: &Scene, view: ScaledView) -> Self {
+        Shading {
+            player: view.to_pixels(scene.player_view),
+            radius: scene.radius_pixels(view.scale),
+            signal_lost: scene.signal_lost,
+        }
+    }
+}
+
+//what a fragment function sees, position is the pixel center and uv.z the per quad data
+#[derive(Debug, Clone, Copy)]
+pub struct Fragment {
+    pub position: Float2,
+    pub color: Float4,
+    pub uv: Float4,
+}
+
+fn saturate(value: f32) -> f32 {
+    value.clamp(0.0, 1.0)
+}
+
+//msl sign, zero stays zero
+fn sign(value: f32) -> f32 {
+    if value == 0.0 { 0.0 } else { value.signum() }
+}
+
+fn mix(a: Float4, b: Float4, t: f32) -> Float4 {
+    Float4(
+        a.0 + (b.0 - a.0) * t,
+        a.1 + (b.1 - a.1) * t,
+        a.2 + (b.2 - a.2) * t,
+        a.3 + (b.3 - a.3) * t,
+    )
+}
+
+fn grayscale(color: Float4, alpha: f32) -> Float4 {
+    let luma = color.0 * 0.299 + 0.587 * color.1 + color.2 * 0.114;
+    Float4(luma, luma, luma, alpha)
+}
+
+//desaturates with distance from the player and with signal lost
+fn signal_fade(shading: &Shading, fragment: &Fragment) -> Float4 {
+    let distance = float2_subtract(shading.player, fragment.position).magnitude();
+    let t = saturate(distance / shading.radius + shading.signal_lost);
+    mix(
+        fragment.color,
+        grayscale(fragment.color, fragment.color.3),
+        t,
+    )
+}
+
+//a port of a fragment function in shaders.wgsl, None is a discard
+pub type FragmentFn = fn(&Shading, &Fragment) -> Option<Float4>;
+
+//FRAGMENTS pairs every fragment entry point in shaders.wgsl with the port below of the same name
+include!(concat!(env!("OUT_DIR"), "/fragments.rs"));
+
+pub fn fragment(name: &str) -> FragmentFn {
+    FRAGMENTS
+        .iter()
+        .find(|(entry, _)| *entry == name)
+        .map(|&(_, function)| function)
+        .unwrap_or_else(|| panic!("{name} is not a fragment function in shaders.wgsl"))
+}
+
+//the ports below match shaders.wgsl line for line, build.rs fails when a fragment or a helper it
+//calls changes without its port's fingerprint being updated to match, and the tests run every
+//port against what build.rs got evaluating the shader itself at the same inputs
+//ported from shaders.wgsl 25fe36e0
+pub fn box_fragment(shading: &Shading, fragment: &Fragment) -> Option<Float4> {
+    Some(signal_fade(shading, fragment))
+}
+
+//ported from shaders.wgsl ada46a03
+pub fn overlay_fragment(_: &Shading, fragment: &Fragment) -> Option<Float4> {
+    Some(fragment.color)
+}
+
+//ported from shaders.wgsl bb4025d6
+pub fn goal_fragment(_: &Shading, fragment: &Fragment) -> Option<Float4> {
+    let clamped_t = saturate(fragment.uv.2);
+    Some(mix(
+        fragment.color,
+        grayscale(fragment.color, 1.0),
+        clamped_t,
+    ))
+}
+
+//ported from shaders.wgsl f6d18035
+pub fn target_fragment(shading: &Shading, fragment: &Fragment) -> Option<Float4> {
+    let color_out = signal_fade(shading, fragment);
+    let coords = Float2((fragment.uv.0 - 0.5).abs(), (fragment.uv.1 - 0.5).abs());
+    let r = 0.06;
+    let w = 0.4;
+    let shift = (coords.0 + coords.1).min(w) * 0.5;
+    let d = Float2(coords.0 - shift, coords.1 - shift).magnitude() - r;
+    if d > 0.0 {
+        return None;
+    }
+    Some(color_out)
+}
+
+//ported from shaders.wgsl faa3987c
+pub fn telegraph_fragment(shading: &Shading, fragment: &Fragment) -> Option<Float4> {
+    let mut color_out = signal_fade(shading, fragment);
+    let d = Float2((fragment.uv.0 - 0.5) * 2.0, (fragment.uv.1 - 0.5) * 2.0).magnitude();
+    if d > 1.0 {
+        return None;
+    }
+    let fill = saturate(fragment.uv.2);
+    let edge = if d >= 0.92 { 1.0 } else { 0.0 };
+    let filled = if fill >= d { 1.0 } else { 0.0 };
+    color_out.3 *= f32::max(edge, f32::max(filled * 0.5, 0.12));
+    Some(color_out)
+}
+
+//t is how far the picked hue is from the goal's, carried in uv.z
+//ported from shaders.wgsl 0a15eeed
+pub fn scorezone_fragment(_: &Shading, fragment: &Fragment) -> Option<Float4> {
+    let coords0 = Float2(fragment.uv.0 - 0.5, fragment.uv.1 - 0.5);
+    let he = 1.2;
+    let ra = 0.2;
+    let coords = Float2(coords0.0.abs() * 2.0, coords0.1.abs() * 2.0);
+    let coords = Float2(
+        (coords.0 - coords.1).abs() / 2.0_f32.sqrt(),
+        (1.0 - coords.0 - coords.1) / 2.0_f32.sqrt(),
+    );
+
+    let p = (he - coords.1 - 0.25 / he) / (6.0 * he);
+    let q = coords.0 / (he * he * 16.0);
+    let h = q * q - p * p * p;
+    let x = if h > 0.0 {
+        let r = h.sqrt();
+        (q + r).powf(1.0 / 3.0) - (q - r).abs().powf(1.0 / 3.0) * sign(r - q)
+    } else {
+        let r = p.sqrt();
+        2.0 * r * ((q / (p * r)).acos() / 3.0).cos()
+    };
+    let x = x.min(2.0_f32.sqrt() / 2.0);
+
+    let z = Float2(x - coords.0, he * (1.0 - 2.0 * x * x) - coords.1);
+    let inner_d = z.magnitude() * sign(z.1) - ra;
+
+    let b = 0.06125;
+    let a = Float2((coords0.0 * 0.9).abs() - b, (coords0.1 * 0.9).abs() - b);
+    let d = Float2(a.0.max(0.0), a.1.max(0.0)).magnitude() + a.0.max(a.1).min(0.0);
+    if sign(inner_d) == 1.0 && sign(d) == 1.0 {
+        return None;
+    }
+    let clamped_t = saturate(fragment.uv.2);
+    Some(mix(
+        fragment.color,
+        grayscale(fragment.color, 0.0),
+        clamped_t,
+    ))
+}
+
+//an rgba float target with the same source alpha blending as the gpu pipelines
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Float4>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![Float4(0.0, 0.0, 0.0, 1.0); width * height],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pixels.fill(Float4(0.0, 0.0, 0.0, 1.0));
+    }
+
+    fn blend(&mut self, x: usize, y: usize, color: Float4) {
+        let pixel = &mut self.pixels[y * self.width + x];
+        let a = color.3;
+        *pixel = Float4(
+            color.0 * a + pixel.0 * (1.0 - a),
+            color.1 * a + pixel.1 * (1.0 - a),
+            color.2 * a + pixel.2 * (1.0 - a),
+            color.3 * a + pixel.3 * (1.0 - a),
+        );
+    }
+
+    //quads from build_rect are parallelograms, so uv comes straight from the two edge vectors
+    fn fill_quad(
+        &mut self,
+        quad: &[vertex_t],
+        view_projection: &[[f32; 4]; 4],
+        view: ScaledView,
+        shading: &Shading,
+        shader: FragmentFn,
+    ) {
+        let m = view_projection;
+        let to_pixels = |vert: &vertex_t| {
+            let (x, y) = (vert.position.0, vert.position.1);
+            let clip_x = m[0][0] * x + m[1][0] * y + m[3][0];
+            let clip_y = m[0][1] * x + m[1][1] * y + m[3][1];
+            Float2(
+                view.viewport.x + (clip_x + 1.0) / 2.0 * view.viewport.width,
+                view.viewport.y + (1.0 - clip_y) / 2.0 * view.viewport.height,
+            )
+        };
+        let origin = to_pixels(&quad[0]);
+        let edge_u = float2_subtract(to_pixels(&quad[1]), origin);
+        let edge_v = float2_subtract(to_pixels(&quad[2]), origin);
+        let determinant = edge_u.0 * edge_v.1 - edge_u.1 * edge_v.0;
+        if determinant.abs() < f32::EPSILON {
+            return;
+        }
+        let corners = [
+            origin,
+            float2_add(origin, edge_u),
+            float2_add(origin, edge_v),
+            float2_add(float2_add(origin, edge_u), edge_v),
+        ];
+        let (mut min, mut max) = (Float2(f32::MAX, f32::MAX), Float2(f32::MIN, f32::MIN));
+        for corner in corners {
+            min = Float2(min.0.min(corner.0), min.1.min(corner.1));
+            max = Float2(max.0.max(corner.0), max.1.max(corner.1));
+        }
+        //clipped to the viewport like clip space clipping on the gpu
+        let left = min.0.max(view.viewport.x).max(0.0).floor() as usize;
+        let top = min.1.max(view.viewport.y).max(0.0).floor() as usize;
+        let right =
+            (max.0.min(view.viewport.x + view.viewport.width).ceil() as usize).min(self.width);
+        let bottom =
+            (max.1.min(view.viewport.y + view.viewport.height).ceil() as usize).min(self.height);
+        let color = quad[0].color;
+        let data = quad[0].position.2;
+        for y in top..bottom {
+            for x in left..right {
+                let position = Float2(x as f32 + 0.5, y as f32 + 0.5);
+                let offset = float2_subtract(position, origin);
+                let u = (offset.0 * edge_v.1 - offset.1 * edge_v.0) / determinant;
+                let v = (edge_u.0 * offset.1 - edge_u.1 * offset.0) / determinant;
+                //half open so neighbouring quads never blend the same pixel twice
+                if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                    continue;
+                }
+                let fragment = Fragment {
+                    position,
+                    color,
+                    uv: Float4(u, v, data, 1.0),
+                };
+                if let Some(out) = shader(shading, &fragment) {
+                    self.blend(x, y, out);
+                }
+            }
+        }
+    }
+
+    //same order as the gpu frontends, telegraphs under boxes under goals under the overlay
+    pub fn draw(&mut self, scene: &Scene, view: ScaledView) {
+        let shading = Shading::new(scene, view);
+        let layers = [(fragment("telegraph_fragment"), &scene.telegraphs[..])]
+            .into_iter()
+            .chain(scene.boxes().map(|verts| (fragment("box_fragment"), verts)))
+            .chain([
+                (fragment("scorezone_fragment"), &scene.goals[..]),
+                (fragment("overlay_fragment"), &scene.overlay[..]),
+            ]);
+        for (shader, verts) in layers {
+            for quad in verts.chunks(4) {
+                self.fill_quad(quad, &scene.view_projection, view, &shading, shader);
+            }
+        }
+    }
+
+    //0rgb words, the layout softbuffer presents
+    pub fn write_rgb(&self, out: &mut [u32]) {
+        let channel = |value: f32| (saturate(value) * 255.0).round() as u32;
+        for (word, pixel) in out.iter_mut().zip(self.pixels.iter()) {
+            *word = channel(pixel.0) << 16 | channel(pixel.1) << 8 | channel(pixel.2);
+        }
+    }
+}
+
+//the fallback when no gpu adapter is available, everything rasterized on the cpu
+pub struct SoftwareRenderer {
+    surface: Surface<OwnedDisplayHandle, Arc<Window>>,
+    canvas: Canvas,
+}
+
+impl SoftwareRenderer {
+    pub fn new(window: Arc<Window>, display: OwnedDisplayHandle) -> Self {
+        let context = Context::new(display).expect("couldn't create software context");
+        let surface = Surface::new(&context, window).expect("couldn't create software surface");
+        SoftwareRenderer {
+            surface,
+            canvas: Canvas::new(1, 1),
+        }
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (width.max(1), height.max(1));
+        self.canvas = Canvas::new(width as usize, height as usize);
+        self.surface
+            .resize(
+                NonZeroU32::new(width).unwrap(),
+                NonZeroU32::new(height).unwrap(),
+            )
+            .expect("couldn't resize software surface");
+    }
+
+    fn render(&mut self, scene: &Scene, view: ScaledView, profiler: &mut Profiler) {
+        let span = profiler.start();
+        self.canvas.clear();
+        self.canvas.draw(scene, view);
+        profiler.record(Phase::Encode, span);
+        let span = profiler.start();
+        let Ok(mut buffer) = self.surface.buffer_mut() else {
+            return;
+        };
+        self.canvas.write_rgb(&mut buffer);
+        //a failed present just drops this frame
+        let _ = buffer.present();
+        profiler.record(Phase::Upload, span);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use hsv::hsv_to_rgb;
+
+    use super::*;
+    use crate::scaling::{ScalePolicy, fit};
+    use crate::{COLOR_STEPS, build_rect, color_convert, palette_t, stepped_hue};
+
+    const SIZE: usize = 48;
+    //per channel slack out of 255, libm differences move sdf edges and gradients a little
+    const TOLERANCE: u8 = 2;
+    //pixels allowed past the tolerance, an sdf edge pixel can flip either way
+    const MAX_OUTLIERS: usize = 4;
+    const IDENTITY: [[f32; 4]; 4] = [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ];
+
+    fn palette(index: u32) -> Float4 {
+        color_convert(hsv_to_rgb(
+            stepped_hue(palette_t(index, COLOR_STEPS), COLOR_STEPS),
+            1.0,
+            1.0,
+        ))
+    }
+
+    //player in the middle of the image
+    fn shading(signal_lost: f32) -> Shading {
+        Shading {
+            player: Float2(SIZE as f32 / 2.0, SIZE as f32 / 2.0),
+            radius: SIZE as f32 / 2.0,
+            signal_lost,
+        }
+    }
+
+    //one quad covering the whole image, z carrying the per quad data
+    fn render(shader: FragmentFn, shading: Shading, color: Float4, data: f32) -> Vec<u8> {
+        let mut quad = build_rect(0.0, 0.0, 2.0, 2.0, 0.0, color);
+        for vert in quad.iter_mut() {
+            vert.position.2 = data;
+        }
+        let view = fit(
+            Float2(1.0, 1.0),
+            Float2(SIZE as f32, SIZE as f32),
+            ScalePolicy::Letterbox,
+        );
+        let mut canvas = Canvas::new(SIZE, SIZE);
+        canvas.fill_quad(&quad, &IDENTITY, view, &shading, shader);
+        let mut words = vec![0; SIZE * SIZE];
+        canvas.write_rgb(&mut words);
+        words
+            .iter()
+            .flat_map(|word| [(word >> 16) as u8, (word >> 8) as u8, *word as u8])
+            .collect()
+    }
+
+    fn golden_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{name}.ppm"))
+    }
+
+    fn write_ppm(path: &PathBuf, rgb: &[u8]) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut file = format!("P6\n{SIZE} {SIZE}\n255\n").into_bytes();
+        file.extend_from_slice(rgb);
+        fs::write(path, file).unwrap();
+    }
+
+    //binary ppm with the header this file writes
+    fn read_ppm(path: &PathBuf) -> Option<Vec<u8>> {
+        let file = fs::read(path).ok()?;
+        let header = format!("P6\n{SIZE} {SIZE}\n255\n").into_bytes();
+        file.strip_prefix(&header[..]).map(|rgb| rgb.to_vec())
+    }
+
+    //UPDATE_GOLDEN=1 rewrites the reference, a mismatch leaves the actual image in target/golden
+    fn assert_golden(name: &str, rgb: &[u8]) {
+        let path = golden_path(name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            write_ppm(&path, rgb);
+            return;
+        }
+        let expected = read_ppm(&path).unwrap_or_else(|| {
+            panic!(
+                "no golden image at {}, run with UPDATE_GOLDEN=1",
+                path.display()
+            )
+        });
+        let outliers = expected
+            .chunks(3)
+            .zip(rgb.chunks(3))
+            .filter(|(expected, actual)| {
+                expected
+                    .iter()
+                    .zip(actual.iter())
+                    .any(|(e, a)| e.abs_diff(*a) > TOLERANCE)
+            })
+            .count();
+        if outliers > MAX_OUTLIERS {
+            let actual = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("target/golden")
+                .join(format!("{name}.ppm"));
+            write_ppm(&actual, rgb);
+            panic!(
+                "{name}: {outliers} pixels differ from {}, actual image written to {}",
+                path.display(),
+                actual.display()
+            );
+        }
+    }
+
+    #[test]
+    fn box_full_signal() {
+        let rgb = render(box_fragment, shading(0.0), palette(2), 0.0);
+        assert_golden("box_full_signal", &rgb);
+    }
+
+    #[test]
+    fn box_half_signal() {
+        let rgb = render(box_fragment, shading(0.5), palette(2), 0.0);
+        assert_golden("box_half_signal", &rgb);
+    }
+
+    #[test]
+    fn target_full_signal() {
+        let rgb = render(target_fragment, shading(0.0), palette(4), 0.0);
+        assert_golden("target_full_signal", &rgb);
+    }
+
+    #[test]
+    fn target_half_signal() {
+        let rgb = render(target_fragment, shading(0.5), palette(4), 0.0);
+        assert_golden("target_half_signal", &rgb);
+    }
+
+    #[test]
+    fn telegraph_half_filled() {
+        let rgb = render(telegraph_fragment, shading(0.0), palette(0), 0.5);
+        assert_golden("telegraph_half_filled", &rgb);
+    }
+
+    //the picked hue is the goal's, so it shows in full color
+    #[test]
+    fn scorezone_hue_match() {
+        let rgb = render(scorezone_fragment, shading(0.0), palette(5), 0.0);
+        assert_golden("scorezone_hue_match", &rgb);
+    }
+
+    //a carried goal stays drawn at its spot with its saturation taken away
+    #[test]
+    fn scorezone_carrying() {
+        let carried = color_convert(hsv_to_rgb(
+            stepped_hue(palette_t(5, COLOR_STEPS), COLOR_STEPS),
+            0.0,
+            1.0,
+        ));
+        let rgb = render(scorezone_fragment, shading(0.0), carried, 0.0);
+        assert_golden("scorezone_carrying", &rgb);
+    }
+
+    //half a palette step away greys it out and fades it, so it can't be picked up
+    #[test]
+    fn scorezone_pickup_hue_mismatch() {
+        let rgb = render(scorezone_fragment, shading(0.0), palette(5), 0.5);
+        assert_golden("scorezone_pickup_hue_mismatch", &rgb);
+    }
+
+    //evaluated from the naga ir by build.rs, FRAGMENT_INPUTS and FRAGMENT_OUTPUTS
+    mod shader {
+        include!(concat!(env!("OUT_DIR"), "/fragment_samples.rs"));
+    }
+
+    #[test]
+    fn every_port_matches_the_shader_it_was_ported_from() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4 || (a.is_nan() && b.is_nan());
+        assert_eq!(shader::FRAGMENT_OUTPUTS.len(), FRAGMENTS.len());
+        for (name, outputs) in shader::FRAGMENT_OUTPUTS.iter() {
+            let port = fragment(name);
+            for ((shading, input), expected) in shader::FRAGMENT_INPUTS.iter().zip(outputs.iter()) {
+                let [px, py, radius, signal_lost] = *shading;
+                let [x, y, r, g, b, a, u, v, data, w] = *input;
+                let shading = Shading {
+                    player: Float2(px, py),
+                    radius,
+                    signal_lost,
+                };
+                let fragment = Fragment {
+                    position: Float2(x, y),
+                    color: Float4(r, g, b, a),
+                    uv: Float4(u, v, data, w),
+                };
+                let actual = port(&shading, &fragment).map(|c| [c.0, c.1, c.2, c.3]);
+                let matches = match (actual, expected) {
+                    (Some(actual), Some(expected)) => actual
+                        .iter()
+                        .zip(expected.iter())
+                        .all(|(&a, &e)| close(a, e)),
+                    (None, None) => true,
+                    _ => false,
+                };
+                assert!(
+                    matches,
+                    "{name} at {shading:?} {fragment:?}: port gave {actual:?}, shader {expected:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn signal_fade_follows_the_shader_formula() {
+        let color = palette(2);
+        let luma = color.0 * 0.299 + 0.587 * color.1 + color.2 * 0.114;
+        let at = |position, signal_lost| {
+            let fragment = Fragment {
+                position,
+                color,
+                uv: Float4(0.5, 0.5, 0.0, 1.0),
+            };
+            box_fragment(&shading(signal_lost), &fragment).unwrap()
+        };
+        let center = Float2(SIZE as f32 / 2.0, SIZE as f32 / 2.0);
+        //full color on the player, grey from one radius out or once the signal is gone
+        assert_eq!(at(center, 0.0), color);
+        assert_eq!(at(Float2(center.0 + SIZE as f32, center.1), 0.0).0, luma);
+        assert_eq!(at(center, 1.0).1, luma);
+        //half way mixes half way
+        let half = at(center, 0.5);
+        assert!((half.0 - (color.0 + luma) / 2.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,468 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::COLOR_STEPS;
+use crate::maths::Float2;
+
+//lanes split the world height like the procedural lanes do
+const DEFAULT_LANES: usize = 5;
+
+//palette step from 0 to COLOR_STEPS - 1, None for a random palette hue
+pub type Hue = Option<u32>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lane {
+    Index(usize),
+    //the lane the player is in when the event fires
+    Player,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Point(Float2),
+    //where the player stands when the bomb is lobbed
+    Player,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpeedOf {
+    Laser,
+    Jumprope,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StageEvent {
+    Laser {
+        lane: Lane,
+        color: Hue,
+    },
+    Jumprope {
+        color: Hue,
+    },
+    //from None drops the bomb from the top edge straight above the target
+    Cluster {
+        target: Target,
+        from: Option<Float2>,
+        color: Hue,
+    },
+    Speed(SpeedOf, f32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+//a hand authored timeline that replaces the procedural spawners, see stages/ for the format
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stage {
+    pub name: String,
+    pub lanes: usize,
+    //seconds from the start of the stage, sorted
+    pub events: Vec<(f32, StageEvent)>,
+    //the timeline restarts after this many seconds, None plays it once
+    pub repeat: Option<f32>,
+}
+
+//the words of one line, consumed front to back
+struct Words<'a> {
+    words: std::iter::Peekable<std::str::SplitWhitespace<'a>>,
+}
+
+impl<'a> Words<'a> {
+    fn next(&mut self, what: &str) -> Result<&'a str, String> {
+        self.words.next().ok_or_else(|| format!("expected {what}"))
+    }
+    fn number(&mut self, what: &str) -> Result<f32, String> {
+        let word = self.next(what)?;
+        word.parse::<f32>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .ok_or_else(|| format!("{what} needs a number, got {word}"))
+    }
+    fn point(&mut self, what: &str) -> Result<Float2, String> {
+        Ok(Float2(self.number(what)?, self.number(what)?))
+    }
+    fn hue(&mut self) -> Result<Hue, String> {
+        match self.next("a palette step or random")? {
+            "random" => Ok(None),
+            word => match word.parse::<u32>() {
+                Ok(hue) if hue < COLOR_STEPS => Ok(Some(hue)),
+                _ => Err(format!(
+                    "color is a palette step from 0 to {} or random, got {word}",
+                    COLOR_STEPS - 1
+                )),
+            },
+        }
+    }
+    //optional trailing options, each a keyword and its value
+    fn options(
+        &mut self,
+        mut option: impl FnMut(&str, &mut Self) -> Result<(), String>,
+    ) -> Result<(), String> {
+        while let Some(word) = self.words.next() {
+            option(word, self)?;
+        }
+        Ok(())
+    }
+}
+
+impl Stage {
+    pub fn parse(text: &str) -> Result<Stage, Vec<ScriptError>> {
+        let mut stage = Stage {
+            name: String::from("untitled"),
+            lanes: DEFAULT_LANES,
+            events: Vec::new(),
+            repeat: None,
+        };
+        let mut errors = Vec::new();
+        //lane indices are checked once the lanes directive is known, wherever it is
+        let mut lane_lines = Vec::new();
+        let mut repeat_line = 0;
+        let mut time = 0.0;
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut words = Words {
+                words: line.split_whitespace().peekable(),
+            };
+            let result = (|| -> Result<(), String> {
+                let first = words.next("a time or a directive")?;
+                match first {
+                    "name" => {
+                        stage.name = line["name".len()..].trim().to_string();
+                        return Ok(());
+                    }
+                    "lanes" => {
+                        let lanes = words.number("lanes")?;
+                        if lanes < 1.0 || lanes.fract() != 0.0 {
+                            return Err("lanes needs a whole number above 0".to_string());
+                        }
+                        stage.lanes = lanes as usize;
+                    }
+                    "loop" => {
+                        let seconds = words.number("loop")?;
+                        if seconds <= 0.0 {
+                            return Err("loop needs a length above 0".to_string());
+                        }
+                        stage.repeat = Some(seconds);
+                        repeat_line = line_number;
+                    }
+                    _ => {
+                        //+seconds is relative to the event before
+                        let (relative, number) = match first.strip_prefix('+') {
+                            Some(rest) => (true, rest),
+                            None => (false, first),
+                        };
+                        let at = number
+                            .parse::<f32>()
+                            .ok()
+                            .filter(|at| at.is_finite() && *at >= 0.0)
+                            .ok_or_else(|| format!("unknown directive or bad time {first}"))?;
+                        time = if relative { time + at } else { at };
+                        let event = Self::event(&mut words)?;
+                        if let StageEvent::Laser {
+                            lane: Lane::Index(lane),
+                            ..
+                        } = event
+                        {
+                            lane_lines.push((line_number, lane));
+                        }
+                        stage.events.push((time, event));
+                    }
+                }
+                match words.words.next() {
+                    Some(extra) => Err(format!("unexpected {extra}")),
+                    None => Ok(()),
+                }
+            })();
+            if let Err(message) = result {
+                errors.push(ScriptError {
+                    line: line_number,
+                    message,
+                });
+            }
+        }
+        for (line, lane) in lane_lines {
+            if lane >= stage.lanes {
+                errors.push(ScriptError {
+                    line,
+                    message: format!("lane {lane} is past the last lane, {}", stage.lanes - 1),
+                });
+            }
+        }
+        stage.events.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if let (Some(repeat), Some((last, _))) = (stage.repeat, stage.events.last())
+            && *last >= repeat
+        {
+            errors.push(ScriptError {
+                line: repeat_line,
+                message: format!("loop of {repeat}s ends before the event at {last}s"),
+            });
+        }
+        errors.sort_by_key(|error| error.line);
+        if errors.is_empty() {
+            Ok(stage)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn event(words: &mut Words) -> Result<StageEvent, String> {
+        let mut color = None;
+        let event = match words.next("an event")? {
+            "laser" => {
+                if words.next("lane")? != "lane" {
+                    return Err("laser needs lane N or lane player".to_string());
+                }
+                let lane =
+                    match words.next("a lane")? {
+                        "player" => Lane::Player,
+                        word => Lane::Index(word.parse().map_err(|_| {
+                            format!("lane needs a whole number or player, got {word}")
+                        })?),
+                    };
+                words.options(|option, words| match option {
+                    "color" => {
+                        color = words.hue()?;
+                        Ok(())
+                    }
+                    _ => Err(format!("laser takes color, not {option}")),
+                })?;
+                StageEvent::Laser { lane, color }
+            }
+            "jumprope" => {
+                words.options(|option, words| match option {
+                    "color" => {
+                        color = words.hue()?;
+                        Ok(())
+                    }
+                    _ => Err(format!("jumprope takes color, not {option}")),
+                })?;
+                StageEvent::Jumprope { color }
+            }
+            "cluster" => {
+                if words.next("at")? != "at" {
+                    return Err("cluster needs at X Y or at player".to_string());
+                }
+                let target = match words.words.peek() {
+                    Some(&"player") => {
+                        words.words.next();
+                        Target::Player
+                    }
+                    _ => Target::Point(words.point("cluster target")?),
+                };
+                let mut from = None;
+                words.options(|option, words| match option {
+                    "color" => {
+                        color = words.hue()?;
+                        Ok(())
+                    }
+                    "from" => {
+                        from = Some(words.point("cluster start")?);
+                        Ok(())
+                    }
+                    _ => Err(format!("cluster takes color and from, not {option}")),
+                })?;
+                StageEvent::Cluster {
+                    target,
+                    from,
+                    color,
+                }
+            }
+            "speed" => {
+                let of = match words.next("laser or jumprope")? {
+                    "laser" => SpeedOf::Laser,
+                    "jumprope" => SpeedOf::Jumprope,
+                    other => return Err(format!("speed of laser or jumprope, not {other}")),
+                };
+                let speed = words.number("speed")?;
+                if speed <= 0.0 {
+                    return Err("speed needs to be above 0".to_string());
+                }
+                StageEvent::Speed(of, speed)
+            }
+            other => return Err(format!("unknown event {other}")),
+        };
+        Ok(event)
+    }
+
+    //parse errors come back with the file name in front
+    pub fn load(path: &Path) -> Result<Stage, Vec<String>> {
+        let name = path.display();
+        let text = fs::read_to_string(path).map_err(|error| vec![format!("{name}: {error}")])?;
+        Stage::parse(&text).map_err(|errors| {
+            errors
+                .iter()
+                .map(|error| format!("{name} {error}"))
+                .collect()
+        })
+    }
+}
+
+//a stage being played, hit-stop freezes its clock with the rest of the simulation
+#[derive(Debug, Clone)]
+pub struct StageRun {
+    pub stage: Stage,
+    pub time: f32,
+    next: usize,
+}
+
+impl StageRun {
+    pub fn new(stage: Stage) -> Self {
+        StageRun {
+            stage,
+            time: 0.0,
+            next: 0,
+        }
+    }
+
+    //events that came due this step, in script order
+    pub fn update(&mut self, delta_t: f32) -> Vec<StageEvent> {
+        self.time += delta_t;
+        let mut due = Vec::new();
+        while let Some(&(at, event)) = self.stage.events.get(self.next)
+            && at <= self.time
+        {
+            due.push(event);
+            self.next += 1;
+        }
+        if let Some(repeat) = self.stage.repeat
+            && self.time >= repeat
+        {
+            self.time -= repeat;
+            self.next = 0;
+        }
+        due
+    }
+
+    //every event has fired and the timeline doesn't loop
+    pub fn finished(&self) -> bool {
+        self.stage.repeat.is_none() && self.next == self.stage.events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_parse_into_a_sorted_timeline() {
+        let stage = Stage::parse(
+            "# two lasers then a rope\n\
+             name warm up\n\
+             lanes 4\n\
+             1 laser lane 3 color 2\n\
+             +0.5 laser lane player\n\
+             0.25 jumprope color random\n\
+             2 cluster at -100 50.5 from 0 700 color 6\n\
+             2 cluster at player\n\
+             3 speed laser 600\n",
+        )
+        .unwrap();
+        assert_eq!(stage.name, "warm up");
+        assert_eq!(stage.lanes, 4);
+        let times: Vec<f32> = stage.events.iter().map(|(at, _)| *at).collect();
+        assert_eq!(times, vec![0.25, 1.0, 1.5, 2.0, 2.0, 3.0]);
+        assert_eq!(
+            stage.events[1].1,
+            StageEvent::Laser {
+                lane: Lane::Index(3),
+                color: Some(2)
+            }
+        );
+        assert_eq!(
+            stage.events[3].1,
+            StageEvent::Cluster {
+                target: Target::Point(Float2(-100.0, 50.5)),
+                from: Some(Float2(0.0, 700.0)),
+                color: Some(6)
+            }
+        );
+        assert_eq!(stage.events[5].1, StageEvent::Speed(SpeedOf::Laser, 600.0));
+    }
+
+    #[test]
+    fn bad_lines_are_reported_by_number() {
+        let errors = Stage::parse(
+            "1 laser lane 9\n\
+             lanes 3\n\
+             -1 jumprope\n\
+             2 jumprope color 7\n\
+             3 boss\n\
+             4 cluster at 10\n\
+             5 speed laser fast\n\
+             6 jumprope color 1 twice\n\
+             loop 0.5\n",
+        )
+        .unwrap_err();
+        let lines: Vec<usize> = errors.iter().map(|error| error.line).collect();
+        assert_eq!(lines, vec![1, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(errors[3].to_string(), "line 5: unknown event boss");
+    }
+
+    #[test]
+    fn looping_stages_replay_and_one_shot_stages_finish() {
+        let stage = Stage::parse("0.5 jumprope\n1 laser lane 0\nloop 2").unwrap();
+        let mut run = StageRun::new(stage);
+        let fired: usize = (0..240).map(|_| run.update(1.0 / 60.0).len()).sum();
+        assert_eq!(fired, 4);
+        assert!(!run.finished());
+
+        let mut run = StageRun::new(Stage::parse("0.5 jumprope").unwrap());
+        run.update(0.25);
+        assert!(!run.finished());
+        assert_eq!(run.update(0.25).len(), 1);
+        assert!(run.finished());
+    }
+
+    #[test]
+    fn played_stages_replace_the_spawners_and_clear() {
+        use crate::entities::HazardKind;
+        use crate::game::{Difficulty, Game};
+        use crate::input::Input;
+
+        let mut game = Game::with_difficulty(Difficulty::NORMAL);
+        game.verbose = false;
+        game.debug.god = true;
+        game.score = 50;
+        game.play_stage(
+            Stage::parse("0.5 laser lane player color 1\n0.5 speed laser 900").unwrap(),
+        );
+        let input = Input::default();
+        game.step(&input);
+        assert!(game.world.entities().is_empty() && game.clusters.is_empty());
+        for _ in 0..30 {
+            game.step(&input);
+        }
+        assert_eq!(game.world.count(HazardKind::Laser), 1);
+        assert_eq!(game.laser_speed, 900.0);
+        assert!(game.boss.is_none());
+        let mut frames = 0;
+        while !game.over() && frames < 600 {
+            game.step(&input);
+            frames += 1;
+        }
+        assert!(game.stage_cleared());
+    }
+
+    #[test]
+    fn bundled_stages_parse() {
+        for script in [include_str!("../stages/crossfire.txt")] {
+            if let Err(errors) = Stage::parse(script) {
+                panic!("{errors:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::game::Difficulty;
+use crate::signal::SignalWeights;
+
+//the file is checked for changes this often, in simulated frames
+const CHECK_FRAMES: u32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bound {
+    Positive,
+    NonNegative,
+}
+
+//values the game reads every frame, the file wins over the difficulty's starting speeds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tuning {
+    pub player_speed: f32,
+    //starting hazard speeds, deliveries still speed them up from here
+    pub laser_speed: f32,
+    pub jumprope_speed: f32,
+    //per frame, like the fragment patterns
+    pub cluster_frag_speed: f32,
+    pub particle_width: f32,
+    pub signal: SignalWeights,
+}
+
+impl Tuning {
+    pub fn for_difficulty(difficulty: Difficulty) -> Self {
+        Tuning {
+            player_speed: 600.0,
+            laser_speed: difficulty.laser_speed,
+            jumprope_speed: difficulty.jumprope_speed,
+            cluster_frag_speed: 150.0,
+            particle_width: 10.0,
+            signal: SignalWeights::default(),
+        }
+    }
+
+    //every key the file understands, in the order it is written out
+    fn fields(&mut self) -> [(&'static str, &mut f32, Bound); 16] {
+        let signal = &mut self.signal;
+        [
+            ("player_speed", &mut self.player_speed, Bound::Positive),
+            ("laser_speed", &mut self.laser_speed, Bound::Positive),
+            ("jumprope_speed", &mut self.jumprope_speed, Bound::Positive),
+            (
+                "cluster_frag_speed",
+                &mut self.cluster_frag_speed,
+                Bound::Positive,
+            ),
+            ("particle_width", &mut self.particle_width, Bound::Positive),
+            ("laser_hit", &mut signal.laser_hit, Bound::NonNegative),
+            ("jumprope_hit", &mut signal.jumprope_hit, Bound::NonNegative),
+            ("fragment_hit", &mut signal.fragment_hit, Bound::NonNegative),
+            ("blast_hit", &mut signal.blast_hit, Bound::NonNegative),
+            ("graze", &mut signal.graze, Bound::NonNegative),
+            ("deliver", &mut signal.deliver, Bound::NonNegative),
+            (
+                "drain_per_second",
+                &mut signal.drain_per_second,
+                Bound::NonNegative,
+            ),
+            (
+                "decay_per_second",
+                &mut signal.decay_per_second,
+                Bound::NonNegative,
+            ),
+            (
+                "jumprope_graze_radius",
+                &mut signal.jumprope_graze_radius,
+                Bound::NonNegative,
+            ),
+            (
+                "drain_radius_per_second",
+                &mut signal.drain_radius_per_second,
+                Bound::NonNegative,
+            ),
+            (
+                "invulnerability",
+                &mut signal.invulnerability,
+                Bound::NonNegative,
+            ),
+        ]
+    }
+
+    //key = value lines, # starts a comment, keys left out keep the values passed in
+    pub fn parse(text: &str, base: Tuning) -> Result<Tuning, Vec<TuningError>> {
+        let mut tuning = base;
+        let mut errors = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let mut error = |message: String| {
+                errors.push(TuningError {
+                    line: line_number,
+                    message,
+                })
+            };
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                error(format!("expected key = value, got {line}"));
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let mut fields = tuning.fields();
+            let Some((_, field, bound)) = fields.iter_mut().find(|(name, _, _)| *name == key)
+            else {
+                error(format!("unknown key {key}"));
+                continue;
+            };
+            let number = match value.parse::<f32>() {
+                Ok(number) if number.is_finite() => number,
+                _ => {
+                    error(format!("{key} needs a number, got {value}"));
+                    continue;
+                }
+            };
+            match bound {
+                Bound::Positive if number <= 0.0 => {
+                    error(format!("{key} must be above 0"));
+                }
+                Bound::NonNegative if number < 0.0 => {
+                    error(format!("{key} can't be negative"));
+                }
+                _ => **field = number,
+            }
+        }
+        if errors.is_empty() {
+            Ok(tuning)
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn file_contents(&self) -> String {
+        let mut copy = *self;
+        let mut text = String::from(
+            "# lost signal tuning, saved edits apply while the game runs\n\
+             # hazard speeds are where a run starts, deliveries speed them up from there\n",
+        );
+        for (name, value, _) in copy.fields() {
+            text.push_str(&format!("{name} = {value}\n"));
+        }
+        text
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TuningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+//polls a tuning file's modified time, no reload until it changes again
+#[derive(Debug, Clone)]
+pub struct TuningFile {
+    pub path: PathBuf,
+    modified: Option<SystemTime>,
+    countdown: u32,
+}
+
+impl TuningFile {
+    //writes the current values out first when the file doesn't exist yet
+    pub fn open(path: &Path, tuning: &Tuning) -> io::Result<Self> {
+        if !path.exists() {
+            fs::write(path, tuning.file_contents())?;
+        }
+        Ok(TuningFile {
+            path: path.to_path_buf(),
+            modified: None,
+            countdown: 0,
+        })
+    }
+
+    //called every frame, Some once the file has changed since it was last read
+    pub fn poll(&mut self, base: Tuning) -> Option<Result<Tuning, Vec<String>>> {
+        if self.countdown > 0 {
+            self.countdown -= 1;
+            return None;
+        }
+        self.countdown = CHECK_FRAMES;
+        let modified = fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        let name = self.path.display();
+        Some(match fs::read_to_string(&self.path) {
+            Ok(text) => Tuning::parse(&text, base).map_err(|errors| {
+                errors
+                    .iter()
+                    .map(|error| format!("{name} {error}"))
+                    .collect()
+            }),
+            Err(error) => Err(vec![format!("couldn't read {name}: {error}")]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_files_parse_back_to_the_same_values() {
+        let tuning = Tuning::for_difficulty(Difficulty::HARD);
+        let base = Tuning::for_difficulty(Difficulty::EASY);
+        assert_eq!(Tuning::parse(&tuning.file_contents(), base), Ok(tuning));
+    }
+
+    #[test]
+    fn edits_change_only_their_keys() {
+        let base = Tuning::for_difficulty(Difficulty::NORMAL);
+        let tuning =
+            Tuning::parse("# faster\nplayer_speed = 750 # was 600\n\ngraze=0.01", base).unwrap();
+        assert_eq!(tuning.player_speed, 750.0);
+        assert_eq!(tuning.signal.graze, 0.01);
+        assert_eq!(tuning.laser_speed, base.laser_speed);
+    }
+
+    #[test]
+    fn bad_lines_are_reported_by_number() {
+        let base = Tuning::for_difficulty(Difficulty::NORMAL);
+        let errors = Tuning::parse(
+            "player_speed = 700\nlaser_speed = fast\nteleport = 1\nparticle_width = 0\nlaser_hit -1\ngraze = -0.1",
+            base,
+        )
+        .unwrap_err();
+        let lines: Vec<usize> = errors.iter().map(|error| error.line).collect();
+        assert_eq!(lines, vec![2, 3, 4, 5, 6]);
+        assert_eq!(errors[1].to_string(), "line 3: unknown key teleport");
+    }
+
+    #[test]
+    fn the_file_is_only_read_again_after_it_changes() {
+        let path =
+            std::env::temp_dir().join(format!("lost-signal-tuning-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let base = Tuning::for_difficulty(Difficulty::NORMAL);
+        let mut file = TuningFile::open(&path, &base).unwrap();
+        assert_eq!(file.poll(base), Some(Ok(base)));
+        for _ in 0..CHECK_FRAMES * 2 {
+            assert_eq!(file.poll(base), None);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,247 @@
+use crate::game::Game;
+use crate::hud;
+use crate::maths::{Float2, Float4};
+use crate::signal::{HitSource, SignalEvent};
+use crate::stage::Stage;
+use crate::{stepped_hue, vertex_t};
+
+//prompt text, a bit larger than the debug text so it reads from across the room
+const FONT_PIXEL: f32 = 5.0;
+const TEXT_COLOR: Float4 = Float4(1.0, 1.0, 1.0, 1.0);
+const DONE_COLOR: Float4 = Float4(0.4, 1.0, 0.5, 1.0);
+const PANEL_COLOR: Float4 = Float4(0.0, 0.0, 0.0, 0.6);
+//the prompt turns green this long before the next step starts
+const DONE_SECONDS: f32 = 1.0;
+
+//what finishes a step, judged from the game after every simulation step
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Check {
+    //this many different palette hues picked
+    Hues(usize),
+    //same color contact with a hazard of this kind
+    Graze(HitSource),
+    Pickup,
+    Deliver,
+    Wait(f32),
+}
+
+struct Step {
+    prompt: &'static [&'static str],
+    //a stage script, looping ones keep the lesson going until the check passes
+    script: &'static str,
+    check: Check,
+}
+
+//hue first, then the goal, then one hazard at a time, all on the same rule
+const STEPS: [Step; 7] = [
+    Step {
+        prompt: &[
+            "move the mouse left and right to change your color",
+            "move with w a s d",
+        ],
+        script: "",
+        check: Check::Hues(4),
+    },
+    Step {
+        prompt: &[
+            "hazards in your own color are safe to touch",
+            "match a laser and let it pass through you",
+        ],
+        script: "0.5 laser lane player color 1\n1.5 laser lane player color 4\nloop 2",
+        check: Check::Graze(HitSource::Laser),
+    },
+    Step {
+        prompt: &[
+            "goals only open to their own color",
+            "match the goal and touch it to pick it up",
+        ],
+        script: "",
+        check: Check::Pickup,
+    },
+    Step {
+        prompt: &["carry it into the delivery zone"],
+        script: "",
+        check: Check::Deliver,
+    },
+    Step {
+        prompt: &[
+            "jumpropes sweep the whole world",
+            "match one as it passes over you",
+        ],
+        script: "0.5 jumprope color 2\nloop 4",
+        check: Check::Graze(HitSource::Jumprope),
+    },
+    Step {
+        prompt: &[
+            "cluster bombs burst where the ring fills",
+            "match one and stand in its fragments",
+        ],
+        script: "0.5 cluster at player color 5\nloop 3",
+        check: Check::Graze(HitSource::Fragment),
+    },
+    Step {
+        prompt: &[
+            "your color is safe, every other color costs signal",
+            "tutorial complete",
+        ],
+        script: "",
+        check: Check::Wait(3.0),
+    },
+];
+
+//a guided run, each step swaps in its own stage and the signal can't be lost while it plays
+#[derive(Debug, Clone)]
+pub struct Tutorial {
+    step: usize,
+    //signal clock when the step started, grazes before it don't count
+    started: f32,
+    time: f32,
+    hues: Vec<f64>,
+    deliveries: u32,
+    //time since the check passed, None while the step is still going
+    done: Option<f32>,
+}
+
+impl Tutorial {
+    //starts the first step, the game keeps running the tutorial until finished
+    pub fn start(game: &mut Game) -> Self {
+        let tutorial = Tutorial {
+            step: 0,
+            started: game.signal.time(),
+            time: 0.0,
+            hues: Vec::new(),
+            deliveries: game.deliveries,
+            done: None,
+        };
+        game.play_stage(tutorial.stage());
+        tutorial
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::parse(STEPS[self.step].script).expect("tutorial scripts parse")
+    }
+
+    pub fn finished(&self) -> bool {
+        self.step == STEPS.len()
+    }
+
+    fn passed(&mut self, game: &Game) -> bool {
+        let hue = stepped_hue(game.hue, game.palette_steps());
+        if !self.hues.contains(&hue) {
+            self.hues.push(hue);
+        }
+        match STEPS[self.step].check {
+            Check::Hues(count) => self.hues.len() >= count,
+            Check::Graze(source) => game.signal.log().any(|entry| {
+                entry.time >= self.started && entry.event == SignalEvent::Graze(source)
+            }),
+            //a delivery made early counts as a pickup too
+            Check::Pickup => game.carrying.is_some() || game.deliveries > self.deliveries,
+            Check::Deliver => game.deliveries > self.deliveries,
+            Check::Wait(seconds) => self.time >= seconds,
+        }
+    }
+
+    //called once per simulation step before the stage fires its events
+    pub fn update(&mut self, game: &mut Game, delta_t: f32) {
+        if self.finished() {
+            return;
+        }
+        if game.signal.is_lost() {
+            game.signal.set_lost(0.0);
+        }
+        self.time += delta_t;
+        if let Some(done) = self.done.as_mut() {
+            *done += delta_t;
+            if *done < DONE_SECONDS {
+                return;
+            }
+            self.step += 1;
+            self.started = game.signal.time();
+            self.time = 0.0;
+            self.hues.clear();
+            self.deliveries = game.deliveries;
+            self.done = None;
+            if !self.finished() {
+                game.play_stage(self.stage());
+            }
+        } else if self.passed(game) {
+            self.done = Some(0.0);
+        }
+    }
+
+    //the current prompt and step count, centered near the bottom of the view
+    pub fn prompt(&self, viewport: Float2) -> Vec<vertex_t> {
+        let mut out = Vec::new();
+        let Some(step) = STEPS.get(self.step) else {
+            return out;
+        };
+        let mut lines = vec![format!("{}/{}", self.step + 1, STEPS.len())];
+        lines.extend(step.prompt.iter().map(|line| line.to_string()));
+        if let Check::Hues(count) = step.check {
+            lines.push(format!("colors {}/{count}", self.hues.len().min(count)));
+        }
+        let width = lines
+            .iter()
+            .map(|line| hud::text_width(line, FONT_PIXEL))
+            .fold(0.0, f32::max);
+        let height = (lines.len() as f32 * hud::LINE_HEIGHT + 4.0) * FONT_PIXEL;
+        let color = if self.done.is_some() {
+            DONE_COLOR
+        } else {
+            TEXT_COLOR
+        };
+        hud::text_block(
+            &mut out,
+            &lines,
+            Float2(-width / 2.0, -viewport.1 * 0.6 + height),
+            FONT_PIXEL,
+            color,
+            PANEL_COLOR,
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Difficulty;
+    use crate::input::Input;
+
+    #[test]
+    fn every_step_script_parses() {
+        for step in STEPS.iter() {
+            assert!(Stage::parse(step.script).is_ok(), "{}", step.script);
+        }
+    }
+
+    #[test]
+    fn steps_advance_only_once_they_are_done() {
+        let mut game = Game::with_difficulty(Difficulty::NORMAL);
+        game.verbose = false;
+        game.play_tutorial();
+        let mut input = Input::default();
+        for _ in 0..120 {
+            game.step(&input);
+        }
+        assert_eq!(game.tutorial.as_ref().unwrap().step, 0);
+        assert!(game.world.entities().is_empty());
+        for hue in [0.0, 0.3, 0.6, 0.9] {
+            input.set_hue(hue);
+            game.step(&input);
+        }
+        for _ in 0..90 {
+            game.step(&input);
+        }
+        assert_eq!(game.tutorial.as_ref().unwrap().step, 1);
+        //the laser step spawns in the player's lane and a lost signal is refilled
+        game.signal.set_lost(10.0);
+        for _ in 0..60 {
+            game.step(&input);
+        }
+        assert!(!game.world.entities().is_empty());
+        assert!(!game.over());
+        assert!(!game.signal.is_lost());
+    }
+}
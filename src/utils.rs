@@ -2,9 +2,12 @@ use std::{ffi::{c_char, CString}, mem, ptr::NonNull};
 use metal::*;
 
 use objc2::rc::Retained;
-use objc2_app_kit::{NSApplication, NSApplicationActivationPolicy, NSBackingStoreType, NSColor, NSScreen, NSWindow, NSWindowStyleMask};
+use objc2_app_kit::{NSApplication, NSApplicationActivationPolicy, NSBackingStoreType, NSColor, NSScreen, NSWindow, NSWindowCollectionBehavior, NSWindowStyleMask};
 use objc2_foundation::{CGPoint, MainThreadMarker, NSDate, NSRect, NSSize, NSString};
 
+use crate::maths::Float4;
+use crate::vertex_t;
+
 
 //Metal utils
 
@@ -31,9 +34,10 @@ pub fn new_metal_layer(device: &DeviceRef) -> MetalLayer {
     return layer;
 }
 
+//generated from src/shaders.wgsl by build.rs, which has already validated it
 pub fn get_library(device : &DeviceRef) -> Library {
-    let library_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/shaders.metallib");
-    device.new_library_with_file(library_path).expect("Library not found")
+    let source = include_str!(concat!(env!("OUT_DIR"), "/shaders.metal"));
+    device.new_library_with_source(source, &CompileOptions::new()).expect("generated metal source failed to compile")
 }
 
 pub fn prepare_pipeline_state (
@@ -49,6 +53,17 @@ pub fn prepare_pipeline_state (
     let pipeline_state_descriptor = RenderPipelineDescriptor::new();
     pipeline_state_descriptor.set_vertex_function(Some(&vert));
     pipeline_state_descriptor.set_fragment_function(Some(&frag));
+
+    //vertex_t arrives as stage_in attributes from buffer 1, the uniforms take buffer 0
+    let vertex_descriptor = VertexDescriptor::new();
+    for (index, offset) in [(0, 0), (1, mem::size_of::<Float4>())] {
+        let attribute = vertex_descriptor.attributes().object_at(index).unwrap();
+        attribute.set_format(MTLVertexFormat::Float4);
+        attribute.set_offset(offset as u64);
+        attribute.set_buffer_index(1);
+    }
+    vertex_descriptor.layouts().object_at(1).unwrap().set_stride(mem::size_of::<vertex_t>() as u64);
+    pipeline_state_descriptor.set_vertex_descriptor(Some(vertex_descriptor));
     // pipeline_state_descriptor.set_depth_attachment_pixel_format(MTLPixelFormat::Depth32Float);
 
     let pipeline_attachment = pipeline_state_descriptor
@@ -80,7 +95,8 @@ pub fn make_buf<T>(data : &Vec<T>, device : &DeviceRef) -> Buffer {
     )
 }
 
-pub fn copy_to_buf<T>(data : &Vec<T>, dst : &Buffer) {
+pub fn copy_to_buf<T>(data : &[T], dst : &Buffer) {
+    assert!(size_of_val(data) as u64 <= dst.length(), "{} bytes don't fit a {} byte buffer", size_of_val(data), dst.length());
     let buf_pointer = dst.contents(); //how does this grab a mut pointer from a non mutable reference?
     unsafe {
         std::ptr::copy(
@@ -91,10 +107,22 @@ pub fn copy_to_buf<T>(data : &Vec<T>, dst : &Buffer) {
     }
     dst.did_modify_range(NSRange::new(
         0 as u64,
-        (data.len() * size_of::<T>()) as u64
+        size_of_val(data) as u64
     ));
 }
 
+//reallocates dst at the next power of two when data no longer fits, then copies
+pub fn grow_to_buf<T>(data : &[T], dst : &mut Buffer, device : &DeviceRef) {
+    let size = size_of_val(data) as u64;
+    if size > dst.length() {
+        *dst = device.new_buffer(
+            size.next_power_of_two(),
+            MTLResourceOptions::CPUCacheModeDefaultCache | MTLResourceOptions::StorageModeManaged
+        );
+    }
+    copy_to_buf(data, dst);
+}
+
 // pub fn prepare_compute_state(device: &DeviceRef) {
 //     let descriptor = ComputePassDescriptor::new();
 //     descriptor.set_dispatch_type(MTLDis);
@@ -154,6 +182,26 @@ pub fn set_window_layer(window : &Retained<NSWindow>, layer: &MetalLayer) {
     }
 }
 
+//keeps the layer at the window's backing scale, returns the drawable size in pixels
+//the layer resizes its drawable with the view, only the scale has to be pushed when it changes
+pub fn sync_drawable_size(window : &NSWindow, layer : &MetalLayer) -> (f64, f64) {
+    let backing_scale = window.backingScaleFactor();
+    if layer.contents_scale() != backing_scale {
+        layer.set_contents_scale(backing_scale);
+    }
+    let size = layer.drawable_size();
+    if size.width > 0.0 && size.height > 0.0 {
+        return (size.width, size.height);
+    }
+    //before the first layout the drawable can still be empty, fall back to the view
+    let view_size = window.contentView().map(|view| view.bounds().size).unwrap_or(NSSize{ width: 0.0, height: 0.0 });
+    (view_size.width * backing_scale, view_size.height * backing_scale)
+}
+
+pub fn toggle_fullscreen(window : &NSWindow) {
+    window.toggleFullScreen(None);
+}
+
 pub fn get_next_frame (fps : f64) -> Retained<NSDate> {
     unsafe {
         NSDate::dateWithTimeIntervalSinceNow(1.0 / fps)
@@ -167,9 +215,15 @@ pub fn simple_app (width : f64, height : f64, title : &str) -> (Retained<NSAppli
 
     let style_mask =
         NSWindowStyleMask::Titled.union(
-        NSWindowStyleMask::Closable);
+        NSWindowStyleMask::Closable).union(
+        NSWindowStyleMask::Miniaturizable).union(
+        NSWindowStyleMask::Resizable);
 
     let window = initialize_window(width, height, (1.0, 1.0, 1.0, 1.0), title, style_mask, mtm);
+    unsafe {
+        window.setCollectionBehavior(NSWindowCollectionBehavior::FullScreenPrimary);
+        window.setContentMinSize(NSSize{ width: width * 0.5, height: height * 0.5 });
+    }
     let device = Device::system_default().expect("Error getting GPU device");
 
     let layer = new_metal_layer(&device);
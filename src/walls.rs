@@ -0,0 +1,128 @@
+use crate::maths::{Float2, Float4, apply_rotation_float2, dot, float2_subtract, scale2};
+use crate::{build_rect, vertex_t};
+
+const MIRROR_COLOR: Float4 = Float4(0.85, 0.9, 1.0, 1.0);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WallKind {
+    //bounces lasers and fragments off its long face
+    Mirror,
+    //stops hazards of any other color, matching ones pass straight through
+    Colored(Float4),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Wall {
+    pub position: Float2,
+    pub width: f32,
+    pub height: f32,
+    pub rotation: f32,
+    pub kind: WallKind,
+}
+
+impl Wall {
+    pub fn new(position: Float2, width: f32, height: f32, rotation: f32, kind: WallKind) -> Self {
+        Wall {
+            position,
+            width,
+            height,
+            rotation,
+            kind,
+        }
+    }
+    pub fn color(&self) -> Float4 {
+        match self.kind {
+            WallKind::Mirror => MIRROR_COLOR,
+            WallKind::Colored(color) => color,
+        }
+    }
+    pub fn rect(&self) -> Vec<vertex_t> {
+        build_rect(
+            self.position.0,
+            self.position.1,
+            self.width,
+            self.height,
+            self.rotation,
+            self.color(),
+        )
+    }
+    //normal of the long face, pointing to the side the point is on
+    pub fn normal_towards(&self, point: Float2) -> Float2 {
+        let normal = apply_rotation_float2(Float2(1.0, 0.0), self.rotation);
+        if dot(normal, float2_subtract(point, self.position)) < 0.0 {
+            scale2(normal, -1.0)
+        } else {
+            normal
+        }
+    }
+    pub fn blocks(&self, color: Float4) -> bool {
+        match self.kind {
+            WallKind::Mirror => false,
+            WallKind::Colored(wall_color) => wall_color != color,
+        }
+    }
+}
+
+pub fn reflect(v: Float2, normal: Float2) -> Float2 {
+    float2_subtract(v, scale2(normal, 2.0 * dot(v, normal)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_1_SQRT_2, PI};
+
+    use super::*;
+
+    const RED: Float4 = Float4(1.0, 0.0, 0.0, 1.0);
+    const BLUE: Float4 = Float4(0.0, 0.0, 1.0, 1.0);
+
+    fn close(a: Float2, b: Float2) -> bool {
+        float2_subtract(a, b).magnitude() < 1e-5
+    }
+
+    #[test]
+    fn reflection_mirrors_the_angle_of_incidence() {
+        assert!(close(
+            reflect(Float2(1.0, -1.0), Float2(0.0, 1.0)),
+            Float2(1.0, 1.0)
+        ));
+        //a mirror at 45 degrees turns a rightward shot straight up
+        let diagonal = Float2(-FRAC_1_SQRT_2, FRAC_1_SQRT_2);
+        assert!(close(reflect(Float2(1.0, 0.0), diagonal), Float2(0.0, 1.0)));
+        let incoming = Float2(3.0, -4.0);
+        let outgoing = reflect(incoming, diagonal);
+        assert!((outgoing.magnitude() - incoming.magnitude()).abs() < 1e-5);
+        assert!((dot(outgoing, diagonal) + dot(incoming, diagonal)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn normals_point_to_the_side_of_the_point() {
+        let wall = Wall::new(Float2(100.0, 0.0), 10.0, 200.0, 0.0, WallKind::Mirror);
+        assert!(close(
+            wall.normal_towards(Float2(150.0, 20.0)),
+            Float2(1.0, 0.0)
+        ));
+        assert!(close(
+            wall.normal_towards(Float2(50.0, -20.0)),
+            Float2(-1.0, 0.0)
+        ));
+        //a turned wall turns its normal, still off the long face
+        let turned = Wall::new(Float2(0.0, 0.0), 10.0, 200.0, PI / 2.0, WallKind::Mirror);
+        for point in [Float2(0.0, 50.0), Float2(0.0, -50.0)] {
+            let normal = turned.normal_towards(point);
+            assert!((normal.magnitude() - 1.0).abs() < 1e-5);
+            assert!(normal.0.abs() < 1e-5);
+            assert!(dot(normal, point) > 0.0);
+        }
+    }
+
+    #[test]
+    fn colored_walls_block_every_other_color() {
+        let mirror = Wall::new(Float2(0.0, 0.0), 10.0, 100.0, 0.0, WallKind::Mirror);
+        let red = Wall::new(Float2(0.0, 0.0), 10.0, 100.0, 0.0, WallKind::Colored(RED));
+        assert!(!mirror.blocks(RED) && !mirror.blocks(BLUE));
+        assert!(red.blocks(BLUE));
+        assert!(!red.blocks(RED));
+        assert_eq!(red.color(), RED);
+    }
+}